@@ -0,0 +1,51 @@
+//! Throughput of SET over a local connection, one request per round trip against pipelines of
+//! `PIPELINE` requests. Run with `cargo bench --bench pipeline`.
+
+use anyhow::Result;
+use r_redis::{network, Backend};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const REQUESTS: usize = 100_000;
+const PIPELINE: usize = 100;
+const REQUEST: &[u8] = b"*3\r\n$3\r\nset\r\n$3\r\nkey\r\n$5\r\nvalue\r\n";
+const REPLY: &[u8] = b"+OK\r\n";
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(network::serve(
+        network::Listener::Tcp(listener),
+        Backend::new(),
+    ));
+
+    for depth in [1, PIPELINE] {
+        let mut stream = TcpStream::connect(addr).await?;
+        stream.set_nodelay(true)?;
+        let elapsed = run(&mut stream, depth).await?;
+        println!(
+            "pipeline {:>3}: {:>8.0} requests/s ({:?} for {} requests)",
+            depth,
+            REQUESTS as f64 / elapsed.as_secs_f64(),
+            elapsed,
+            REQUESTS
+        );
+    }
+    Ok(())
+}
+
+// sends the requests `depth` at a time, waiting for all the replies of a batch before the next
+async fn run(stream: &mut TcpStream, depth: usize) -> Result<Duration> {
+    let batch = REQUEST.repeat(depth);
+    let mut replies = vec![0; REPLY.len() * depth];
+    let start = Instant::now();
+    for _ in 0..REQUESTS / depth {
+        stream.write_all(&batch).await?;
+        stream.read_exact(&mut replies).await?;
+    }
+    let elapsed = start.elapsed();
+    assert!(replies.chunks(REPLY.len()).all(|reply| reply == REPLY));
+    Ok(elapsed)
+}
@@ -0,0 +1,115 @@
+use super::{Acl, AclError, User};
+use crate::network::split_args;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tracing::warn;
+
+impl Acl {
+    /// Replaces every user with the ones declared in an ACL file, one `user <name> <rules...>`
+    /// per line. Nothing changes unless the whole file is valid. A file without the default
+    /// user gets a fresh one.
+    pub fn load_file(&self, path: &Path) -> Result<(), AclError> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| AclError::Open(path.display().to_string(), e.to_string()))?;
+        let error =
+            |line: usize, reason: String| AclError::File(path.display().to_string(), line, reason);
+
+        let mut users = BTreeMap::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let args: Vec<String> = split_args(line.as_bytes())
+                .ok_or_else(|| error(i + 1, "Unbalanced quotes in configuration line".into()))?
+                .into_iter()
+                .map(|arg| String::from_utf8_lossy(&arg).into_owned())
+                .collect();
+            if args.len() < 2 || args[0] != "user" {
+                return Err(error(i + 1, "line should start with user keyword".into()));
+            }
+            let name = &args[1];
+            if users.contains_key(name) {
+                return Err(error(i + 1, format!("Duplicate user '{}' found", name)));
+            }
+            let mut user = User::new(name);
+            user.apply_rules(&args[2..])
+                .map_err(|e| error(i + 1, e.to_string()))?;
+            users.insert(name.clone(), user);
+        }
+        users
+            .entry("default".to_string())
+            .or_insert_with(User::default_user);
+
+        *self.users.write().unwrap() = users;
+        Ok(())
+    }
+
+    /// Writes every user to an ACL file, replacing it at once.
+    pub fn save_file(&self, path: &Path) -> Result<(), AclError> {
+        let mut text = String::new();
+        for user in self.users() {
+            text.push_str(&user.to_string());
+            text.push('\n');
+        }
+
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(format!(".tmp-{}", std::process::id()));
+        fs::write(&tmp, text)
+            .and_then(|_| fs::rename(&tmp, path))
+            .map_err(|e| {
+                let _ = fs::remove_file(&tmp);
+                warn!("Failed saving the ACL file {}: {}", path.display(), e);
+                AclError::Save
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() -> Result<(), AclError> {
+        let path = std::env::temp_dir().join(format!("r-redis-{}.acl", std::process::id()));
+        let acl = Acl::default();
+        acl.set_user(
+            "alice",
+            &["on", ">pw", "~cache:*", "&news", "+@read", "(+set ~w:*)"],
+        )?;
+        acl.save_file(&path)?;
+
+        let loaded = Acl::default();
+        loaded.set_user("bob", &["on"])?;
+        loaded.load_file(&path)?;
+        assert_eq!(loaded.users(), acl.users());
+        assert!(loaded.authenticate("alice", "pw"));
+
+        std::fs::write(&path, "user alice on\n# a comment\n\nuser carol +nope\n").unwrap();
+        assert_eq!(
+            loaded.load_file(&path).unwrap_err().to_string(),
+            format!(
+                "{}:4: Error in ACL SETUSER modifier '+nope': Unknown command or category name in ACL",
+                path.display()
+            )
+        );
+        // a failed load changes nothing
+        assert!(loaded.authenticate("alice", "pw"));
+
+        std::fs::write(&path, "user alice on nopass\n").unwrap();
+        loaded.load_file(&path)?;
+        assert_eq!(
+            loaded.user("default"),
+            Some(User::default_user()),
+            "the default user is created when missing"
+        );
+        std::fs::write(&path, "user a on\nuser a off\n").unwrap();
+        assert!(loaded.load_file(&path).is_err());
+        std::fs::write(&path, "users a on\n").unwrap();
+        assert!(loaded.load_file(&path).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+        Ok(())
+    }
+}
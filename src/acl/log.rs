@@ -0,0 +1,133 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// like redis, repeated denials within this time add to the entry they repeat
+const GROUPING_MAX_TIME_DELTA: Duration = Duration::from_secs(60);
+
+/// The denials ACL LOG reports, newest first.
+#[derive(Debug, Default)]
+pub struct AclLog(Mutex<LogState>);
+
+#[derive(Debug, Default)]
+struct LogState {
+    entries: VecDeque<LogEntry>,
+    next_id: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub count: u64,
+    /// What was denied: `command`, `key`, `channel` or `auth`.
+    pub reason: &'static str,
+    pub context: &'static str,
+    /// The command, key or channel that was denied, `AUTH` for failed authentication.
+    pub object: String,
+    pub username: String,
+    pub client_info: String,
+    pub entry_id: u64,
+    pub created: SystemTime,
+    pub updated: SystemTime,
+}
+
+impl AclLog {
+    /// Records a denial, keeping at most `max_len` entries.
+    pub fn add(
+        &self,
+        reason: &'static str,
+        object: &str,
+        username: &str,
+        client_info: String,
+        max_len: usize,
+    ) {
+        let mut state = self.0.lock().unwrap();
+        let now = SystemTime::now();
+        let similar = state.entries.iter().position(|entry| {
+            entry.reason == reason
+                && entry.object == object
+                && entry.username == username
+                && now
+                    .duration_since(entry.updated)
+                    .is_ok_and(|age| age < GROUPING_MAX_TIME_DELTA)
+        });
+        let entry = match similar.and_then(|i| state.entries.remove(i)) {
+            Some(entry) => LogEntry {
+                count: entry.count + 1,
+                client_info,
+                updated: now,
+                ..entry
+            },
+            None => {
+                state.next_id += 1;
+                LogEntry {
+                    count: 1,
+                    reason,
+                    context: "toplevel",
+                    object: object.to_string(),
+                    username: username.to_string(),
+                    client_info,
+                    entry_id: state.next_id - 1,
+                    created: now,
+                    updated: now,
+                }
+            }
+        };
+        state.entries.push_front(entry);
+        state.entries.truncate(max_len);
+    }
+
+    /// The `count` most recent entries.
+    pub fn entries(&self, count: usize) -> Vec<LogEntry> {
+        let state = self.0.lock().unwrap();
+        state.entries.iter().take(count).cloned().collect()
+    }
+
+    pub fn reset(&self) {
+        self.0.lock().unwrap().entries.clear();
+    }
+}
+
+impl LogEntry {
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.created)
+            .unwrap_or_default()
+    }
+}
+
+/// Milliseconds since the epoch, the unit of the ACL LOG timestamps.
+pub fn unix_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acl_log_groups_entries() {
+        let log = AclLog::default();
+        log.add("command", "get", "alice", "id=1".into(), 10);
+        log.add("key", "secret", "alice", "id=2".into(), 10);
+        log.add("command", "get", "alice", "id=3".into(), 10);
+
+        let entries = log.entries(10);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].object, "get");
+        assert_eq!(entries[0].count, 2);
+        assert_eq!(entries[0].client_info, "id=3");
+        assert_eq!(entries[0].entry_id, 0);
+        assert_eq!(entries[1].entry_id, 1);
+        assert_eq!(log.entries(1).len(), 1);
+
+        log.add("auth", "AUTH", "bob", "id=4".into(), 2);
+        log.add("auth", "AUTH", "carol", "id=5".into(), 2);
+        let entries = log.entries(10);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].username, "carol");
+
+        log.reset();
+        assert!(log.entries(10).is_empty());
+    }
+}
@@ -0,0 +1,193 @@
+mod file;
+mod log;
+mod user;
+
+use crate::cmd::CommandSpec;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use thiserror::Error;
+
+pub use log::{unix_millis, AclLog, LogEntry};
+pub use user::{all_commands, hash_password, Denied, Selector, User, CATEGORIES};
+
+/// The users clients authenticate as and the permissions they have, following the ACL model
+/// of redis.
+#[derive(Debug)]
+pub struct Acl {
+    users: RwLock<BTreeMap<String, User>>,
+    log: AclLog,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum AclError {
+    #[error("Error in ACL SETUSER modifier '{0}': {1}")]
+    InvalidRule(String, String),
+    #[error("Usernames can't contain spaces or null characters")]
+    InvalidName,
+    #[error("The 'default' user cannot be removed")]
+    DefaultUser,
+    #[error("This Redis instance is not configured to use an ACL file. You may want to specify users via the ACL SETUSER command and then issue a CONFIG REWRITE (assuming you have a Redis configuration file set) in order to store users in the Redis configuration.")]
+    NoFile,
+    #[error("Error loading ACLs, opening file '{0}': {1}")]
+    Open(String, String),
+    #[error("{0}:{1}: {2}")]
+    File(String, usize, String),
+    #[error("There was an error trying to save the ACLs. Please check the server logs for more information")]
+    Save,
+}
+
+impl Default for Acl {
+    fn default() -> Self {
+        let default = User::default_user();
+        Acl {
+            users: RwLock::new(BTreeMap::from([(default.name().to_string(), default)])),
+            log: AclLog::default(),
+        }
+    }
+}
+
+impl Acl {
+    /// Whether new connections have to authenticate, which they need not while the default
+    /// user is enabled without a password.
+    pub fn auth_required(&self) -> bool {
+        let users = self.users.read().unwrap();
+        users
+            .get("default")
+            .is_none_or(|user| !user.is_enabled() || !user.is_nopass())
+    }
+
+    /// Checks the credentials of `username`, which must be an enabled user.
+    pub fn authenticate(&self, username: &str, password: &str) -> bool {
+        let users = self.users.read().unwrap();
+        users
+            .get(username)
+            .is_some_and(|user| user.check_password(password))
+    }
+
+    /// Sets the password of the default user the way requirepass does, an empty one removing
+    /// the need for it.
+    pub fn set_requirepass(&self, password: &str) {
+        let mut users = self.users.write().unwrap();
+        let user = users
+            .entry("default".to_string())
+            .or_insert_with(User::default_user);
+        let rules = match password {
+            "" => vec!["nopass".to_string()],
+            password => vec!["resetpass".to_string(), format!(">{}", password)],
+        };
+        user.apply_rules(&rules)
+            .expect("password rules are always valid");
+    }
+
+    /// Checks whether `username` may call `spec` with `args`, the name included. Users that were
+    /// deleted since the client authenticated may run nothing.
+    pub fn check(&self, username: &str, spec: &CommandSpec, args: &[&[u8]]) -> Result<(), Denied> {
+        let users = self.users.read().unwrap();
+        match users.get(username) {
+            Some(user) => user.check(spec, args),
+            None => Err(Denied::Command(spec.name)),
+        }
+    }
+
+    pub fn user(&self, name: &str) -> Option<User> {
+        self.users.read().unwrap().get(name).cloned()
+    }
+
+    /// Every user, ordered by name.
+    pub fn users(&self) -> Vec<User> {
+        self.users.read().unwrap().values().cloned().collect()
+    }
+
+    /// Creates or modifies a user with ACL SETUSER rules. Either every rule applies or the user
+    /// is left as it was.
+    pub fn set_user<S: AsRef<str>>(&self, name: &str, rules: &[S]) -> Result<(), AclError> {
+        if name.contains([' ', '\0']) {
+            return Err(AclError::InvalidName);
+        }
+        let mut users = self.users.write().unwrap();
+        let mut user = users.get(name).cloned().unwrap_or_else(|| User::new(name));
+        user.apply_rules(rules)?;
+        users.insert(name.to_string(), user);
+        Ok(())
+    }
+
+    /// Deletes users, returning how many of them existed.
+    pub fn del_users(&self, names: &[String]) -> Result<usize, AclError> {
+        if names.iter().any(|name| name == "default") {
+            return Err(AclError::DefaultUser);
+        }
+        let mut users = self.users.write().unwrap();
+        Ok(names
+            .iter()
+            .filter(|name| users.remove(name.as_str()).is_some())
+            .count())
+    }
+
+    pub fn log(&self) -> &AclLog {
+        &self.log
+    }
+}
+
+/// Compares passwords in a time that does not depend on where they first differ.
+pub fn time_independent_eq(a: &[u8], b: &[u8]) -> bool {
+    let len = a.len().max(b.len());
+    let mut diff = a.len() ^ b.len();
+    for i in 0..len {
+        let x = a.get(i).copied().unwrap_or(0);
+        let y = b.get(i).copied().unwrap_or(0);
+        diff |= (x ^ y) as usize;
+    }
+    diff == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requirepass() {
+        let acl = Acl::default();
+        assert!(!acl.auth_required());
+        assert!(acl.authenticate("default", "anything"));
+
+        acl.set_requirepass("secret");
+        assert!(acl.auth_required());
+        assert!(acl.authenticate("default", "secret"));
+        assert!(!acl.authenticate("default", "anything"));
+
+        acl.set_requirepass("");
+        assert!(!acl.auth_required());
+    }
+
+    #[test]
+    fn test_set_user_is_atomic() -> Result<(), AclError> {
+        let acl = Acl::default();
+        acl.set_user("alice", &["on", ">pw", "+get", "~*"])?;
+        assert!(acl.authenticate("alice", "pw"));
+        assert!(!acl.authenticate("bob", "pw"));
+
+        let err = acl.set_user("alice", &["off", "+nope"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error in ACL SETUSER modifier '+nope': Unknown command or category name in ACL"
+        );
+        assert!(acl.user("alice").unwrap().is_enabled());
+        assert_eq!(acl.set_user("a b", &["on"]), Err(AclError::InvalidName));
+
+        assert_eq!(acl.del_users(&["alice".into(), "bob".into()]), Ok(1));
+        assert_eq!(
+            acl.del_users(&["default".into()]),
+            Err(AclError::DefaultUser)
+        );
+        assert_eq!(acl.users().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_time_independent_eq() {
+        assert!(time_independent_eq(b"secret", b"secret"));
+        assert!(!time_independent_eq(b"secret", b"secreT"));
+        assert!(!time_independent_eq(b"secret", b"secret\0"));
+        assert!(time_independent_eq(b"", b""));
+    }
+}
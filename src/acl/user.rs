@@ -0,0 +1,694 @@
+use crate::acl::{time_independent_eq, AclError};
+use crate::cmd::{lookup, CommandSpec, COMMAND_TABLE};
+use crate::glob::glob_match;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// The ACL categories redis knows, whether or not a command here belongs to them.
+pub const CATEGORIES: &[&str] = &[
+    "keyspace",
+    "read",
+    "write",
+    "set",
+    "sortedset",
+    "list",
+    "hash",
+    "string",
+    "bitmap",
+    "hyperloglog",
+    "geo",
+    "stream",
+    "pubsub",
+    "admin",
+    "fast",
+    "slow",
+    "blocking",
+    "dangerous",
+    "connection",
+    "transaction",
+    "scripting",
+];
+
+const UNKNOWN_COMMAND: &str = "Unknown command or category name in ACL";
+const SYNTAX_ERROR: &str = "Syntax error";
+
+/// A user of the ACL model: its passwords, and a root selector plus any number of others, each
+/// granting a set of commands on a set of keys and channels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    name: String,
+    enabled: bool,
+    nopass: bool,
+    // SHA-256 digests in hex, in the order they were added
+    passwords: Vec<String>,
+    root: Selector,
+    selectors: Vec<Selector>,
+}
+
+/// The commands, keys and channels a selector grants, which a command must all pass in a single
+/// selector to run.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Selector {
+    // full names of the allowed commands and subcommands
+    commands: BTreeSet<&'static str>,
+    // the rules that built `commands`, kept to describe them back
+    command_rules: Vec<String>,
+    allkeys: bool,
+    keys: Vec<KeyPattern>,
+    allchannels: bool,
+    channels: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct KeyPattern {
+    pattern: String,
+    read: bool,
+    write: bool,
+}
+
+/// Why a command was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Denied {
+    Command(&'static str),
+    Key(String),
+    Channel(String),
+}
+
+impl User {
+    /// A user as ACL SETUSER creates it: disabled, with no passwords and no permissions.
+    pub fn new(name: &str) -> Self {
+        User {
+            name: name.to_string(),
+            enabled: false,
+            nopass: false,
+            passwords: vec![],
+            root: Selector::new(),
+            selectors: vec![],
+        }
+    }
+
+    /// The user connections start out as, allowed everything without a password.
+    pub fn default_user() -> Self {
+        let mut user = User::new("default");
+        user.apply_rules(&["on", "nopass", "~*", "&*", "+@all"])
+            .expect("the default user rules are valid");
+        user
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn is_nopass(&self) -> bool {
+        self.nopass
+    }
+
+    pub fn passwords(&self) -> &[String] {
+        &self.passwords
+    }
+
+    pub fn root(&self) -> &Selector {
+        &self.root
+    }
+
+    pub fn selectors(&self) -> &[Selector] {
+        &self.selectors
+    }
+
+    /// Whether `password` lets a client authenticate as this user.
+    pub fn check_password(&self, password: &str) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if self.nopass {
+            return true;
+        }
+        let hash = hash_password(password);
+        self.passwords
+            .iter()
+            .any(|p| time_independent_eq(p.as_bytes(), hash.as_bytes()))
+    }
+
+    /// Applies the rules of ACL SETUSER in order, where a selector in parentheses may span
+    /// several arguments. Stops at the first invalid rule, leaving the user partly changed.
+    pub fn apply_rules<S: AsRef<str>>(&mut self, rules: &[S]) -> Result<(), AclError> {
+        let mut rules = rules.iter().map(AsRef::as_ref);
+        while let Some(rule) = rules.next() {
+            if !rule.starts_with('(') {
+                self.apply(rule)?;
+                continue;
+            }
+            let mut selector = rule.to_string();
+            while !selector.ends_with(')') {
+                match rules.next() {
+                    Some(next) => {
+                        selector.push(' ');
+                        selector.push_str(next);
+                    }
+                    None => {
+                        return Err(AclError::InvalidRule(
+                            rule.to_string(),
+                            format!(
+                                "Unmatched parenthesis in acl selector starting at '{}'.",
+                                rule
+                            ),
+                        ))
+                    }
+                }
+            }
+            let mut new = Selector::new();
+            for op in selector[1..selector.len() - 1].split_whitespace() {
+                new.apply(op)
+                    .map_err(|reason| AclError::InvalidRule(op.to_string(), reason))?;
+            }
+            self.selectors.push(new);
+        }
+        Ok(())
+    }
+
+    fn apply(&mut self, rule: &str) -> Result<(), AclError> {
+        let error = |reason: &str| AclError::InvalidRule(rule.to_string(), reason.to_string());
+        match rule.to_ascii_lowercase().as_str() {
+            "on" => self.enabled = true,
+            "off" => self.enabled = false,
+            "nopass" => {
+                self.nopass = true;
+                self.passwords.clear();
+            }
+            "resetpass" => {
+                self.nopass = false;
+                self.passwords.clear();
+            }
+            "clearselectors" => self.selectors.clear(),
+            "reset" => {
+                *self = User::new(&self.name);
+            }
+            _ => match split_op(rule) {
+                Some(('>', password)) => self.add_password(hash_password(password)),
+                Some(('<', password)) => self.remove_password(&hash_password(password), &error)?,
+                Some(('#', hash)) => {
+                    self.add_password(parse_hash(hash).ok_or_else(|| error(BAD_HASH))?)
+                }
+                Some(('!', hash)) => {
+                    let hash = parse_hash(hash).ok_or_else(|| error(BAD_HASH))?;
+                    self.remove_password(&hash, &error)?
+                }
+                _ => self.root.apply(rule).map_err(|reason| error(&reason))?,
+            },
+        }
+        Ok(())
+    }
+
+    fn add_password(&mut self, hash: String) {
+        self.nopass = false;
+        if !self.passwords.contains(&hash) {
+            self.passwords.push(hash);
+        }
+    }
+
+    fn remove_password(
+        &mut self,
+        hash: &str,
+        error: &dyn Fn(&str) -> AclError,
+    ) -> Result<(), AclError> {
+        let before = self.passwords.len();
+        self.passwords.retain(|p| p != hash);
+        if self.passwords.len() == before {
+            return Err(error(
+                "The password you are trying to remove from the user does not exist",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks a call of `spec` with `args`, the name included, against the root selector and
+    /// then the others, until one allows it.
+    pub fn check(&self, spec: &CommandSpec, args: &[&[u8]]) -> Result<(), Denied> {
+        let mut denied = match self.root.check(spec, args) {
+            Ok(()) => return Ok(()),
+            Err(denied) => denied,
+        };
+        for selector in &self.selectors {
+            match selector.check(spec, args) {
+                Ok(()) => return Ok(()),
+                // a selector that got past the command says more about what went wrong
+                Err(e) if matches!(denied, Denied::Command(_)) => denied = e,
+                Err(_) => {}
+            }
+        }
+        Err(denied)
+    }
+}
+
+const BAD_HASH: &str =
+    "The password hash must be exactly 64 characters and contain only lowercase hexadecimal characters";
+
+/// The user the way ACL LIST and ACL files write it, rules that recreate it when applied to a
+/// new user.
+impl fmt::Display for User {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "user {}", self.name)?;
+        f.write_str(if self.enabled { " on" } else { " off" })?;
+        if self.nopass {
+            f.write_str(" nopass")?;
+        }
+        for hash in &self.passwords {
+            write!(f, " #{}", hash)?;
+        }
+        write!(f, " {}", self.root)?;
+        for selector in &self.selectors {
+            write!(f, " ({})", selector)?;
+        }
+        Ok(())
+    }
+}
+
+impl Selector {
+    fn new() -> Self {
+        Selector {
+            command_rules: vec!["-@all".to_string()],
+            ..Default::default()
+        }
+    }
+
+    // the reason is all there is to an error, the caller knows the rule
+    fn apply(&mut self, rule: &str) -> Result<(), String> {
+        match rule.to_ascii_lowercase().as_str() {
+            "allkeys" | "~*" => {
+                self.allkeys = true;
+                self.keys.clear();
+            }
+            "resetkeys" => {
+                self.allkeys = false;
+                self.keys.clear();
+            }
+            "allchannels" | "&*" => {
+                self.allchannels = true;
+                self.channels.clear();
+            }
+            "resetchannels" => {
+                self.allchannels = false;
+                self.channels.clear();
+            }
+            "allcommands" | "+@all" => {
+                self.commands = all_commands().map(|spec| spec.name).collect();
+                self.command_rules = vec!["+@all".to_string()];
+            }
+            "nocommands" | "-@all" => {
+                self.commands.clear();
+                self.command_rules = vec!["-@all".to_string()];
+            }
+            _ => {
+                if let Some(pattern) = rule.strip_prefix('~') {
+                    return self.add_key_pattern(pattern, true, true);
+                }
+                if let Some(rest) = rule.strip_prefix('%') {
+                    let (flags, pattern) = rest.split_once('~').ok_or(SYNTAX_ERROR)?;
+                    let read = flags.contains(['R', 'r']);
+                    let write = flags.contains(['W', 'w']);
+                    if flags.is_empty() || !flags.chars().all(|c| "RWrw".contains(c)) {
+                        return Err(SYNTAX_ERROR.to_string());
+                    }
+                    return self.add_key_pattern(pattern, read, write);
+                }
+                if let Some(pattern) = rule.strip_prefix('&') {
+                    if self.allchannels {
+                        return Err("Adding a pattern after the * pattern (or the 'allchannels' flag) is not valid and does not have any effect. Try 'resetchannels' to start with an empty list of channels".to_string());
+                    }
+                    if !self.channels.iter().any(|c| c == pattern) {
+                        self.channels.push(pattern.to_string());
+                    }
+                    return Ok(());
+                }
+                match split_op(rule) {
+                    Some(('+', name)) => self.set_commands(name, true)?,
+                    Some(('-', name)) => self.set_commands(name, false)?,
+                    _ => return Err(SYNTAX_ERROR.to_string()),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn add_key_pattern(&mut self, pattern: &str, read: bool, write: bool) -> Result<(), String> {
+        if self.allkeys {
+            return Err("Adding a pattern after the * pattern (or the 'allkeys' flag) is not valid and does not have any effect. Try 'resetkeys' to start with an empty list of patterns".to_string());
+        }
+        match self.keys.iter_mut().find(|key| key.pattern == pattern) {
+            Some(key) => {
+                key.read |= read;
+                key.write |= write;
+            }
+            None => self.keys.push(KeyPattern {
+                pattern: pattern.to_string(),
+                read,
+                write,
+            }),
+        }
+        Ok(())
+    }
+
+    // +name, -name, +@category or -@category, name being a command or `command|subcommand`
+    fn set_commands(&mut self, name: &str, allow: bool) -> Result<(), String> {
+        let name = name.to_ascii_lowercase();
+        let specs: Vec<&'static CommandSpec> = match name.strip_prefix('@') {
+            Some(category) if CATEGORIES.contains(&category) => all_commands()
+                .filter(|spec| {
+                    spec.acl_categories
+                        .contains(&format!("@{}", category).as_str())
+                })
+                .collect(),
+            Some(_) => return Err(UNKNOWN_COMMAND.to_string()),
+            None => {
+                let spec = lookup(&name).ok_or(UNKNOWN_COMMAND)?;
+                std::iter::once(spec).chain(spec.subcommands).collect()
+            }
+        };
+        for spec in specs {
+            if allow {
+                self.commands.insert(spec.name);
+            } else {
+                self.commands.remove(spec.name);
+            }
+        }
+
+        // only the latest rule about a command or category says anything
+        self.command_rules.retain(|rule| rule[1..] != name);
+        self.command_rules
+            .push(format!("{}{}", if allow { '+' } else { '-' }, name));
+        Ok(())
+    }
+
+    /// The key patterns as ACL GETUSER shows them.
+    pub fn describe_keys(&self) -> String {
+        if self.allkeys {
+            return "~*".to_string();
+        }
+        let keys: Vec<String> = self
+            .keys
+            .iter()
+            .map(|key| match (key.read, key.write) {
+                (true, true) => format!("~{}", key.pattern),
+                (true, false) => format!("%R~{}", key.pattern),
+                _ => format!("%W~{}", key.pattern),
+            })
+            .collect();
+        keys.join(" ")
+    }
+
+    /// The channel patterns as ACL GETUSER shows them.
+    pub fn describe_channels(&self) -> String {
+        if self.allchannels {
+            return "&*".to_string();
+        }
+        let channels: Vec<String> = self.channels.iter().map(|c| format!("&{}", c)).collect();
+        channels.join(" ")
+    }
+
+    /// The command rules as ACL GETUSER shows them.
+    pub fn describe_commands(&self) -> String {
+        self.command_rules.join(" ")
+    }
+
+    fn check(&self, spec: &CommandSpec, args: &[&[u8]]) -> Result<(), Denied> {
+        if !self.commands.contains(spec.name) {
+            return Err(Denied::Command(spec.name));
+        }
+        if !spec.key_flags.contains(&"NOT_KEY") {
+            for pos in spec.key_positions(args.len()) {
+                let key = args[pos];
+                if !self.check_key(key, spec.key_flags) {
+                    return Err(Denied::Key(String::from_utf8_lossy(key).into_owned()));
+                }
+            }
+        }
+        let (channels, literal) = channel_args(spec, args);
+        for channel in channels {
+            if !self.check_channel(channel, literal) {
+                return Err(Denied::Channel(
+                    String::from_utf8_lossy(channel).into_owned(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_key(&self, key: &[u8], flags: &[&str]) -> bool {
+        if self.allkeys {
+            return true;
+        }
+        let mut read = flags.contains(&"ACCESS");
+        let mut write = flags
+            .iter()
+            .any(|flag| matches!(*flag, "INSERT" | "DELETE" | "UPDATE"));
+        // a key used in no particular way needs every permission
+        if !read && !write {
+            (read, write) = (true, true);
+        }
+        self.keys.iter().any(|p| {
+            (p.read || !read) && (p.write || !write) && glob_match(p.pattern.as_bytes(), key)
+        })
+    }
+
+    // patterns subscribed to must be allowed literally, not just match an allowed pattern
+    fn check_channel(&self, channel: &[u8], literal: bool) -> bool {
+        self.allchannels
+            || self.channels.iter().any(|pattern| {
+                if literal {
+                    pattern.as_bytes() == channel
+                } else {
+                    glob_match(pattern.as_bytes(), channel)
+                }
+            })
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys = self.describe_keys();
+        if !keys.is_empty() {
+            write!(f, "{} ", keys)?;
+        }
+        if !self.allchannels {
+            f.write_str("resetchannels ")?;
+        }
+        let channels = self.describe_channels();
+        if !channels.is_empty() {
+            write!(f, "{} ", channels)?;
+        }
+        f.write_str(&self.describe_commands())
+    }
+}
+
+/// Every command and subcommand, containers included.
+pub fn all_commands() -> impl Iterator<Item = &'static CommandSpec> {
+    COMMAND_TABLE
+        .iter()
+        .flat_map(|spec| std::iter::once(spec).chain(spec.subcommands))
+}
+
+// the arguments of a pubsub command that name channels, and whether they are patterns
+fn channel_args<'a>(spec: &CommandSpec, args: &'a [&'a [u8]]) -> (&'a [&'a [u8]], bool) {
+    match spec.name {
+        "publish" | "spublish" => (&args[1..2], false),
+        "subscribe" | "ssubscribe" => (&args[1..], false),
+        "psubscribe" => (&args[1..], true),
+        _ => (&[], false),
+    }
+}
+
+// the first character of a rule, which tells what it does, and the rest
+fn split_op(rule: &str) -> Option<(char, &str)> {
+    let mut chars = rule.chars();
+    let op = chars.next()?;
+    Some((op, chars.as_str()))
+}
+
+/// The SHA-256 digest of a password in lowercase hex, the form users keep passwords in.
+pub fn hash_password(password: &str) -> String {
+    Sha256::digest(password.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn parse_hash(hash: &str) -> Option<String> {
+    let valid = hash.len() == 64 && hash.bytes().all(|c| matches!(c, b'0'..=b'9' | b'a'..=b'f'));
+    valid.then(|| hash.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::resolve;
+    use crate::{BulkString, RespArray};
+
+    fn with_rules(rules: &[&str]) -> User {
+        let mut user = User::new("alice");
+        user.apply_rules(rules).unwrap();
+        user
+    }
+
+    fn check(user: &User, args: &[&str]) -> Result<(), Denied> {
+        let frame = RespArray::new(
+            args.iter()
+                .map(|arg| BulkString::from(*arg).into())
+                .collect::<Vec<_>>(),
+        );
+        let spec = resolve(&frame).unwrap();
+        let args: Vec<&[u8]> = args.iter().map(|arg| arg.as_bytes()).collect();
+        user.check(spec, &args)
+    }
+
+    #[test]
+    fn test_default_user() {
+        let user = User::default_user();
+        assert_eq!(user.to_string(), "user default on nopass ~* &* +@all");
+        assert!(user.check_password("anything"));
+        assert_eq!(check(&user, &["config", "set", "timeout", "1"]), Ok(()));
+    }
+
+    #[test]
+    fn test_new_user() {
+        let user = User::new("alice");
+        assert_eq!(user.to_string(), "user alice off resetchannels -@all");
+        assert!(!user.check_password(""));
+        assert_eq!(check(&user, &["get", "a"]), Err(Denied::Command("get")));
+    }
+
+    #[test]
+    fn test_passwords() {
+        let mut user = with_rules(&["on", ">secret", ">other"]);
+        assert!(user.check_password("secret"));
+        assert!(user.check_password("other"));
+        assert!(!user.check_password("guess"));
+        assert_eq!(user.passwords()[0], hash_password("secret"));
+
+        user.apply_rules(&["<other"]).unwrap();
+        assert!(!user.check_password("other"));
+        assert!(user.apply_rules(&["<other"]).is_err());
+
+        let hash = hash_password("hashed");
+        user.apply_rules(&[format!("#{}", hash)]).unwrap();
+        assert!(user.check_password("hashed"));
+        assert!(user.apply_rules(&["#abc"]).is_err());
+
+        user.apply_rules(&["off"]).unwrap();
+        assert!(!user.check_password("secret"));
+        user.apply_rules(&["on", "nopass"]).unwrap();
+        assert!(user.passwords().is_empty());
+        assert!(user.check_password("anything"));
+    }
+
+    #[test]
+    fn test_command_rules() {
+        let user = with_rules(&["+@all", "-config", "+config|get", "~*"]);
+        assert_eq!(check(&user, &["get", "a"]), Ok(()));
+        assert_eq!(check(&user, &["config", "get", "port"]), Ok(()));
+        assert_eq!(
+            check(&user, &["config", "set", "port", "1"]),
+            Err(Denied::Command("config|set"))
+        );
+        assert_eq!(user.root().describe_commands(), "+@all -config +config|get");
+
+        let user = with_rules(&["+@hash", "-hset", "+get", "~*"]);
+        assert_eq!(check(&user, &["get", "a"]), Ok(()));
+        assert_eq!(check(&user, &["hget", "h", "f"]), Ok(()));
+        assert_eq!(
+            check(&user, &["hset", "h", "f", "v"]),
+            Err(Denied::Command("hset"))
+        );
+        assert_eq!(user.root().describe_commands(), "-@all +@hash -hset +get");
+
+        let user = with_rules(&["+@all", "-@read", "+get", "-get", "~*"]);
+        assert_eq!(check(&user, &["set", "a", "1"]), Ok(()));
+        assert_eq!(check(&user, &["get", "a"]), Err(Denied::Command("get")));
+        assert_eq!(user.root().describe_commands(), "+@all -@read -get");
+
+        let mut user = User::new("alice");
+        for rule in ["+nope", "+@nope", "+get|nope", "get"] {
+            assert!(user.apply_rules(&[rule]).is_err(), "{}", rule);
+        }
+    }
+
+    #[test]
+    fn test_key_patterns() {
+        let user = with_rules(&["+@all", "~cache:*", "%R~ro:*", "%W~wo:*"]);
+        assert_eq!(user.root().describe_keys(), "~cache:* %R~ro:* %W~wo:*");
+        assert_eq!(check(&user, &["set", "cache:1", "v"]), Ok(()));
+        assert_eq!(check(&user, &["get", "ro:1"]), Ok(()));
+        assert_eq!(
+            check(&user, &["set", "ro:1", "v"]),
+            Err(Denied::Key("ro:1".into()))
+        );
+        assert_eq!(check(&user, &["hset", "wo:1", "f", "v"]), Ok(()));
+        assert_eq!(
+            check(&user, &["get", "wo:1"]),
+            Err(Denied::Key("wo:1".into()))
+        );
+        assert_eq!(
+            check(&user, &["get", "other"]),
+            Err(Denied::Key("other".into()))
+        );
+
+        let mut user = user.clone();
+        assert!(user.apply_rules(&["%X~a"]).is_err());
+        user.apply_rules(&["allkeys"]).unwrap();
+        assert!(user.apply_rules(&["~more"]).is_err());
+        assert_eq!(check(&user, &["get", "other"]), Ok(()));
+    }
+
+    #[test]
+    fn test_channel_patterns() {
+        let user = with_rules(&["+@all", "&news.*"]);
+        assert_eq!(check(&user, &["publish", "news.tech", "hi"]), Ok(()));
+        assert_eq!(check(&user, &["subscribe", "news.a", "news.b"]), Ok(()));
+        assert_eq!(
+            check(&user, &["subscribe", "news.a", "sports"]),
+            Err(Denied::Channel("sports".into()))
+        );
+        assert_eq!(check(&user, &["psubscribe", "news.*"]), Ok(()));
+        assert_eq!(
+            check(&user, &["psubscribe", "news.t*"]),
+            Err(Denied::Channel("news.t*".into()))
+        );
+        // shard channels are not keys, only the channel patterns apply
+        assert_eq!(check(&user, &["spublish", "news.x", "hi"]), Ok(()));
+        assert_eq!(user.root().to_string(), "resetchannels &news.* +@all");
+    }
+
+    #[test]
+    fn test_selectors() {
+        let mut user = with_rules(&["+get", "~public:*", "(+set", "~private:*)", "(+@hash ~h:*)"]);
+        assert_eq!(user.selectors().len(), 2);
+        assert_eq!(check(&user, &["get", "public:1"]), Ok(()));
+        assert_eq!(check(&user, &["set", "private:1", "v"]), Ok(()));
+        assert_eq!(check(&user, &["hset", "h:1", "f", "v"]), Ok(()));
+        // the root selector allows the command but not the key, which is the better reason
+        assert_eq!(
+            check(&user, &["get", "private:1"]),
+            Err(Denied::Key("private:1".into()))
+        );
+        assert_eq!(
+            user.to_string(),
+            "user alice off ~public:* resetchannels -@all +get (~private:* resetchannels -@all +set) (~h:* resetchannels -@all +@hash)"
+        );
+
+        assert!(user.apply_rules(&["(+get", "~a"]).is_err());
+        assert!(user.apply_rules(&["(on)"]).is_err());
+        user.apply_rules(&["clearselectors"]).unwrap();
+        assert!(user.selectors().is_empty());
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut user = with_rules(&["on", ">pw", "+@all", "~*", "&*", "(+get)"]);
+        user.apply_rules(&["reset"]).unwrap();
+        assert_eq!(user, User::new("alice"));
+    }
+}
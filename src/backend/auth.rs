@@ -0,0 +1,60 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+// a client guessing passwords must not flood the log, failures are reported at most this often
+const FAILURE_LOG_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Failed authentication attempts, logged at a limited rate.
+#[derive(Debug, Default)]
+pub struct AuthFailures(Mutex<FailureLog>);
+
+#[derive(Debug, Default)]
+struct FailureLog {
+    last_logged: Option<Instant>,
+    suppressed: u64,
+}
+
+impl AuthFailures {
+    // returns whether the failure made it to the log
+    pub fn record(&self, username: &str, addr: &str) -> bool {
+        let mut log = self.0.lock().unwrap();
+        let now = Instant::now();
+        if log
+            .last_logged
+            .is_some_and(|last| now.duration_since(last) < FAILURE_LOG_INTERVAL)
+        {
+            log.suppressed += 1;
+            return false;
+        }
+
+        if log.suppressed > 0 {
+            warn!(
+                "Failed authentication of user '{}' from {}, {} more failures since the last report",
+                username, addr, log.suppressed
+            );
+        } else {
+            warn!("Failed authentication of user '{}' from {}", username, addr);
+        }
+        log.last_logged = Some(now);
+        log.suppressed = 0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auth_failures_rate_limited() {
+        let failures = AuthFailures::default();
+        assert!(failures.record("default", "127.0.0.1:5000"));
+        assert!(!failures.record("default", "127.0.0.1:5000"));
+        assert_eq!(failures.0.lock().unwrap().suppressed, 1);
+
+        failures.0.lock().unwrap().last_logged = Instant::now().checked_sub(FAILURE_LOG_INTERVAL);
+        assert!(failures.record("default", "127.0.0.1:5001"));
+        assert_eq!(failures.0.lock().unwrap().suppressed, 0);
+    }
+}
@@ -0,0 +1,501 @@
+use crate::config::OutputBufferLimit;
+use crate::network::ClientAddr;
+use crate::RespProtocol;
+use dashmap::DashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::Notify;
+
+/// The connected clients, as CLIENT LIST reports them and CLIENT KILL and PAUSE act on them.
+#[derive(Debug, Default)]
+pub struct Clients {
+    clients: DashMap<u64, Arc<ClientHandle>>,
+    // the clients registered, counted apart from the map so that a slot is taken atomically
+    registered: AtomicUsize,
+    pause: Mutex<Option<Pause>>,
+    // woken whenever the pause changes, clients waiting it out check it again
+    pause_changed: Notify,
+    // woken whenever a client goes
+    unregistered: Notify,
+}
+
+/// What a connection shares with the rest of the server, its description and the means to
+/// close it.
+#[derive(Debug)]
+pub struct ClientHandle {
+    info: Mutex<ClientInfo>,
+    output: Mutex<Output>,
+    // set when the output buffer limit closed the client
+    over_output_limit: AtomicBool,
+    killed: AtomicBool,
+    kill: Notify,
+}
+
+/// A client as of its last command, the fields of a CLIENT LIST line.
+#[derive(Debug, Clone)]
+pub struct ClientInfo {
+    pub id: u64,
+    pub addr: Option<ClientAddr>,
+    pub name: Option<String>,
+    pub db: usize,
+    pub user: String,
+    pub protocol: RespProtocol,
+    pub sub: usize,
+    pub psub: usize,
+    pub ssub: usize,
+    pub no_evict: bool,
+    /// The full name of the last command, empty until the client sends a known one.
+    pub last_cmd: &'static str,
+    pub qbuf: usize,
+    pub qbuf_free: usize,
+    /// Bytes of replies not yet written to the socket.
+    pub obl: usize,
+    /// Messages queued for the client, such as pub/sub pushes.
+    pub oll: usize,
+    /// Bytes of output held for the client, its replies and queued messages together.
+    pub omem: usize,
+    pub created: Instant,
+    pub last_interaction: Instant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PauseMode {
+    /// Only commands that may change the dataset wait.
+    Write,
+    All,
+}
+
+// the output of a client that counts against its output buffer limit
+#[derive(Debug, Default)]
+struct Output {
+    limit: OutputBufferLimit,
+    // bytes of messages sent to the client and not yet picked up by its connection
+    queued: usize,
+    // bytes of replies waiting to be written to the socket, as of the last check
+    buffered: usize,
+    // since when the client is over its soft limit
+    soft_since: Option<Instant>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Pause {
+    mode: PauseMode,
+    until: Instant,
+}
+
+impl Clients {
+    pub fn register(&self, id: u64) -> Arc<ClientHandle> {
+        self.registered.fetch_add(1, Ordering::AcqRel);
+        self.insert(id)
+    }
+
+    /// Registers a client unless `max` clients already are, as maxclients has it.
+    pub fn try_register(&self, id: u64, max: usize) -> Option<Arc<ClientHandle>> {
+        self.registered
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < max).then_some(n + 1)
+            })
+            .ok()?;
+        Some(self.insert(id))
+    }
+
+    fn insert(&self, id: u64) -> Arc<ClientHandle> {
+        let handle = Arc::new(ClientHandle {
+            info: Mutex::new(ClientInfo::new(id)),
+            output: Mutex::default(),
+            over_output_limit: AtomicBool::new(false),
+            killed: AtomicBool::new(false),
+            kill: Notify::new(),
+        });
+        self.clients.insert(id, handle.clone());
+        handle
+    }
+
+    pub fn unregister(&self, id: u64) {
+        if self.clients.remove(&id).is_some() {
+            self.registered.fetch_sub(1, Ordering::AcqRel);
+        }
+        self.unregistered.notify_waiters();
+    }
+
+    pub fn len(&self) -> usize {
+        self.registered.load(Ordering::Acquire)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    /// Waits until every client is gone.
+    pub async fn wait_empty(&self) {
+        loop {
+            let unregistered = self.unregistered.notified();
+            tokio::pin!(unregistered);
+            // registered before the clients are counted, the last one going meanwhile is seen
+            unregistered.as_mut().enable();
+            if self.clients.is_empty() {
+                return;
+            }
+            unregistered.await;
+        }
+    }
+
+    /// Every client, ordered by id.
+    pub fn list(&self) -> Vec<ClientInfo> {
+        let mut clients: Vec<ClientInfo> = self
+            .clients
+            .iter()
+            .map(|client| client.value().info())
+            .collect();
+        clients.sort_by_key(|client| client.id);
+        clients
+    }
+
+    /// Closes every client `filter` selects, returning how many there were.
+    pub fn kill(&self, filter: impl Fn(&ClientInfo) -> bool) -> usize {
+        let mut killed = 0;
+        for client in self.clients.iter() {
+            if filter(&client.value().info()) {
+                client.value().kill();
+                killed += 1;
+            }
+        }
+        killed
+    }
+
+    /// Holds back the commands `mode` covers until `until`. A pause already in place is only
+    /// ever extended and made stricter.
+    pub fn pause(&self, mode: PauseMode, until: Instant) {
+        let mut pause = self.pause.lock().unwrap();
+        *pause = Some(match *pause {
+            Some(current) if current.until > Instant::now() => Pause {
+                mode: mode.max(current.mode),
+                until: until.max(current.until),
+            },
+            _ => Pause { mode, until },
+        });
+        self.pause_changed.notify_waiters();
+    }
+
+    pub fn unpause(&self) {
+        *self.pause.lock().unwrap() = None;
+        self.pause_changed.notify_waiters();
+    }
+
+    /// Waits until a command may run, `write` telling whether it may change the dataset.
+    pub async fn wait_unpaused(&self, write: bool) {
+        loop {
+            let changed = self.pause_changed.notified();
+            tokio::pin!(changed);
+            // registered before the pause is read, an unpause in between is not missed
+            changed.as_mut().enable();
+
+            let until = match *self.pause.lock().unwrap() {
+                Some(pause) if write || pause.mode == PauseMode::All => pause.until,
+                _ => return,
+            };
+            if until <= Instant::now() {
+                return;
+            }
+            tokio::select! {
+                _ = tokio::time::sleep_until(until.into()) => return,
+                _ = changed => {}
+            }
+        }
+    }
+}
+
+impl ClientHandle {
+    pub fn info(&self) -> ClientInfo {
+        self.info.lock().unwrap().clone()
+    }
+
+    pub fn update(&self, f: impl FnOnce(&mut ClientInfo)) {
+        f(&mut self.info.lock().unwrap())
+    }
+
+    pub fn set_output_limit(&self, limit: OutputBufferLimit) {
+        self.output.lock().unwrap().limit = limit;
+    }
+
+    /// Bytes of messages queued for the client.
+    pub fn queued_output(&self) -> usize {
+        self.output.lock().unwrap().queued
+    }
+
+    /// Counts a message of `size` bytes sent to the client. Returns false, and kills the client,
+    /// when that takes it past its output buffer limit.
+    pub fn queue_output(&self, size: usize) -> bool {
+        let mut output = self.output.lock().unwrap();
+        output.queued += size;
+        if output.over_limit() {
+            output.queued -= size;
+            drop(output);
+            self.kill_over_output_limit();
+            return false;
+        }
+        true
+    }
+
+    /// Counts `size` bytes of queued messages as picked up by the connection.
+    pub fn dequeue_output(&self, size: usize) {
+        let mut output = self.output.lock().unwrap();
+        output.queued = output.queued.saturating_sub(size);
+    }
+
+    /// Checks the client against its output buffer limit with `buffered` bytes of replies not
+    /// yet written. Returns false, and kills the client, when it is past the limit.
+    pub fn check_output(&self, buffered: usize) -> bool {
+        let mut output = self.output.lock().unwrap();
+        output.buffered = buffered;
+        if output.over_limit() {
+            drop(output);
+            self.kill_over_output_limit();
+            return false;
+        }
+        true
+    }
+
+    /// Whether the client was closed for going past its output buffer limit.
+    pub fn is_over_output_limit(&self) -> bool {
+        self.over_output_limit.load(Ordering::Relaxed)
+    }
+
+    fn kill_over_output_limit(&self) {
+        self.over_output_limit.store(true, Ordering::Relaxed);
+        self.kill();
+    }
+
+    /// Asks the connection to close, once it is done with the command it runs.
+    pub fn kill(&self) {
+        self.killed.store(true, Ordering::Relaxed);
+        self.kill.notify_one();
+    }
+
+    pub fn is_killed(&self) -> bool {
+        self.killed.load(Ordering::Relaxed)
+    }
+
+    /// Resolves once the client is killed.
+    pub async fn killed(&self) {
+        // the flag covers a kill whose notification another waiter already took
+        while !self.is_killed() {
+            self.kill.notified().await
+        }
+    }
+}
+
+impl Output {
+    // like redis, the soft limit only disconnects a client that stays over it for longer than
+    // `soft_seconds`
+    fn over_limit(&mut self) -> bool {
+        let total = self.queued + self.buffered;
+        let limit = self.limit;
+        if limit.hard > 0 && total >= limit.hard {
+            return true;
+        }
+        if limit.soft == 0 || total < limit.soft {
+            self.soft_since = None;
+            return false;
+        }
+        let since = *self.soft_since.get_or_insert_with(Instant::now);
+        since.elapsed().as_secs() > limit.soft_seconds
+    }
+}
+
+impl ClientInfo {
+    fn new(id: u64) -> Self {
+        let now = Instant::now();
+        Self {
+            id,
+            addr: None,
+            name: None,
+            db: 0,
+            user: "default".to_string(),
+            protocol: RespProtocol::default(),
+            sub: 0,
+            psub: 0,
+            ssub: 0,
+            no_evict: false,
+            last_cmd: "",
+            qbuf: 0,
+            qbuf_free: 0,
+            obl: 0,
+            oll: 0,
+            omem: 0,
+            created: now,
+            last_interaction: now,
+        }
+    }
+
+    /// The type CLIENT LIST and CLIENT KILL filter by. Like redis, only RESP2 clients count as
+    /// pubsub ones, RESP3 clients can still run regular commands while subscribed.
+    pub fn client_type(&self) -> &'static str {
+        if self.is_subscribed() && self.protocol == RespProtocol::Resp2 {
+            "pubsub"
+        } else {
+            "normal"
+        }
+    }
+
+    pub fn is_subscribed(&self) -> bool {
+        self.sub + self.psub + self.ssub > 0
+    }
+
+    fn flags(&self) -> String {
+        let mut flags = String::new();
+        if self.is_subscribed() {
+            flags.push('P');
+        }
+        if self.no_evict {
+            flags.push('e');
+        }
+        if flags.is_empty() {
+            flags.push('N');
+        }
+        flags
+    }
+}
+
+// a CLIENT LIST line, without the newline
+impl fmt::Display for ClientInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (addr, laddr) = match &self.addr {
+            Some(addr) => (addr.addr(), addr.laddr()),
+            None => (String::new(), String::new()),
+        };
+        let cmd = match self.last_cmd {
+            "" => "NULL",
+            cmd => cmd,
+        };
+        write!(
+            f,
+            "id={} addr={} laddr={} name={} age={} idle={} flags={} db={} sub={} psub={} ssub={} multi=-1 qbuf={} qbuf-free={} obl={} oll={} omem={} cmd={} user={} resp={}",
+            self.id,
+            addr,
+            laddr,
+            self.name.as_deref().unwrap_or(""),
+            self.created.elapsed().as_secs(),
+            self.last_interaction.elapsed().as_secs(),
+            self.flags(),
+            self.db,
+            self.sub,
+            self.psub,
+            self.ssub,
+            self.qbuf,
+            self.qbuf_free,
+            self.obl,
+            self.oll,
+            self.omem,
+            cmd,
+            self.user,
+            self.protocol.version()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_clients_kill() {
+        let clients = Clients::default();
+        let alice = clients.register(1);
+        alice.update(|info| info.user = "alice".into());
+        let other = clients.register(2);
+        other.update(|info| {
+            info.sub = 1;
+            info.name = Some("worker".into());
+        });
+        assert_eq!(clients.len(), 2);
+
+        let line = other.info().to_string();
+        assert!(line.starts_with("id=2 addr= laddr= name=worker age=0 idle=0 flags=P db=0 sub=1"));
+        assert!(line.ends_with("cmd=NULL user=default resp=2"));
+        assert_eq!(other.info().client_type(), "pubsub");
+
+        assert_eq!(clients.kill(|info| info.user == "alice"), 1);
+        assert!(alice.is_killed());
+        assert!(!other.is_killed());
+
+        clients.unregister(1);
+        assert_eq!(clients.list().iter().map(|c| c.id).collect::<Vec<_>>(), [2]);
+    }
+
+    #[test]
+    fn test_clients_try_register() {
+        let clients = Arc::new(Clients::default());
+        let threads: Vec<_> = (0..8)
+            .map(|id| {
+                let clients = clients.clone();
+                std::thread::spawn(move || clients.try_register(id, 3).is_some())
+            })
+            .collect();
+        let registered = threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .filter(|registered| *registered)
+            .count();
+        assert_eq!(registered, 3);
+        assert_eq!(clients.len(), 3);
+
+        let id = clients.list()[0].id;
+        clients.unregister(id);
+        clients.unregister(id);
+        assert_eq!(clients.len(), 2);
+        assert!(clients.try_register(8, 3).is_some());
+        assert!(clients.try_register(9, 3).is_none());
+    }
+
+    #[test]
+    fn test_client_output_limit() {
+        let clients = Clients::default();
+        let client = clients.register(1);
+        // no limit by default
+        assert!(client.queue_output(1 << 20));
+        client.dequeue_output(1 << 20);
+
+        client.set_output_limit(OutputBufferLimit {
+            hard: 100,
+            soft: 50,
+            soft_seconds: 60,
+        });
+        assert!(client.queue_output(40));
+        // over the soft limit for less than soft_seconds
+        assert!(client.check_output(20));
+        assert!(!client.is_killed());
+        assert_eq!(client.queued_output(), 40);
+
+        assert!(!client.queue_output(50));
+        assert!(client.is_killed());
+        assert_eq!(client.queued_output(), 40);
+    }
+
+    #[tokio::test]
+    async fn test_clients_pause() {
+        let clients = Arc::new(Clients::default());
+        clients.pause(PauseMode::Write, Instant::now() + Duration::from_secs(60));
+        // reads go on during a write pause
+        clients.wait_unpaused(false).await;
+
+        let waiter = tokio::spawn({
+            let clients = clients.clone();
+            async move { clients.wait_unpaused(true).await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+        clients.unpause();
+        waiter.await.unwrap();
+
+        clients.pause(PauseMode::All, Instant::now() + Duration::from_millis(20));
+        // a shorter, weaker pause leaves the current one in place
+        clients.pause(PauseMode::Write, Instant::now());
+        let start = Instant::now();
+        clients.wait_unpaused(false).await;
+        assert!(start.elapsed() >= Duration::from_millis(15));
+    }
+}
@@ -0,0 +1,493 @@
+use crate::backend::lua::{valid_name, Engine, Function, ScriptState};
+use crate::{RespFrame, SimpleError};
+use bytes::Bytes;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use thiserror::Error;
+
+// the first byte of a FUNCTION DUMP payload, bumped whenever its layout changes
+const DUMP_VERSION: u8 = 1;
+// bytes of the sha256 of the payload that end it
+const CHECKSUM_LEN: usize = 8;
+
+/// The libraries of functions FUNCTION LOAD created, by library name.
+#[derive(Debug, Default)]
+pub struct Functions {
+    libraries: RwLock<BTreeMap<String, Arc<Library>>>,
+    // held while a function runs, as scripts run one at a time like in redis
+    running: Mutex<()>,
+    script: Arc<ScriptState>,
+}
+
+/// A library as FUNCTION LIST shows it, with the Lua state its functions run in.
+#[derive(Debug)]
+pub struct Library {
+    pub name: String,
+    pub code: String,
+    pub functions: Vec<Function>,
+    engine: Engine,
+}
+
+/// What FUNCTION RESTORE does with the libraries that already exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestorePolicy {
+    /// Fails if a restored library already exists.
+    Append,
+    /// Restored libraries take the place of those with the same name.
+    Replace,
+    /// Deletes every library first.
+    Flush,
+}
+
+/// Displayed as the message of the error reply, without its code.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum FunctionError {
+    #[error("Missing library metadata")]
+    MissingMetadata,
+    #[error("Engine '{0}' not found")]
+    UnknownEngine(String),
+    #[error("Invalid metadata value given: {0}")]
+    InvalidMetadata(String),
+    #[error("Library name was not given")]
+    MissingName,
+    #[error("Library names can only contain letters, numbers, or underscores(_) and must be at least one character long")]
+    InvalidName,
+    #[error("Library '{0}' already exists")]
+    LibraryExists(String),
+    #[error("Function {0} already exists")]
+    FunctionExists(String),
+    #[error("Library not found")]
+    LibraryNotFound,
+    #[error("payload version or checksum are wrong")]
+    InvalidPayload,
+    #[error("No scripts in execution right now.")]
+    NotBusy,
+    #[error("Sorry the script already executed write commands against the dataset. You can either wait the script termination or kill the server in a hard way using the SHUTDOWN NOSAVE command.")]
+    Unkillable,
+    #[error("{0}")]
+    Load(String),
+}
+
+impl Functions {
+    /// Creates the library `code` describes, replacing the one of the same name if `replace`.
+    /// Returns the name of the library.
+    pub fn load(&self, code: &str, replace: bool) -> Result<String, FunctionError> {
+        let library = Library::load(code)?;
+        let name = library.name.clone();
+        let mut libraries = self.libraries.write().unwrap();
+        if !replace && libraries.contains_key(&name) {
+            return Err(FunctionError::LibraryExists(name));
+        }
+        check_functions(&libraries, &library)?;
+        libraries.insert(name.clone(), Arc::new(library));
+        Ok(name)
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), FunctionError> {
+        match self.libraries.write().unwrap().remove(name) {
+            Some(_) => Ok(()),
+            None => Err(FunctionError::LibraryNotFound),
+        }
+    }
+
+    pub fn flush(&self) {
+        self.libraries.write().unwrap().clear();
+    }
+
+    /// Every library, ordered by name.
+    pub fn list(&self) -> Vec<Arc<Library>> {
+        self.libraries.read().unwrap().values().cloned().collect()
+    }
+
+    /// The library that registered the function `name`, and the function.
+    pub fn function(&self, name: &str) -> Option<(Arc<Library>, Function)> {
+        let libraries = self.libraries.read().unwrap();
+        libraries.values().find_map(|library| {
+            let function = library.functions.iter().find(|f| f.name == name)?;
+            Some((library.clone(), function.clone()))
+        })
+    }
+
+    /// Runs `function` of `library`, once any other function has returned. See
+    /// [`Engine::call`].
+    pub fn call(
+        &self,
+        library: &Library,
+        function: &str,
+        keys: &[Bytes],
+        args: &[Bytes],
+        busy_threshold: Duration,
+        call: &mut dyn FnMut(Vec<Bytes>) -> Result<RespFrame, SimpleError>,
+    ) -> Result<RespFrame, SimpleError> {
+        let _running = self.running.lock().unwrap();
+        self.script.start();
+        let ret = library
+            .engine
+            .call(function, keys, args, &self.script, busy_threshold, call);
+        self.script.finish();
+        ret
+    }
+
+    /// Whether a function has run past busy-reply-threshold.
+    pub fn busy(&self) -> bool {
+        self.script.is_busy()
+    }
+
+    /// Stops the running function, unless it has written to the dataset.
+    pub fn kill(&self) -> Result<(), FunctionError> {
+        if !self.script.is_running() {
+            return Err(FunctionError::NotBusy);
+        }
+        if self.script.wrote() {
+            return Err(FunctionError::Unkillable);
+        }
+        self.script.kill();
+        Ok(())
+    }
+
+    /// Records that the running function ran a write command.
+    pub fn record_write(&self) {
+        self.script.record_write();
+    }
+
+    /// The code of every library, in a payload FUNCTION RESTORE takes back. It starts with
+    /// its version, then every library's code as a 4 byte big endian length and the code
+    /// itself, and ends with the first bytes of the sha256 of what comes before.
+    pub fn dump(&self) -> Vec<u8> {
+        let mut payload = vec![DUMP_VERSION];
+        for library in self.libraries.read().unwrap().values() {
+            payload.extend_from_slice(&(library.code.len() as u32).to_be_bytes());
+            payload.extend_from_slice(library.code.as_bytes());
+        }
+        let checksum = Sha256::digest(&payload);
+        payload.extend_from_slice(&checksum[..CHECKSUM_LEN]);
+        payload
+    }
+
+    /// Loads the libraries of a FUNCTION DUMP payload. Nothing changes unless all of them can be
+    /// restored.
+    pub fn restore(&self, payload: &[u8], policy: RestorePolicy) -> Result<(), FunctionError> {
+        let restored = parse_dump(payload)?
+            .iter()
+            .map(|code| Library::load(code))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut libraries = self.libraries.write().unwrap();
+        let mut updated = match policy {
+            RestorePolicy::Flush => BTreeMap::new(),
+            _ => libraries.clone(),
+        };
+        for library in restored {
+            if policy == RestorePolicy::Append && updated.contains_key(&library.name) {
+                return Err(FunctionError::LibraryExists(library.name));
+            }
+            check_functions(&updated, &library)?;
+            updated.insert(library.name.clone(), Arc::new(library));
+        }
+        *libraries = updated;
+        Ok(())
+    }
+}
+
+impl Library {
+    // code starts with a `#!lua name=<library>` line, the rest is run to register functions
+    fn load(code: &str) -> Result<Self, FunctionError> {
+        let (header, body) = code.split_once('\n').unwrap_or((code, ""));
+        let header = header
+            .strip_prefix("#!")
+            .ok_or(FunctionError::MissingMetadata)?;
+        let mut params = header.split(' ').filter(|p| !p.is_empty());
+        let engine = params.next().unwrap_or_default();
+        if !engine.eq_ignore_ascii_case("lua") {
+            return Err(FunctionError::UnknownEngine(engine.to_string()));
+        }
+        let mut name = None;
+        for param in params {
+            match param.split_once('=') {
+                Some(("name", value)) => name = Some(value.to_string()),
+                _ => return Err(FunctionError::InvalidMetadata(param.to_string())),
+            }
+        }
+        let name = name.ok_or(FunctionError::MissingName)?;
+        if !valid_name(&name) {
+            return Err(FunctionError::InvalidName);
+        }
+
+        let (engine, functions) = Engine::load(body).map_err(FunctionError::Load)?;
+        Ok(Library {
+            name,
+            code: code.to_string(),
+            functions,
+            engine,
+        })
+    }
+}
+
+// function names are unique across libraries, the one `library` replaces aside
+fn check_functions(
+    libraries: &BTreeMap<String, Arc<Library>>,
+    library: &Library,
+) -> Result<(), FunctionError> {
+    for other in libraries
+        .values()
+        .filter(|other| other.name != library.name)
+    {
+        if let Some(function) = library
+            .functions
+            .iter()
+            .find(|f| other.functions.iter().any(|o| o.name == f.name))
+        {
+            return Err(FunctionError::FunctionExists(function.name.clone()));
+        }
+    }
+    Ok(())
+}
+
+fn parse_dump(payload: &[u8]) -> Result<Vec<String>, FunctionError> {
+    let invalid = || FunctionError::InvalidPayload;
+    let split = payload
+        .len()
+        .checked_sub(CHECKSUM_LEN)
+        .ok_or_else(invalid)?;
+    let (data, checksum) = payload.split_at(split);
+    if data.first() != Some(&DUMP_VERSION) || Sha256::digest(data)[..CHECKSUM_LEN] != *checksum {
+        return Err(invalid());
+    }
+
+    let mut codes = vec![];
+    let mut rest = &data[1..];
+    while !rest.is_empty() {
+        let (len, tail) = rest.split_first_chunk::<4>().ok_or_else(invalid)?;
+        let len = u32::from_be_bytes(*len) as usize;
+        if tail.len() < len {
+            return Err(invalid());
+        }
+        let (code, tail) = tail.split_at(len);
+        codes.push(String::from_utf8(code.to_vec()).map_err(|_| invalid())?);
+        rest = tail;
+    }
+    Ok(codes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespNull};
+    use anyhow::Result;
+
+    const LIB: &str = "#!lua name=mylib\n\
+        redis.register_function('echo', function(keys, args) return args[1] end)\n\
+        redis.register_function{function_name='keys', callback=function(keys) return keys end, flags={'no-writes'}, description='the keys'}";
+
+    #[test]
+    fn test_functions_load() -> Result<()> {
+        let functions = Functions::default();
+        assert_eq!(functions.load(LIB, false)?, "mylib");
+        assert_eq!(
+            functions.load(LIB, false),
+            Err(FunctionError::LibraryExists("mylib".into()))
+        );
+        assert_eq!(functions.load(LIB, true)?, "mylib");
+
+        let (library, function) = functions.function("keys").unwrap();
+        assert_eq!(library.name, "mylib");
+        assert_eq!(function.flags, ["no-writes"]);
+        assert_eq!(function.description.as_deref(), Some("the keys"));
+        assert!(functions.function("nosuch").is_none());
+
+        // function names are unique across libraries
+        let other = LIB.replace("mylib", "other");
+        assert_eq!(
+            functions.load(&other, false),
+            Err(FunctionError::FunctionExists("echo".into()))
+        );
+
+        for (code, error) in [
+            ("return 1", FunctionError::MissingMetadata),
+            ("#!js name=a\n", FunctionError::UnknownEngine("js".into())),
+            ("#!lua\n", FunctionError::MissingName),
+            ("#!lua name=a-b\n", FunctionError::InvalidName),
+            (
+                "#!lua name=a foo=bar\n",
+                FunctionError::InvalidMetadata("foo=bar".into()),
+            ),
+            (
+                "#!lua name=a\nreturn 1",
+                FunctionError::Load("No functions registered".into()),
+            ),
+        ] {
+            assert_eq!(functions.load(code, false).unwrap_err(), error, "{}", code);
+        }
+        let code = "#!lua name=a\nredis.register_function('a', function() end, 1)";
+        assert!(matches!(
+            functions.load(code, false),
+            Err(FunctionError::Load(_))
+        ));
+
+        functions.delete("mylib")?;
+        assert_eq!(
+            functions.delete("mylib"),
+            Err(FunctionError::LibraryNotFound)
+        );
+        assert!(functions.list().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_functions_call() -> Result<()> {
+        let functions = Functions::default();
+        functions.load(LIB, false)?;
+        let (library, _) = functions.function("echo").unwrap();
+        let mut calls = vec![];
+        let mut call = |args: Vec<Bytes>| {
+            calls.push(args);
+            Ok(RespFrame::from(BulkString::from("called")))
+        };
+        let reply = functions
+            .call(
+                &library,
+                "echo",
+                &[],
+                &["hi".into()],
+                Duration::ZERO,
+                &mut call,
+            )
+            .unwrap();
+        assert_eq!(reply, BulkString::from("hi").into());
+        let keys = ["a".into(), "b".into()];
+        let reply = functions
+            .call(&library, "keys", &keys, &[], Duration::ZERO, &mut call)
+            .unwrap();
+        assert_eq!(
+            reply,
+            crate::RespArray::new(vec![
+                BulkString::from("a").into(),
+                BulkString::from("b").into()
+            ])
+            .into()
+        );
+        assert!(calls.is_empty());
+
+        // redis.call raises the error reply as is, redis.pcall returns it
+        let code = "#!lua name=calls\n\
+            redis.register_function('call', function(keys, args) return redis.call('get', keys[1]) end)\n\
+            redis.register_function('pcall', function(keys, args) return redis.pcall('get', 1) end)\n\
+            redis.register_function('fail', function() return nosuch.field end)";
+        functions.load(code, false)?;
+        let (library, _) = functions.function("call").unwrap();
+        let mut call = |args: Vec<Bytes>| match &args[1][..] {
+            b"a" => Ok(BulkString::from("1").into()),
+            _ => Err(SimpleError::new("WRONGTYPE Operation")),
+        };
+        let mut run = |function, keys: &[Bytes]| {
+            functions.call(&library, function, keys, &[], Duration::ZERO, &mut call)
+        };
+        assert_eq!(
+            run("call", &["a".into()]).unwrap(),
+            BulkString::from("1").into()
+        );
+        assert_eq!(
+            run("call", &["b".into()]),
+            Err(SimpleError::new("WRONGTYPE Operation"))
+        );
+        assert_eq!(
+            run("pcall", &[]).unwrap(),
+            SimpleError::new("WRONGTYPE Operation").into()
+        );
+        let e = run("fail", &[]).unwrap_err();
+        assert!(e.starts_with("ERR ") && e.contains("nosuch"), "{}", *e);
+        Ok(())
+    }
+
+    #[test]
+    fn test_functions_limits() -> Result<()> {
+        let code = "#!lua name=slow\nwhile true do end";
+        assert_eq!(
+            Functions::default().load(code, false),
+            Err(FunctionError::Load(
+                "Error registering functions: FUNCTION LOAD timeout".into()
+            ))
+        );
+        let code = "#!lua name=big\nlocal s = 'x' for i = 1, 40 do s = s .. s end";
+        assert!(matches!(
+            Functions::default().load(code, false),
+            Err(FunctionError::Load(e)) if e.contains("memory")
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_functions_kill() -> Result<()> {
+        let functions = Arc::new(Functions::default());
+        let code = "#!lua name=loops\n\
+            redis.register_function('loop', function() while true do end end)\n\
+            redis.register_function('write', function() redis.call('set', 'a', 1) for i = 1, 1e8 do end end)";
+        functions.load(code, false)?;
+        assert_eq!(functions.kill(), Err(FunctionError::NotBusy));
+
+        let run = |function: &'static str| {
+            let running = functions.clone();
+            let (library, _) = functions.function(function).unwrap();
+            let handle = std::thread::spawn(move || {
+                let mut call = |_| {
+                    running.record_write();
+                    Ok(RespFrame::from(BulkString::from("OK")))
+                };
+                let busy = Duration::from_millis(10);
+                running.call(&library, function, &[], &[], busy, &mut call)
+            });
+            while !functions.busy() {
+                std::thread::yield_now();
+            }
+            handle
+        };
+
+        let handle = run("loop");
+        functions.kill()?;
+        let e = handle.join().unwrap().unwrap_err();
+        assert!(e.contains("Script killed by user"), "{}", *e);
+        assert!(!functions.busy());
+        assert_eq!(functions.kill(), Err(FunctionError::NotBusy));
+
+        // a function that wrote can only run to its end
+        let handle = run("write");
+        assert_eq!(functions.kill(), Err(FunctionError::Unkillable));
+        assert_eq!(handle.join().unwrap(), Ok(RespNull.into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_functions_dump_restore() -> Result<()> {
+        let functions = Functions::default();
+        functions.load(LIB, false)?;
+        let payload = functions.dump();
+
+        let restored = Functions::default();
+        restored.restore(&payload, RestorePolicy::Append)?;
+        assert_eq!(restored.list()[0].code, LIB);
+        assert_eq!(
+            restored.restore(&payload, RestorePolicy::Append),
+            Err(FunctionError::LibraryExists("mylib".into()))
+        );
+        restored.restore(&payload, RestorePolicy::Replace)?;
+
+        let other = "#!lua name=other\nredis.register_function('other', function() end)";
+        restored.load(other, false)?;
+        restored.restore(&payload, RestorePolicy::Flush)?;
+        let names: Vec<_> = restored.list().iter().map(|l| l.name.clone()).collect();
+        assert_eq!(names, ["mylib"]);
+
+        let mut corrupt = payload.clone();
+        corrupt[5] ^= 1;
+        for payload in [&corrupt[..], &payload[..4], b""] {
+            assert_eq!(
+                restored.restore(payload, RestorePolicy::Flush),
+                Err(FunctionError::InvalidPayload)
+            );
+        }
+        assert_eq!(restored.list().len(), 1);
+        Ok(())
+    }
+}
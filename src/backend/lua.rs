@@ -0,0 +1,446 @@
+use crate::{BulkString, RespArray, RespFrame, RespNull, SimpleError, SimpleString};
+use bytes::Bytes;
+use mlua::{
+    HookTriggers, Lua, LuaOptions, MultiValue, RegistryKey, StdLib, Table, Value, Variadic,
+};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, trace, warn};
+
+/// The flags a function may be registered with, only `no-writes` is enforced.
+pub const FUNCTION_FLAGS: &[&str] = &[
+    "no-writes",
+    "allow-oom",
+    "allow-stale",
+    "no-cluster",
+    "allow-cross-slot-keys",
+];
+
+// how long the body of a library may run as it loads, as in redis
+const LOAD_TIMEOUT: Duration = Duration::from_millis(500);
+// instructions between two checks of the time a script has been running and of FUNCTION KILL
+const HOOK_INSTRUCTIONS: u32 = 100_000;
+// the memory a library's Lua state may allocate, its code and whatever its functions create
+const MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+
+/// A library's own Lua state, holding the callbacks of the functions its code registered.
+pub struct Engine {
+    lua: Mutex<Lua>,
+    callbacks: BTreeMap<String, RegistryKey>,
+}
+
+/// A function as registered by `redis.register_function`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    pub name: String,
+    pub description: Option<String>,
+    pub flags: Vec<String>,
+}
+
+/// The state of the running script, shared with the hook that interrupts it.
+#[derive(Debug, Default)]
+pub struct ScriptState {
+    running: AtomicBool,
+    // past busy-reply-threshold, so other clients are told to wait or to kill it
+    busy: AtomicBool,
+    killed: AtomicBool,
+    // whether it ran a write command, after which it may no longer be killed
+    wrote: AtomicBool,
+}
+
+// an error reply from redis.call, raised through the script unchanged
+#[derive(Debug)]
+struct ReplyError(SimpleError);
+
+impl Engine {
+    /// Runs the body of a library, returning the functions it registered.
+    pub fn load(body: &str) -> Result<(Self, Vec<Function>), String> {
+        let lua = new_lua().map_err(|e| e.to_string())?;
+        let start = Instant::now();
+        lua.set_hook(
+            HookTriggers::new().every_nth_instruction(HOOK_INSTRUCTIONS),
+            move |_, _| match start.elapsed() < LOAD_TIMEOUT {
+                true => Ok(()),
+                false => Err(mlua::Error::runtime("FUNCTION LOAD timeout")),
+            },
+        );
+        let registered = RefCell::new(vec![]);
+        lua.scope(|scope| {
+            let register = scope.create_function(|lua, args: MultiValue| {
+                let (function, callback) = registration(args)?;
+                let mut registered = registered.borrow_mut();
+                if registered
+                    .iter()
+                    .any(|(f, _): &(Function, _)| f.name == function.name)
+                {
+                    return Err(mlua::Error::runtime(
+                        "Function already exists in the library",
+                    ));
+                }
+                registered.push((function, lua.create_registry_value(callback)?));
+                Ok(())
+            })?;
+            let redis: Table = lua.globals().get("redis")?;
+            redis.set("register_function", register)?;
+            lua.load(body).set_name("@user_function").exec()?;
+            // functions may only be registered as the library loads
+            redis.set("register_function", Value::Nil)
+        })
+        .map_err(|e| format!("Error registering functions: {}", error_message(&e)))?;
+        lua.remove_hook();
+
+        let registered = registered.into_inner();
+        if registered.is_empty() {
+            return Err("No functions registered".to_string());
+        }
+        let mut functions = vec![];
+        let mut callbacks = BTreeMap::new();
+        for (function, callback) in registered {
+            callbacks.insert(function.name.clone(), callback);
+            functions.push(function);
+        }
+        let engine = Engine {
+            lua: Mutex::new(lua),
+            callbacks,
+        };
+        Ok((engine, functions))
+    }
+
+    /// Calls `function` with `keys` and `args`, the commands it sends through redis.call and
+    /// redis.pcall being run by `call`. The script is marked busy in `state` once it has run
+    /// for `busy_threshold`, if not zero, and stops once `state` is killed.
+    pub fn call(
+        &self,
+        function: &str,
+        keys: &[Bytes],
+        args: &[Bytes],
+        state: &Arc<ScriptState>,
+        busy_threshold: Duration,
+        call: &mut dyn FnMut(Vec<Bytes>) -> Result<RespFrame, SimpleError>,
+    ) -> Result<RespFrame, SimpleError> {
+        let callback = self
+            .callbacks
+            .get(function)
+            .ok_or_else(|| SimpleError::new("ERR Function not found"))?;
+        let lua = self.lua.lock().unwrap();
+        let start = Instant::now();
+        let hook_state = state.clone();
+        lua.set_hook(
+            HookTriggers::new().every_nth_instruction(HOOK_INSTRUCTIONS),
+            move |_, _| {
+                if hook_state.killed.load(Ordering::Relaxed) {
+                    return Err(mlua::Error::runtime(
+                        "Script killed by user with FUNCTION KILL...",
+                    ));
+                }
+                if !busy_threshold.is_zero() && start.elapsed() >= busy_threshold {
+                    hook_state.busy.store(true, Ordering::Relaxed);
+                }
+                Ok(())
+            },
+        );
+        let call = RefCell::new(call);
+        let ret = lua.scope(|scope| {
+            let redis: Table = lua.globals().get("redis")?;
+            redis.set(
+                "call",
+                scope.create_function(|lua, args: MultiValue| {
+                    let frame = (call.borrow_mut())(command_args(args)?)
+                        .map_err(|e| mlua::Error::external(ReplyError(e)))?;
+                    to_lua(lua, frame)
+                })?,
+            )?;
+            redis.set(
+                "pcall",
+                scope.create_function(|lua, args: MultiValue| {
+                    let frame = match (call.borrow_mut())(command_args(args)?) {
+                        Ok(frame) => frame,
+                        Err(e) => e.into(),
+                    };
+                    to_lua(lua, frame)
+                })?,
+            )?;
+            let callback: mlua::Function = lua.registry_value(callback)?;
+            let keys = strings(&lua, keys)?;
+            let args = strings(&lua, args)?;
+            let ret: Value = callback.call((keys, args))?;
+            Ok(from_lua(ret))
+        });
+        lua.remove_hook();
+        ret.map_err(|e| match reply_error(&e) {
+            Some(e) => e.clone(),
+            None => SimpleError::new(format!("ERR {}", error_message(&e))),
+        })
+    }
+}
+
+impl ScriptState {
+    pub fn start(&self) {
+        self.busy.store(false, Ordering::Relaxed);
+        self.killed.store(false, Ordering::Relaxed);
+        self.wrote.store(false, Ordering::Relaxed);
+        self.running.store(true, Ordering::Relaxed);
+    }
+
+    pub fn finish(&self) {
+        self.running.store(false, Ordering::Relaxed);
+        self.busy.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.busy.load(Ordering::Relaxed)
+    }
+
+    pub fn kill(&self) {
+        self.killed.store(true, Ordering::Relaxed);
+    }
+
+    pub fn record_write(&self) {
+        self.wrote.store(true, Ordering::Relaxed);
+    }
+
+    pub fn wrote(&self) -> bool {
+        self.wrote.load(Ordering::Relaxed)
+    }
+}
+
+impl fmt::Debug for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Engine")
+            .field("functions", &self.callbacks.keys())
+            .finish()
+    }
+}
+
+impl fmt::Display for ReplyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ReplyError {}
+
+// a state with the libraries redis gives scripts, without access to files, and the redis table
+fn new_lua() -> mlua::Result<Lua> {
+    let lua = Lua::new_with(
+        StdLib::TABLE | StdLib::STRING | StdLib::MATH,
+        LuaOptions::default(),
+    )?;
+    lua.set_memory_limit(MEMORY_LIMIT)?;
+    let globals = lua.globals();
+    for name in ["dofile", "loadfile"] {
+        globals.set(name, Value::Nil)?;
+    }
+
+    let redis = lua.create_table()?;
+    redis.set(
+        "status_reply",
+        lua.create_function(|lua, status: mlua::String| {
+            let reply = lua.create_table()?;
+            reply.set("ok", status)?;
+            Ok(reply)
+        })?,
+    )?;
+    redis.set(
+        "error_reply",
+        lua.create_function(|lua, error: mlua::String| {
+            let reply = lua.create_table()?;
+            reply.set("err", error)?;
+            Ok(reply)
+        })?,
+    )?;
+    for (level, name) in ["LOG_DEBUG", "LOG_VERBOSE", "LOG_NOTICE", "LOG_WARNING"]
+        .iter()
+        .enumerate()
+    {
+        redis.set(*name, level)?;
+    }
+    redis.set(
+        "log",
+        lua.create_function(|_, (level, message): (i64, Variadic<String>)| {
+            let message = message.join(" ");
+            match level {
+                0 => trace!("{}", message),
+                1 => debug!("{}", message),
+                2 => info!("{}", message),
+                _ => warn!("{}", message),
+            }
+            Ok(())
+        })?,
+    )?;
+    globals.set("redis", redis)?;
+    drop(globals);
+    Ok(lua)
+}
+
+// redis.register_function('name', callback) or redis.register_function{function_name='name',
+// callback=callback, flags={...}, description='...'}
+fn registration(args: MultiValue) -> mlua::Result<(Function, mlua::Function)> {
+    let args: Vec<Value> = args.into_iter().collect();
+    let (name, callback, flags, description) = match args.as_slice() {
+        [Value::String(name), Value::Function(callback)] => {
+            (name.to_str()?.to_string(), callback.clone(), vec![], None)
+        }
+        [Value::Table(table)] => {
+            for pair in table.clone().pairs::<String, Value>() {
+                let (key, _) = pair?;
+                if !["function_name", "callback", "flags", "description"].contains(&key.as_str()) {
+                    return Err(mlua::Error::runtime(
+                        "unknown argument given to register_function",
+                    ));
+                }
+            }
+            let name: Option<String> = table.get("function_name")?;
+            let callback: Option<mlua::Function> = table.get("callback")?;
+            let flags: Option<Vec<String>> = table.get("flags")?;
+            let description: Option<String> = table.get("description")?;
+            let name = name.ok_or_else(|| {
+                mlua::Error::runtime(
+                    "function_name argument given to redis.register_function must be a string",
+                )
+            })?;
+            let callback = callback.ok_or_else(|| {
+                mlua::Error::runtime(
+                    "callback argument given to redis.register_function must be a function",
+                )
+            })?;
+            (name, callback, flags.unwrap_or_default(), description)
+        }
+        _ => {
+            return Err(mlua::Error::runtime(
+                "wrong number of arguments to redis.register_function",
+            ))
+        }
+    };
+    if !valid_name(&name) {
+        return Err(mlua::Error::runtime(
+            "Function names can only contain letters, numbers, or underscores(_) and must be at least one character long",
+        ));
+    }
+    if let Some(flag) = flags.iter().find(|f| !FUNCTION_FLAGS.contains(&f.as_str())) {
+        return Err(mlua::Error::runtime(format!(
+            "unknown flag given: {}",
+            flag
+        )));
+    }
+    let function = Function {
+        name,
+        description,
+        flags,
+    };
+    Ok((function, callback))
+}
+
+/// Whether a function or library name only has letters, digits and underscores.
+pub fn valid_name(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+fn strings<'lua>(lua: &'lua Lua, values: &[Bytes]) -> mlua::Result<Table<'lua>> {
+    let values = values
+        .iter()
+        .map(|value| lua.create_string(value))
+        .collect::<mlua::Result<Vec<_>>>()?;
+    lua.create_sequence_from(values)
+}
+
+fn command_args(args: MultiValue) -> mlua::Result<Vec<Bytes>> {
+    if args.is_empty() {
+        return Err(mlua::Error::runtime(
+            "Please specify at least one argument for this redis lib call",
+        ));
+    }
+    args.into_iter()
+        .map(|arg| match arg {
+            Value::String(s) => Ok(Bytes::copy_from_slice(s.as_bytes())),
+            Value::Integer(n) => Ok(Bytes::from(n.to_string())),
+            Value::Number(n) => Ok(Bytes::from(n.to_string())),
+            _ => Err(mlua::Error::runtime(
+                "Lua redis lib command arguments must be strings or integers",
+            )),
+        })
+        .collect()
+}
+
+// like redis, replies reach scripts as RESP2: statuses and errors as tables with an `ok` or an
+// `err` field and nulls as false
+fn to_lua(lua: &Lua, frame: RespFrame) -> mlua::Result<Value<'_>> {
+    Ok(match frame.into_resp2() {
+        RespFrame::SimpleString(s) => {
+            let table = lua.create_table()?;
+            table.set("ok", s.as_str())?;
+            Value::Table(table)
+        }
+        RespFrame::Error(e) => {
+            let table = lua.create_table()?;
+            table.set("err", e.as_str())?;
+            Value::Table(table)
+        }
+        RespFrame::Integer(n) => Value::Integer(n),
+        RespFrame::BulkString(s) if s.is_null() => Value::Boolean(false),
+        RespFrame::BulkString(s) => Value::String(lua.create_string(&s[..])?),
+        RespFrame::Array(array) if array.is_null() => Value::Boolean(false),
+        RespFrame::Array(array) => {
+            let table = lua.create_table_with_capacity(array.len(), 0)?;
+            for frame in array.data {
+                table.raw_push(to_lua(lua, frame)?)?;
+            }
+            Value::Table(table)
+        }
+        _ => Value::Boolean(false),
+    })
+}
+
+// the reverse of `to_lua`, an array ending at its first nil
+fn from_lua(value: Value) -> RespFrame {
+    match value {
+        Value::Boolean(true) => RespFrame::Integer(1),
+        Value::Integer(n) => RespFrame::Integer(n),
+        Value::Number(n) => RespFrame::Integer(n as i64),
+        Value::String(s) => BulkString::from(s.as_bytes()).into(),
+        Value::Table(table) => {
+            if let Ok(Value::String(e)) = table.raw_get("err") {
+                return SimpleError::new(e.to_string_lossy()).into();
+            }
+            if let Ok(Value::String(s)) = table.raw_get("ok") {
+                return SimpleString::new(s.to_string_lossy()).into();
+            }
+            let mut data = vec![];
+            for i in 1.. {
+                match table.raw_get(i) {
+                    Ok(Value::Nil) | Err(_) => break,
+                    Ok(value) => data.push(from_lua(value)),
+                }
+            }
+            RespArray::new(data).into()
+        }
+        _ => RespNull.into(),
+    }
+}
+
+fn reply_error(e: &mlua::Error) -> Option<&SimpleError> {
+    match e {
+        mlua::Error::CallbackError { cause, .. } => reply_error(cause),
+        mlua::Error::ExternalError(e) => e.downcast_ref::<ReplyError>().map(|e| &e.0),
+        _ => None,
+    }
+}
+
+// the message of a script error, without the traceback of a callback that raised it
+fn error_message(e: &mlua::Error) -> String {
+    match e {
+        mlua::Error::CallbackError { cause, .. } => error_message(cause),
+        mlua::Error::RuntimeError(message) | mlua::Error::SyntaxError { message, .. } => {
+            message.clone()
+        }
+        e => e.to_string(),
+    }
+}
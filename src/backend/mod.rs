@@ -1,15 +1,57 @@
-use crate::{RespFrame, RespNull};
+use crate::acl::Acl;
+use crate::config::{LogLevel, ServerConfig};
+use crate::{BulkString, RespFrame, RespLimits, RespNull};
+use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
 use std::ops::Deref;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use thiserror::Error;
+use tokio_rustls::rustls;
+
+mod auth;
+mod clients;
+mod functions;
+mod lua;
+pub mod notify;
+mod pubsub;
+mod shutdown;
+mod stats;
+
+pub use auth::AuthFailures;
+pub use clients::{ClientHandle, ClientInfo, Clients, PauseMode};
+pub use functions::{FunctionError, Functions, Library, RestorePolicy};
+pub use lua::{Engine, Function, FUNCTION_FLAGS};
+pub use pubsub::{ClientSender, PubSub};
+pub use shutdown::ShutdownRequest;
+pub use stats::{CommandStats, Counter, Histogram, Stats};
 
 #[derive(Debug, Clone)]
 pub struct Backend(Arc<BackendInner>);
 
-#[derive(Debug, Clone)]
+/// The key holds a value of another type than the one an operation works on.
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+pub struct WrongType;
+
+#[derive(Debug)]
 pub struct BackendInner {
     map: DashMap<String, RespFrame>,
     hmap: DashMap<String, DashMap<String, RespFrame>>,
+    functions: Functions,
+    pubsub: PubSub,
+    next_client_id: AtomicU64,
+    config: RwLock<ServerConfig>,
+    // the loglevel setting, apart from the config so that logging never waits for its lock
+    loglevel: AtomicU8,
+    // the notify-keyspace-events setting, apart from the config so that writes never take its lock
+    notify_keyspace_events: AtomicU32,
+    tls: RwLock<Option<Arc<rustls::ServerConfig>>>,
+    acl: Acl,
+    auth_failures: AuthFailures,
+    clients: Clients,
+    shutdown: ShutdownRequest,
+    stats: Stats,
 }
 
 impl Deref for Backend {
@@ -24,6 +66,10 @@ impl Backend {
     pub fn new() -> Self {
         Self::default()
     }
+
+    pub fn with_config(config: ServerConfig) -> Self {
+        Self(Arc::new(BackendInner::with_config(config)))
+    }
 }
 
 impl Default for Backend {
@@ -40,28 +86,179 @@ impl Default for BackendInner {
 
 impl BackendInner {
     pub fn new() -> Self {
+        Self::with_config(ServerConfig::default())
+    }
+
+    pub fn with_config(config: ServerConfig) -> Self {
+        let acl = Acl::default();
+        acl.set_requirepass(&config.requirepass);
         Self {
             map: DashMap::new(),
             hmap: DashMap::new(),
+            functions: Functions::default(),
+            pubsub: PubSub::default(),
+            next_client_id: AtomicU64::new(1),
+            loglevel: AtomicU8::new(config.loglevel as u8),
+            notify_keyspace_events: AtomicU32::new(config.notify_keyspace_events),
+            config: RwLock::new(config),
+            tls: RwLock::new(None),
+            acl,
+            auth_failures: AuthFailures::default(),
+            clients: Clients::default(),
+            shutdown: ShutdownRequest::default(),
+            stats: Stats::default(),
         }
     }
 
+    pub fn next_client_id(&self) -> u64 {
+        self.next_client_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    pub fn pubsub(&self) -> &PubSub {
+        &self.pubsub
+    }
+
+    pub fn config(&self) -> RwLockReadGuard<'_, ServerConfig> {
+        self.config.read().unwrap()
+    }
+
+    pub fn config_mut(&self) -> RwLockWriteGuard<'_, ServerConfig> {
+        self.config.write().unwrap()
+    }
+
+    /// The loglevel setting, readable from tracing filters while the config is locked.
+    pub fn loglevel(&self) -> LogLevel {
+        LogLevel::from_u8(self.loglevel.load(Ordering::Relaxed))
+    }
+
+    pub fn set_loglevel(&self, level: LogLevel) {
+        self.loglevel.store(level as u8, Ordering::Relaxed);
+    }
+
+    /// The TLS setup new connections on the TLS port are accepted with.
+    pub fn tls(&self) -> Option<Arc<rustls::ServerConfig>> {
+        self.tls.read().unwrap().clone()
+    }
+
+    pub fn set_tls(&self, tls: Arc<rustls::ServerConfig>) {
+        *self.tls.write().unwrap() = Some(tls);
+    }
+
+    pub fn acl(&self) -> &Acl {
+        &self.acl
+    }
+
+    pub fn auth_failures(&self) -> &AuthFailures {
+        &self.auth_failures
+    }
+
+    pub fn clients(&self) -> &Clients {
+        &self.clients
+    }
+
+    pub fn shutdown(&self) -> &ShutdownRequest {
+        &self.shutdown
+    }
+
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// The notify-keyspace-events setting, read on every write without locking the config.
+    pub fn notify_keyspace_events(&self) -> u32 {
+        self.notify_keyspace_events.load(Ordering::Relaxed)
+    }
+
+    pub fn set_notify_keyspace_events(&self, flags: u32) {
+        self.notify_keyspace_events.store(flags, Ordering::Relaxed);
+    }
+
+    /// The limits applied when decoding client input, picked up by connections before they
+    /// read their next request.
+    pub fn resp_limits(&self) -> RespLimits {
+        self.config().resp_limits()
+    }
+
+    /// Publishes a keyspace event for `key` on `__keyspace@0__:<key>` and/or
+    /// `__keyevent@0__:<event>`, if `class` is enabled by notify-keyspace-events.
+    pub fn notify_keyspace_event(&self, class: u32, event: &str, key: &str) {
+        let flags = self.notify_keyspace_events();
+        if flags & class == 0 {
+            return;
+        }
+
+        if flags & notify::NOTIFY_KEYSPACE != 0 {
+            let channel = format!("__keyspace@0__:{}", key);
+            self.pubsub
+                .publish(&channel, BulkString::from(event).into());
+        }
+        if flags & notify::NOTIFY_KEYEVENT != 0 {
+            let channel = format!("__keyevent@0__:{}", event);
+            self.pubsub.publish(&channel, BulkString::from(key).into());
+        }
+    }
+
+    pub fn functions(&self) -> &Functions {
+        &self.functions
+    }
+
     pub fn get(&self, key: &str) -> Option<RespFrame> {
-        self.map.get(key).map(|v| v.value().clone())
+        let value = self.map.get(key).map(|v| v.value().clone());
+        self.record_lookup(key, value.is_some());
+        value
     }
 
+    /// The number of keys.
+    pub fn dbsize(&self) -> usize {
+        self.map.len() + self.hmap.len()
+    }
+
+    // a read of a key the keyspace hits and misses of INFO count, a miss is a keymiss event
+    fn record_lookup(&self, key: &str, hit: bool) {
+        match hit {
+            true => self.stats.keyspace_hits.incr(),
+            false => {
+                self.stats.keyspace_misses.incr();
+                self.notify_keyspace_event(notify::NOTIFY_KEY_MISS, "keymiss", key);
+            }
+        }
+    }
+
+    /// The type of the value stored at `key`, named as TYPE reports it.
+    pub fn key_type(&self, key: &str) -> Option<&'static str> {
+        if self.map.contains_key(key) {
+            Some("string")
+        } else if self.hmap.contains_key(key) {
+            Some("hash")
+        } else {
+            None
+        }
+    }
+
+    // like redis, SET replaces whatever value the key held before
     pub fn set(&self, key: String, value: RespFrame) {
-        self.map.insert(key, value);
+        // the key stays locked until it holds the value, so it is found new only once
+        let entry = self.map.entry(key.clone());
+        let replaced_hash = self.hmap.remove(&key).is_some();
+        let is_new = matches!(entry, Entry::Vacant(_)) && !replaced_hash;
+        entry.insert(value);
+        self.stats.dirty.incr();
+        if is_new {
+            self.notify_keyspace_event(notify::NOTIFY_NEW, "new", &key);
+        }
+        self.notify_keyspace_event(notify::NOTIFY_STRING, "set", &key);
     }
 
     pub fn hget(&self, key: &str, field: &str) -> Option<RespFrame> {
-        self.hmap
-            .get(key)
-            .and_then(|v| v.get(field).map(|v| v.value().clone()))
+        let hash = self.hmap.get(key);
+        self.record_lookup(key, hash.is_some());
+        hash.and_then(|v| v.get(field).map(|v| v.value().clone()))
     }
 
     pub fn hmget(&self, key: &str, fields: &[String]) -> Option<Vec<RespFrame>> {
-        self.hmap.get(key).map(|v| {
+        let hash = self.hmap.get(key);
+        self.record_lookup(key, hash.is_some());
+        hash.map(|v| {
             fields
                 .iter()
                 .map(|field| {
@@ -72,13 +269,41 @@ impl BackendInner {
         })
     }
 
-    pub fn hset(&self, key: String, field: String, value: RespFrame) {
-        let hmap = self.hmap.entry(key).or_default();
-        hmap.insert(field, value);
+    /// Sets `fields` of the hash at `key` at once, notifying a single hset event for them.
+    /// Returns the number of fields that were added rather than updated, or fails if the key
+    /// holds a string.
+    pub fn hset(&self, key: String, fields: Vec<(String, RespFrame)>) -> Result<usize, WrongType> {
+        let count = fields.len() as u64;
+        // the string is locked before the hash, as SET locks them, so that none is set at the
+        // key until the hash holds the fields and SET can't leave both behind
+        let string = self.map.entry(key.clone());
+        if matches!(string, Entry::Occupied(_)) {
+            return Err(WrongType);
+        }
+        let entry = self.hmap.entry(key.clone());
+        let is_new = matches!(entry, Entry::Vacant(_));
+        let hash = entry.or_default();
+        let mut added = 0;
+        for (field, value) in fields {
+            if hash.insert(field, value).is_none() {
+                added += 1;
+            }
+        }
+        // subscribers are not sent the events while the key is locked
+        drop(hash);
+        drop(string);
+        self.stats.dirty.add(count);
+        if is_new {
+            self.notify_keyspace_event(notify::NOTIFY_NEW, "new", &key);
+        }
+        self.notify_keyspace_event(notify::NOTIFY_HASH, "hset", &key);
+        Ok(added)
     }
 
     pub fn hget_all(&self, key: &str) -> Option<DashMap<String, RespFrame>> {
-        self.hmap.get(key).map(|v| v.clone())
+        let hash = self.hmap.get(key).map(|v| v.clone());
+        self.record_lookup(key, hash.is_some());
+        hash
     }
 }
 
@@ -110,15 +335,79 @@ mod tests {
         let backend = Backend::new();
         backend.hset(
             "key".into(),
-            "field".into(),
-            BulkString::new("value").into(),
-        );
+            vec![("field".into(), BulkString::new("value").into())],
+        )?;
 
         let value = backend.hget("key", "field");
         assert_eq!(value, Some(BulkString::new("value").into()));
 
         let value = backend.hget("key", "not_exist_field");
         assert_eq!(value, None);
+
+        backend.set("string".into(), BulkString::new("value").into());
+        let fields = vec![("field".into(), BulkString::new("value").into())];
+        assert_eq!(backend.hset("string".into(), fields), Err(WrongType));
+        assert_eq!(backend.key_type("string"), Some("string"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_backend_keyspace_notifications() -> Result<()> {
+        let backend = Backend::new();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        backend
+            .pubsub()
+            .psubscribe("__key*__:*".into(), 1, tx.into());
+        let pmessage = |channel: &str, message: &str| -> RespFrame {
+            crate::RespPush::new(vec![
+                BulkString::new("pmessage").into(),
+                BulkString::new("__key*__:*").into(),
+                BulkString::new(channel).into(),
+                BulkString::new(message).into(),
+            ])
+            .into()
+        };
+
+        // disabled by default
+        backend.set("hello".into(), BulkString::new("world").into());
+        assert!(rx.try_recv().is_err());
+
+        backend.set_notify_keyspace_events(notify::parse_notify_flags("KE$")?);
+        backend.set("hello".into(), BulkString::new("world").into());
+        assert_eq!(rx.try_recv()?, pmessage("__keyspace@0__:hello", "set"));
+        assert_eq!(rx.try_recv()?, pmessage("__keyevent@0__:set", "hello"));
+
+        // hash events are not enabled
+        backend.hset(
+            "map".into(),
+            vec![("f".into(), BulkString::new("v").into())],
+        )?;
+        assert!(rx.try_recv().is_err());
+
+        backend.set_notify_keyspace_events(notify::parse_notify_flags("Ehn")?);
+        // one event for all the fields of an HSET
+        backend.hset(
+            "map2".into(),
+            vec![
+                ("f".into(), BulkString::new("v").into()),
+                ("g".into(), BulkString::new("w").into()),
+            ],
+        )?;
+        assert_eq!(rx.try_recv()?, pmessage("__keyevent@0__:new", "map2"));
+        assert_eq!(rx.try_recv()?, pmessage("__keyevent@0__:hset", "map2"));
+        assert!(rx.try_recv().is_err());
+
+        // reads of missing keys, not of missing fields
+        backend.set_notify_keyspace_events(notify::parse_notify_flags("Km")?);
+        backend.hget("map2", "missing");
+        backend.get("missing");
+        backend.hmget("nohash", &["f".into()]);
+        assert_eq!(
+            rx.try_recv()?,
+            pmessage("__keyspace@0__:missing", "keymiss")
+        );
+        assert_eq!(rx.try_recv()?, pmessage("__keyspace@0__:nohash", "keymiss"));
+        assert!(rx.try_recv().is_err());
         Ok(())
     }
 }
@@ -0,0 +1,128 @@
+use thiserror::Error;
+
+// The classes of keyspace events, matching NOTIFY_* in redis/src/server.h.
+pub const NOTIFY_KEYSPACE: u32 = 1 << 0; // K
+pub const NOTIFY_KEYEVENT: u32 = 1 << 1; // E
+pub const NOTIFY_GENERIC: u32 = 1 << 2; // g
+pub const NOTIFY_STRING: u32 = 1 << 3; // $
+pub const NOTIFY_LIST: u32 = 1 << 4; // l
+pub const NOTIFY_SET: u32 = 1 << 5; // s
+pub const NOTIFY_HASH: u32 = 1 << 6; // h
+pub const NOTIFY_ZSET: u32 = 1 << 7; // z
+pub const NOTIFY_EXPIRED: u32 = 1 << 8; // x
+pub const NOTIFY_EVICTED: u32 = 1 << 9; // e
+pub const NOTIFY_STREAM: u32 = 1 << 10; // t
+pub const NOTIFY_KEY_MISS: u32 = 1 << 11; // m, excluded from A
+pub const NOTIFY_MODULE: u32 = 1 << 12; // d
+pub const NOTIFY_NEW: u32 = 1 << 13; // n, excluded from A
+pub const NOTIFY_ALL: u32 = NOTIFY_GENERIC
+    | NOTIFY_STRING
+    | NOTIFY_LIST
+    | NOTIFY_SET
+    | NOTIFY_HASH
+    | NOTIFY_ZSET
+    | NOTIFY_EXPIRED
+    | NOTIFY_EVICTED
+    | NOTIFY_STREAM
+    | NOTIFY_MODULE; // A
+
+const CLASSES: [(char, u32); 10] = [
+    ('g', NOTIFY_GENERIC),
+    ('$', NOTIFY_STRING),
+    ('l', NOTIFY_LIST),
+    ('s', NOTIFY_SET),
+    ('h', NOTIFY_HASH),
+    ('z', NOTIFY_ZSET),
+    ('x', NOTIFY_EXPIRED),
+    ('e', NOTIFY_EVICTED),
+    ('t', NOTIFY_STREAM),
+    ('d', NOTIFY_MODULE),
+];
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("Invalid event class character '{0}'")]
+pub struct InvalidEventClass(pub char);
+
+/// Parses the `notify-keyspace-events` flag string, e.g. `KEA` or `Ex`.
+pub fn parse_notify_flags(flags: &str) -> Result<u32, InvalidEventClass> {
+    flags.chars().try_fold(0, |acc, c| {
+        let flag = match c {
+            'A' => NOTIFY_ALL,
+            'K' => NOTIFY_KEYSPACE,
+            'E' => NOTIFY_KEYEVENT,
+            'm' => NOTIFY_KEY_MISS,
+            'n' => NOTIFY_NEW,
+            c => match CLASSES.iter().find(|(name, _)| *name == c) {
+                Some((_, flag)) => *flag,
+                None => return Err(InvalidEventClass(c)),
+            },
+        };
+        Ok(acc | flag)
+    })
+}
+
+/// Formats flags back into their canonical string form, using `A` when every class it
+/// covers is enabled.
+pub fn format_notify_flags(flags: u32) -> String {
+    let mut s = String::new();
+    if flags & NOTIFY_ALL == NOTIFY_ALL {
+        s.push('A');
+    } else {
+        CLASSES
+            .iter()
+            .filter(|(_, flag)| flags & flag != 0)
+            .for_each(|(name, _)| s.push(*name));
+    }
+    for (name, flag) in [
+        ('K', NOTIFY_KEYSPACE),
+        ('E', NOTIFY_KEYEVENT),
+        ('m', NOTIFY_KEY_MISS),
+        ('n', NOTIFY_NEW),
+    ] {
+        if flags & flag != 0 {
+            s.push(name);
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notify_flags() {
+        assert_eq!(parse_notify_flags(""), Ok(0));
+        assert_eq!(
+            parse_notify_flags("KEA"),
+            Ok(NOTIFY_KEYSPACE | NOTIFY_KEYEVENT | NOTIFY_ALL)
+        );
+        assert_eq!(
+            parse_notify_flags("Ex"),
+            Ok(NOTIFY_KEYEVENT | NOTIFY_EXPIRED)
+        );
+        assert_eq!(
+            parse_notify_flags("K$hmn"),
+            Ok(NOTIFY_KEYSPACE | NOTIFY_STRING | NOTIFY_HASH | NOTIFY_KEY_MISS | NOTIFY_NEW)
+        );
+        assert_eq!(parse_notify_flags("KEq"), Err(InvalidEventClass('q')));
+    }
+
+    #[test]
+    fn test_format_notify_flags() {
+        assert_eq!(format_notify_flags(0), "");
+        assert_eq!(
+            format_notify_flags(parse_notify_flags("AKE").unwrap()),
+            "AKE"
+        );
+        assert_eq!(
+            format_notify_flags(parse_notify_flags("g$lshzxetdKE").unwrap()),
+            "AKE"
+        );
+        assert_eq!(format_notify_flags(parse_notify_flags("xE").unwrap()), "xE");
+        assert_eq!(
+            format_notify_flags(parse_notify_flags("nh$K").unwrap()),
+            "$hKn"
+        );
+    }
+}
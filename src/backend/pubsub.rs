@@ -0,0 +1,281 @@
+use crate::backend::ClientHandle;
+use crate::glob::glob_match;
+use crate::{BulkString, RespEncode, RespFrame, RespPush};
+use dashmap::DashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// The sending half of a connection's outbound queue, used to push messages to subscribers.
+/// Messages count against the output buffer limit of the client until it writes them out.
+#[derive(Debug, Clone)]
+pub struct ClientSender {
+    tx: UnboundedSender<RespFrame>,
+    client: Option<Arc<ClientHandle>>,
+}
+
+type Subscribers = DashMap<u64, ClientSender>;
+
+#[derive(Debug, Clone, Default)]
+pub struct PubSub {
+    channels: DashMap<String, Subscribers>,
+    patterns: DashMap<String, Subscribers>,
+    shard_channels: DashMap<String, Subscribers>,
+}
+
+impl PubSub {
+    pub fn subscribe(&self, channel: String, id: u64, sender: ClientSender) {
+        self.channels.entry(channel).or_default().insert(id, sender);
+    }
+
+    pub fn unsubscribe(&self, channel: &str, id: u64) -> bool {
+        remove_subscriber(&self.channels, channel, id)
+    }
+
+    pub fn psubscribe(&self, pattern: String, id: u64, sender: ClientSender) {
+        self.patterns.entry(pattern).or_default().insert(id, sender);
+    }
+
+    pub fn punsubscribe(&self, pattern: &str, id: u64) -> bool {
+        remove_subscriber(&self.patterns, pattern, id)
+    }
+
+    // returns the number of clients that received the message, through channels and patterns
+    pub fn publish(&self, channel: &str, message: RespFrame) -> usize {
+        let mut receivers = 0;
+        if let Some(subscribers) = self.channels.get(channel) {
+            let frame: RespFrame = RespPush::new(vec![
+                BulkString::from("message").into(),
+                BulkString::from(channel).into(),
+                message.clone(),
+            ])
+            .into();
+            receivers += send_all(&subscribers, frame);
+        }
+
+        for item in self.patterns.iter() {
+            if !glob_match(item.key().as_bytes(), channel.as_bytes()) {
+                continue;
+            }
+            let frame: RespFrame = RespPush::new(vec![
+                BulkString::from("pmessage").into(),
+                BulkString::from(item.key().as_str()).into(),
+                BulkString::from(channel).into(),
+                message.clone(),
+            ])
+            .into();
+            receivers += send_all(item.value(), frame);
+        }
+        receivers
+    }
+
+    pub fn channels(&self, pattern: Option<&str>) -> Vec<String> {
+        self.channels
+            .iter()
+            .map(|item| item.key().to_string())
+            .filter(|channel| pattern.is_none_or(|p| glob_match(p.as_bytes(), channel.as_bytes())))
+            .collect()
+    }
+
+    pub fn numsub(&self, channel: &str) -> usize {
+        self.channels.get(channel).map_or(0, |v| v.len())
+    }
+
+    // the number of unique patterns subscribed to by all clients
+    pub fn numpat(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn ssubscribe(&self, channel: String, id: u64, sender: ClientSender) {
+        self.shard_channels
+            .entry(channel)
+            .or_default()
+            .insert(id, sender);
+    }
+
+    pub fn sunsubscribe(&self, channel: &str, id: u64) -> bool {
+        remove_subscriber(&self.shard_channels, channel, id)
+    }
+
+    // returns the number of clients that received the message, patterns are not consulted
+    pub fn spublish(&self, channel: &str, message: RespFrame) -> usize {
+        let Some(subscribers) = self.shard_channels.get(channel) else {
+            return 0;
+        };
+
+        let frame: RespFrame = RespPush::new(vec![
+            BulkString::from("smessage").into(),
+            BulkString::from(channel).into(),
+            message,
+        ])
+        .into();
+        send_all(&subscribers, frame)
+    }
+
+    pub fn shard_channels(&self, pattern: Option<&str>) -> Vec<String> {
+        self.shard_channels
+            .iter()
+            .map(|item| item.key().to_string())
+            .filter(|channel| pattern.is_none_or(|p| glob_match(p.as_bytes(), channel.as_bytes())))
+            .collect()
+    }
+
+    pub fn shard_numsub(&self, channel: &str) -> usize {
+        self.shard_channels.get(channel).map_or(0, |v| v.len())
+    }
+}
+
+impl ClientSender {
+    pub fn new(tx: UnboundedSender<RespFrame>, client: Arc<ClientHandle>) -> Self {
+        Self {
+            tx,
+            client: Some(client),
+        }
+    }
+
+    /// Queues a message, unless the client is gone or the message takes it past its output
+    /// buffer limit, which disconnects it.
+    pub fn send(&self, frame: RespFrame) -> bool {
+        if let Some(client) = &self.client {
+            if !client.queue_output(frame.encode().len()) {
+                return false;
+            }
+        }
+        self.tx.send(frame).is_ok()
+    }
+}
+
+// a queue without a client to account it to, which is never limited
+impl From<UnboundedSender<RespFrame>> for ClientSender {
+    fn from(tx: UnboundedSender<RespFrame>) -> Self {
+        Self { tx, client: None }
+    }
+}
+
+fn remove_subscriber(map: &DashMap<String, Subscribers>, key: &str, id: u64) -> bool {
+    let removed = map
+        .get(key)
+        .is_some_and(|subscribers| subscribers.remove(&id).is_some());
+    map.remove_if(key, |_, subscribers| subscribers.is_empty());
+    removed
+}
+
+fn send_all(subscribers: &Subscribers, frame: RespFrame) -> usize {
+    subscribers
+        .iter()
+        .filter(|subscriber| subscriber.value().send(frame.clone()))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_pubsub_publish() {
+        let pubsub = PubSub::default();
+        let (tx1, mut rx1) = mpsc::unbounded_channel();
+        let (tx2, mut rx2) = mpsc::unbounded_channel();
+        pubsub.subscribe("news".into(), 1, tx1.into());
+        pubsub.subscribe("news".into(), 2, tx2.into());
+
+        assert_eq!(pubsub.numsub("news"), 2);
+        assert_eq!(pubsub.publish("news", BulkString::from("hi").into()), 2);
+        assert_eq!(pubsub.publish("sports", BulkString::from("hi").into()), 0);
+
+        let expected: RespFrame = RespPush::new(vec![
+            BulkString::from("message").into(),
+            BulkString::from("news").into(),
+            BulkString::from("hi").into(),
+        ])
+        .into();
+        assert_eq!(rx1.try_recv().unwrap(), expected);
+        assert_eq!(rx2.try_recv().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_pubsub_unsubscribe() {
+        let pubsub = PubSub::default();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        pubsub.subscribe("news".into(), 1, tx.clone().into());
+        pubsub.subscribe("events.login".into(), 1, tx.into());
+
+        let mut channels = pubsub.channels(None);
+        channels.sort();
+        assert_eq!(channels, ["events.login", "news"]);
+        assert_eq!(pubsub.channels(Some("events.*")), ["events.login"]);
+
+        assert!(pubsub.unsubscribe("news", 1));
+        assert!(!pubsub.unsubscribe("news", 1));
+        assert_eq!(pubsub.numsub("news"), 0);
+        assert_eq!(pubsub.channels(None), ["events.login"]);
+    }
+
+    #[test]
+    fn test_pubsub_shard_channels() {
+        let pubsub = PubSub::default();
+        let (tx1, mut rx1) = mpsc::unbounded_channel();
+        let (tx2, mut rx2) = mpsc::unbounded_channel();
+        pubsub.ssubscribe("{user}.orders".into(), 1, tx1.clone().into());
+        pubsub.ssubscribe("{user}.carts".into(), 1, tx1.clone().into());
+        pubsub.ssubscribe("orders".into(), 1, tx1.into());
+        pubsub.ssubscribe("orders".into(), 2, tx2.clone().into());
+        pubsub.psubscribe("*".into(), 2, tx2.into());
+
+        assert_eq!(pubsub.shard_numsub("{user}.orders"), 1);
+        assert_eq!(pubsub.shard_channels(Some("{user}.*")).len(), 2);
+        assert_eq!(pubsub.shard_numsub("orders"), 2);
+
+        // shard messages do not reach pattern subscribers
+        assert_eq!(
+            pubsub.spublish("{user}.orders", BulkString::from("hi").into()),
+            1
+        );
+        assert_eq!(
+            rx1.try_recv().unwrap(),
+            RespPush::new(vec![
+                BulkString::from("smessage").into(),
+                BulkString::from("{user}.orders").into(),
+                BulkString::from("hi").into(),
+            ])
+            .into()
+        );
+        assert!(rx2.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_pubsub_pattern() {
+        let pubsub = PubSub::default();
+        let (tx1, mut rx1) = mpsc::unbounded_channel();
+        let (tx2, mut rx2) = mpsc::unbounded_channel();
+        pubsub.subscribe("events.login".into(), 1, tx1.into());
+        pubsub.psubscribe("events.*".into(), 2, tx2.clone().into());
+        pubsub.psubscribe("*".into(), 2, tx2.into());
+        assert_eq!(pubsub.numpat(), 2);
+
+        // one channel receiver plus two matching patterns
+        assert_eq!(
+            pubsub.publish("events.login", BulkString::from("alice").into()),
+            3
+        );
+        assert!(rx1.try_recv().is_ok());
+
+        let mut messages = vec![rx2.try_recv().unwrap(), rx2.try_recv().unwrap()];
+        messages.sort();
+        let pmessage = |pattern: &str| -> RespFrame {
+            RespPush::new(vec![
+                BulkString::from("pmessage").into(),
+                BulkString::from(pattern).into(),
+                BulkString::from("events.login").into(),
+                BulkString::from("alice").into(),
+            ])
+            .into()
+        };
+        assert_eq!(messages, vec![pmessage("*"), pmessage("events.*")]);
+
+        assert!(pubsub.punsubscribe("events.*", 2));
+        assert!(!pubsub.punsubscribe("events.*", 2));
+        assert_eq!(pubsub.numpat(), 1);
+        assert_eq!(pubsub.publish("news", BulkString::from("hi").into()), 1);
+    }
+}
@@ -0,0 +1,28 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Notify;
+
+/// A request to shut the server down, made with SHUTDOWN and carried out by whoever runs the
+/// listeners.
+#[derive(Debug, Default)]
+pub struct ShutdownRequest {
+    requested: AtomicBool,
+    notify: Notify,
+}
+
+impl ShutdownRequest {
+    pub fn request(&self) {
+        self.requested.store(true, Ordering::Relaxed);
+        self.notify.notify_one();
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::Relaxed)
+    }
+
+    /// Resolves once a shutdown is requested.
+    pub async fn requested(&self) {
+        while !self.is_requested() {
+            self.notify.notified().await
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// What INFO reports of the server's activity, gathered by the network layer and the backend.
+/// CONFIG RESETSTAT zeroes the counters, the figures describing the server itself stay.
+#[derive(Debug)]
+pub struct Stats {
+    started: Instant,
+    start_time: SystemTime,
+    run_id: String,
+    replication_id: String,
+    pub connections_received: Counter,
+    pub rejected_connections: Counter,
+    pub commands_processed: Counter,
+    pub net_input_bytes: Counter,
+    pub net_output_bytes: Counter,
+    pub reads_processed: Counter,
+    pub writes_processed: Counter,
+    pub keyspace_hits: Counter,
+    pub keyspace_misses: Counter,
+    pub error_replies: Counter,
+    pub query_buffer_limit_disconnections: Counter,
+    pub output_buffer_limit_disconnections: Counter,
+    pub acl_denied_auth: Counter,
+    pub acl_denied_cmd: Counter,
+    pub acl_denied_key: Counter,
+    pub acl_denied_channel: Counter,
+    /// Changes to the dataset, never reset as there is no save to reset them.
+    pub dirty: Counter,
+    peak_memory: AtomicU64,
+    commands: Mutex<BTreeMap<&'static str, CommandStats>>,
+    // error replies by their code, the first word of the error
+    errors: Mutex<BTreeMap<String, u64>>,
+}
+
+#[derive(Debug, Default)]
+pub struct Counter(AtomicU64);
+
+/// The calls of a single command, by its full name.
+#[derive(Debug, Clone, Default)]
+pub struct CommandStats {
+    pub calls: u64,
+    pub usec: u64,
+    /// Calls refused before they ran, for their arity, permissions or arguments.
+    pub rejected_calls: u64,
+    /// Calls that ran and replied with an error.
+    pub failed_calls: u64,
+    pub latency: Histogram,
+}
+
+/// Latencies in microseconds on a log scale, 8 buckets per power of two, which keeps the
+/// percentiles within about 12% of the exact values.
+#[derive(Debug, Clone, Default)]
+pub struct Histogram {
+    buckets: Vec<u64>,
+    count: u64,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            start_time: SystemTime::now(),
+            run_id: random_id("run"),
+            replication_id: random_id("replication"),
+            connections_received: Counter::default(),
+            rejected_connections: Counter::default(),
+            commands_processed: Counter::default(),
+            net_input_bytes: Counter::default(),
+            net_output_bytes: Counter::default(),
+            reads_processed: Counter::default(),
+            writes_processed: Counter::default(),
+            keyspace_hits: Counter::default(),
+            keyspace_misses: Counter::default(),
+            error_replies: Counter::default(),
+            query_buffer_limit_disconnections: Counter::default(),
+            output_buffer_limit_disconnections: Counter::default(),
+            acl_denied_auth: Counter::default(),
+            acl_denied_cmd: Counter::default(),
+            acl_denied_key: Counter::default(),
+            acl_denied_channel: Counter::default(),
+            dirty: Counter::default(),
+            peak_memory: AtomicU64::new(0),
+            commands: Mutex::default(),
+            errors: Mutex::default(),
+        }
+    }
+}
+
+impl Stats {
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn start_time(&self) -> SystemTime {
+        self.start_time
+    }
+
+    /// 40 random hex characters telling this run of the server apart from others.
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// The replication id INFO reports, there being no replication it never changes.
+    pub fn replication_id(&self) -> &str {
+        &self.replication_id
+    }
+
+    /// Records a command that ran for `elapsed`, `failed` telling whether it replied with an
+    /// error.
+    pub fn record_call(&self, name: &'static str, elapsed: Duration, failed: bool) {
+        self.commands_processed.incr();
+        let usec = elapsed.as_micros() as u64;
+        let mut commands = self.commands.lock().unwrap();
+        let stats = commands.entry(name).or_default();
+        stats.calls += 1;
+        stats.usec += usec;
+        if failed {
+            stats.failed_calls += 1;
+        }
+        stats.latency.record(usec);
+    }
+
+    /// Records a command refused before it ran.
+    pub fn record_rejected(&self, name: &'static str) {
+        let mut commands = self.commands.lock().unwrap();
+        commands.entry(name).or_default().rejected_calls += 1;
+    }
+
+    /// Records an error reply, counted by its code.
+    pub fn record_error(&self, error: &str) {
+        self.error_replies.incr();
+        let code = error.split(' ').next().unwrap_or_default();
+        *self
+            .errors
+            .lock()
+            .unwrap()
+            .entry(code.to_string())
+            .or_default() += 1;
+    }
+
+    /// The commands called since the stats were reset, ordered by name.
+    pub fn commands(&self) -> Vec<(&'static str, CommandStats)> {
+        let commands = self.commands.lock().unwrap();
+        commands
+            .iter()
+            .map(|(name, stats)| (*name, stats.clone()))
+            .collect()
+    }
+
+    /// Error replies by code, ordered by code.
+    pub fn errors(&self) -> Vec<(String, u64)> {
+        let errors = self.errors.lock().unwrap();
+        errors
+            .iter()
+            .map(|(code, count)| (code.clone(), *count))
+            .collect()
+    }
+
+    /// The most memory seen in use, `used` included.
+    pub fn peak_memory(&self, used: u64) -> u64 {
+        self.peak_memory
+            .fetch_max(used, Ordering::Relaxed)
+            .max(used)
+    }
+
+    pub fn reset(&self) {
+        for counter in [
+            &self.connections_received,
+            &self.rejected_connections,
+            &self.commands_processed,
+            &self.net_input_bytes,
+            &self.net_output_bytes,
+            &self.reads_processed,
+            &self.writes_processed,
+            &self.keyspace_hits,
+            &self.keyspace_misses,
+            &self.error_replies,
+            &self.query_buffer_limit_disconnections,
+            &self.output_buffer_limit_disconnections,
+            &self.acl_denied_auth,
+            &self.acl_denied_cmd,
+            &self.acl_denied_key,
+            &self.acl_denied_channel,
+        ] {
+            counter.reset();
+        }
+        self.commands.lock().unwrap().clear();
+        self.errors.lock().unwrap().clear();
+    }
+}
+
+impl Counter {
+    pub fn incr(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn reset(&self) {
+        self.0.store(0, Ordering::Relaxed);
+    }
+}
+
+impl Histogram {
+    fn record(&mut self, usec: u64) {
+        // like redis, nothing takes less than a microsecond
+        let bucket = bucket(usec.max(1));
+        if self.buckets.len() <= bucket {
+            self.buckets.resize(bucket + 1, 0);
+        }
+        self.buckets[bucket] += 1;
+        self.count += 1;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// The latency `percentile` percent of the calls took at most, as the upper bound of the
+    /// bucket it falls into.
+    pub fn percentile(&self, percentile: f64) -> u64 {
+        let rank = ((percentile / 100.0 * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return upper_bound(bucket);
+            }
+        }
+        0
+    }
+}
+
+// values below 16 have a bucket each, larger ones share one with the values that agree on their
+// 4 most significant bits
+fn bucket(value: u64) -> usize {
+    if value < 16 {
+        return value as usize;
+    }
+    let exponent = 63 - value.leading_zeros() as usize;
+    let sub = (value >> (exponent - 3)) as usize & 7;
+    16 + (exponent - 4) * 8 + sub
+}
+
+fn upper_bound(bucket: usize) -> u64 {
+    if bucket < 16 {
+        return bucket as u64;
+    }
+    let exponent = (bucket - 16) / 8 + 4;
+    let sub = ((bucket - 16) % 8) as u64;
+    ((8 + sub + 1) << (exponent - 3)) - 1
+}
+
+fn random_id(purpose: &str) -> String {
+    // there is no random source at hand, a hash of what differs between runs does as well
+    let mut hasher = Sha256::new();
+    hasher.update(purpose.as_bytes());
+    hasher.update(std::process::id().to_le_bytes());
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    hasher.update(now.as_nanos().to_le_bytes());
+    hasher.update(format!("{:p}", &now).as_bytes());
+    let hash = hasher.finalize();
+    hash.iter().take(20).map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_reset() {
+        let stats = Stats::default();
+        stats.connections_received.incr();
+        stats.dirty.add(2);
+        stats.record_call("get", Duration::from_micros(10), false);
+        stats.record_call("get", Duration::from_micros(30), true);
+        stats.record_rejected("set");
+        stats.record_error("WRONGTYPE Operation against a key holding the wrong kind of value");
+
+        let commands = stats.commands();
+        assert_eq!(commands.len(), 2);
+        let (name, get) = &commands[0];
+        assert_eq!(*name, "get");
+        assert_eq!((get.calls, get.usec, get.failed_calls), (2, 40, 1));
+        assert_eq!(commands[1].1.rejected_calls, 1);
+        assert_eq!(stats.errors(), vec![("WRONGTYPE".to_string(), 1)]);
+        assert_eq!(stats.commands_processed.get(), 2);
+        assert_eq!(stats.run_id().len(), 40);
+        assert_ne!(stats.run_id(), stats.replication_id());
+
+        stats.reset();
+        assert!(stats.commands().is_empty());
+        assert!(stats.errors().is_empty());
+        assert_eq!(stats.connections_received.get(), 0);
+        assert_eq!(stats.dirty.get(), 2);
+    }
+
+    #[test]
+    fn test_histogram_percentiles() {
+        let mut histogram = Histogram::default();
+        for usec in 1..=100 {
+            histogram.record(usec);
+        }
+        assert_eq!(histogram.percentile(50.0), 51);
+        assert_eq!(histogram.percentile(99.0), 103);
+        assert_eq!(histogram.percentile(100.0), 103);
+
+        for value in [1, 15, 16, 17, 100, 1000, 123_456] {
+            let bound = upper_bound(bucket(value));
+            assert!(bound >= value && bound as f64 <= value as f64 * 1.125 + 1.0);
+        }
+    }
+}
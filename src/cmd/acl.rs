@@ -0,0 +1,615 @@
+use crate::acl::{all_commands, unix_millis, AclError, Selector, User, CATEGORIES};
+use crate::backend::Backend;
+use crate::cmd::{
+    extract_args, extract_strings, validate_command, CommandError, CommandExecutor,
+    ContextExecutor, ExecFuture, RESP_OK,
+};
+use crate::network::Session;
+use crate::{BulkString, RespArray, RespF64, RespFrame, RespMap, RespNull};
+use std::path::Path;
+
+// entries ACL LOG shows unless asked for a count
+const DEFAULT_LOG_COUNT: usize = 10;
+
+#[derive(Debug)]
+pub struct AclCat {
+    category: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct AclDelUser {
+    names: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct AclGetUser {
+    name: String,
+}
+
+#[derive(Debug)]
+pub struct AclList;
+
+#[derive(Debug)]
+pub struct AclLoad;
+
+#[derive(Debug)]
+pub struct AclLog {
+    // None resets the log
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct AclSave;
+
+#[derive(Debug)]
+pub struct AclSetUser {
+    name: String,
+    rules: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct AclUsers;
+
+#[derive(Debug)]
+pub struct AclWhoAmI;
+
+impl CommandExecutor for AclCat {
+    fn execute(self, _: &Backend) -> Result<RespFrame, CommandError> {
+        let names: Vec<RespFrame> = match self.category {
+            None => CATEGORIES
+                .iter()
+                .map(|category| BulkString::from(*category).into())
+                .collect(),
+            Some(category) => {
+                let category = category.to_ascii_lowercase();
+                if !CATEGORIES.contains(&category.as_str()) {
+                    return Err(CommandError::InvalidCmd(format!(
+                        "Unknown category '{}'",
+                        category
+                    )));
+                }
+                let category = format!("@{}", category);
+                all_commands()
+                    .filter(|spec| spec.acl_categories.contains(&category.as_str()))
+                    .map(|spec| BulkString::from(spec.name).into())
+                    .collect()
+            }
+        };
+        Ok(RespArray::new(names).into())
+    }
+}
+
+impl CommandExecutor for AclDelUser {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        let deleted = backend.acl().del_users(&self.names).map_err(acl_error)?;
+        // like redis, the connections authenticated as a deleted user are closed
+        backend
+            .clients()
+            .kill(|client| self.names.contains(&client.user));
+        Ok((deleted as i64).into())
+    }
+}
+
+impl CommandExecutor for AclGetUser {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        let Some(user) = backend.acl().user(&self.name) else {
+            return Ok(RespNull.into());
+        };
+        Ok(user_reply(&user))
+    }
+}
+
+impl CommandExecutor for AclList {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        let users: Vec<RespFrame> = backend
+            .acl()
+            .users()
+            .iter()
+            .map(|user| BulkString::from(user.to_string()).into())
+            .collect();
+        Ok(RespArray::new(users).into())
+    }
+}
+
+impl CommandExecutor for AclLoad {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        let path = acl_file(backend)?;
+        backend
+            .acl()
+            .load_file(Path::new(&path))
+            .map_err(acl_error)?;
+        Ok(RESP_OK.clone())
+    }
+}
+
+impl CommandExecutor for AclLog {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        let log = backend.acl().log();
+        let Some(count) = self.count else {
+            log.reset();
+            return Ok(RESP_OK.clone());
+        };
+
+        let entries: Vec<RespFrame> = log
+            .entries(count)
+            .into_iter()
+            .map(|entry| {
+                let mut map = RespMap::new();
+                map.insert("count".into(), (entry.count as i64).into());
+                map.insert("reason".into(), BulkString::from(entry.reason).into());
+                map.insert("context".into(), BulkString::from(entry.context).into());
+                map.insert(
+                    "object".into(),
+                    BulkString::from(entry.object.clone()).into(),
+                );
+                map.insert(
+                    "username".into(),
+                    BulkString::from(entry.username.clone()).into(),
+                );
+                map.insert(
+                    "age-seconds".into(),
+                    RespF64::new(entry.age().as_secs_f64()).into(),
+                );
+                map.insert(
+                    "client-info".into(),
+                    BulkString::from(entry.client_info.clone()).into(),
+                );
+                map.insert("entry-id".into(), (entry.entry_id as i64).into());
+                map.insert(
+                    "timestamp-created".into(),
+                    unix_millis(entry.created).into(),
+                );
+                map.insert(
+                    "timestamp-last-updated".into(),
+                    unix_millis(entry.updated).into(),
+                );
+                map.into()
+            })
+            .collect();
+        Ok(RespArray::new(entries).into())
+    }
+}
+
+impl CommandExecutor for AclSave {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        let path = acl_file(backend)?;
+        backend
+            .acl()
+            .save_file(Path::new(&path))
+            .map_err(acl_error)?;
+        Ok(RESP_OK.clone())
+    }
+}
+
+impl CommandExecutor for AclSetUser {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        backend
+            .acl()
+            .set_user(&self.name, &self.rules)
+            .map_err(acl_error)?;
+        Ok(RESP_OK.clone())
+    }
+}
+
+impl CommandExecutor for AclUsers {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        let names: Vec<RespFrame> = backend
+            .acl()
+            .users()
+            .iter()
+            .map(|user| BulkString::from(user.name()).into())
+            .collect();
+        Ok(RespArray::new(names).into())
+    }
+}
+
+impl ContextExecutor for AclWhoAmI {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        let user = BulkString::from(session.user()).into();
+        Box::pin(std::future::ready(Ok(vec![user])))
+    }
+}
+
+fn acl_error(e: AclError) -> CommandError {
+    CommandError::InvalidCmd(e.to_string())
+}
+
+fn acl_file(backend: &Backend) -> Result<String, CommandError> {
+    match backend.config().aclfile.as_str() {
+        "" => Err(acl_error(AclError::NoFile)),
+        path => Ok(path.to_string()),
+    }
+}
+
+// flags, passwords, the root selector and the others, as ACL GETUSER describes a user
+fn user_reply(user: &User) -> RespFrame {
+    let mut flags = vec![BulkString::from(if user.is_enabled() { "on" } else { "off" }).into()];
+    if user.is_nopass() {
+        flags.push(BulkString::from("nopass").into());
+    }
+    let passwords: Vec<RespFrame> = user
+        .passwords()
+        .iter()
+        .map(|hash| BulkString::from(hash.as_str()).into())
+        .collect();
+
+    let mut map = selector_reply(user.root());
+    map.insert("flags".into(), RespArray::new(flags).into());
+    map.insert("passwords".into(), RespArray::new(passwords).into());
+    let selectors: Vec<RespFrame> = user
+        .selectors()
+        .iter()
+        .map(|selector| selector_reply(selector).into())
+        .collect();
+    map.insert("selectors".into(), RespArray::new(selectors).into());
+    map.into()
+}
+
+fn selector_reply(selector: &Selector) -> RespMap {
+    let mut map = RespMap::new();
+    map.insert(
+        "commands".into(),
+        BulkString::from(selector.describe_commands()).into(),
+    );
+    map.insert(
+        "keys".into(),
+        BulkString::from(selector.describe_keys()).into(),
+    );
+    map.insert(
+        "channels".into(),
+        BulkString::from(selector.describe_channels()).into(),
+    );
+    map
+}
+
+// acl cat hash
+// *3\r\n$3\r\nacl\r\n$3\r\ncat\r\n$4\r\nhash\r\n
+impl TryFrom<RespArray> for AclCat {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["acl", "cat"])?;
+
+        let mut args = extract_strings(extract_args(value, 2)?)?;
+        if args.len() > 1 {
+            return Err(CommandError::WrongArity("acl|cat".to_string()));
+        }
+        Ok(AclCat {
+            category: args.pop(),
+        })
+    }
+}
+
+// acl deluser alice bob
+// *4\r\n$3\r\nacl\r\n$7\r\ndeluser\r\n$5\r\nalice\r\n$3\r\nbob\r\n
+impl TryFrom<RespArray> for AclDelUser {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["acl", "deluser"])?;
+
+        let names = extract_strings(extract_args(value, 2)?)?;
+        Ok(AclDelUser { names })
+    }
+}
+
+// acl getuser alice
+// *3\r\n$3\r\nacl\r\n$7\r\ngetuser\r\n$5\r\nalice\r\n
+impl TryFrom<RespArray> for AclGetUser {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["acl", "getuser"])?;
+
+        let mut args = extract_strings(extract_args(value, 2)?)?;
+        Ok(AclGetUser {
+            name: args.remove(0),
+        })
+    }
+}
+
+// acl list
+// *2\r\n$3\r\nacl\r\n$4\r\nlist\r\n
+impl TryFrom<RespArray> for AclList {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["acl", "list"])?;
+
+        Ok(AclList)
+    }
+}
+
+// acl load
+// *2\r\n$3\r\nacl\r\n$4\r\nload\r\n
+impl TryFrom<RespArray> for AclLoad {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["acl", "load"])?;
+
+        Ok(AclLoad)
+    }
+}
+
+// acl log 5
+// *3\r\n$3\r\nacl\r\n$3\r\nlog\r\n$1\r\n5\r\n
+impl TryFrom<RespArray> for AclLog {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["acl", "log"])?;
+
+        let args = extract_strings(extract_args(value, 2)?)?;
+        let count = match args.as_slice() {
+            [] => Some(DEFAULT_LOG_COUNT),
+            [arg] if arg.eq_ignore_ascii_case("reset") => None,
+            [arg] => match arg.parse::<i64>() {
+                Ok(count) if count >= 0 => Some(count as usize),
+                Ok(_) => {
+                    return Err(CommandError::InvalidCmd(
+                        "value is out of range, must be positive".to_string(),
+                    ))
+                }
+                Err(_) => {
+                    return Err(CommandError::InvalidCmd(
+                        "value is not an integer or out of range".to_string(),
+                    ))
+                }
+            },
+            _ => return Err(CommandError::WrongArity("acl|log".to_string())),
+        };
+        Ok(AclLog { count })
+    }
+}
+
+// acl save
+// *2\r\n$3\r\nacl\r\n$4\r\nsave\r\n
+impl TryFrom<RespArray> for AclSave {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["acl", "save"])?;
+
+        Ok(AclSave)
+    }
+}
+
+// acl setuser alice on >secret ~cache:* +@read
+// *7\r\n$3\r\nacl\r\n$7\r\nsetuser\r\n$5\r\nalice\r\n$2\r\non\r\n$7\r\n>secret\r\n$8\r\n~cache:*\r\n$6\r\n+@read\r\n
+impl TryFrom<RespArray> for AclSetUser {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["acl", "setuser"])?;
+
+        let mut args = extract_strings(extract_args(value, 2)?)?;
+        let name = args.remove(0);
+        Ok(AclSetUser { name, rules: args })
+    }
+}
+
+// acl users
+// *2\r\n$3\r\nacl\r\n$5\r\nusers\r\n
+impl TryFrom<RespArray> for AclUsers {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["acl", "users"])?;
+
+        Ok(AclUsers)
+    }
+}
+
+// acl whoami
+// *2\r\n$3\r\nacl\r\n$6\r\nwhoami\r\n
+impl TryFrom<RespArray> for AclWhoAmI {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["acl", "whoami"])?;
+
+        Ok(AclWhoAmI)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AclCat, AclDelUser, AclGetUser, AclList, AclLoad, AclLog, AclSave, AclSetUser, AclUsers,
+        AclWhoAmI, CATEGORIES,
+    };
+    use crate::backend::Backend;
+    use crate::cmd::{CommandExecutor, RESP_OK};
+    use crate::config::ServerConfig;
+    use crate::network::Session;
+    use crate::RespDecode;
+    use crate::{BulkString, RespArray, RespFrame, RespNull};
+    use anyhow::Result;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_acl_setuser_getuser() -> Result<()> {
+        let backend = Backend::new();
+        let mut cmd = bytes::BytesMut::from(
+            &b"*7\r\n$3\r\nacl\r\n$7\r\nsetuser\r\n$5\r\nalice\r\n$2\r\non\r\n$7\r\n>secret\r\n$8\r\n~cache:*\r\n$6\r\n+@read\r\n"[..],
+        );
+        let cmd = RespArray::decode(&mut cmd)?;
+        let setuser = AclSetUser::try_from(cmd)?;
+        assert_eq!(setuser.name, "alice");
+        assert_eq!(setuser.execute(&backend)?, RESP_OK.clone());
+
+        let mut cmd =
+            bytes::BytesMut::from(&b"*3\r\n$3\r\nacl\r\n$7\r\ngetuser\r\n$5\r\nalice\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        let RespFrame::Map(map) = AclGetUser::try_from(cmd)?.execute(&backend)? else {
+            panic!("Expected Map");
+        };
+        assert_eq!(
+            map.get(&"flags".into()),
+            Some(&RespArray::new(vec![BulkString::from("on").into()]).into())
+        );
+        let hash = crate::acl::hash_password("secret");
+        assert_eq!(
+            map.get(&"passwords".into()),
+            Some(&RespArray::new(vec![BulkString::from(hash).into()]).into())
+        );
+        assert_eq!(
+            map.get(&"commands".into()),
+            Some(&BulkString::from("-@all +@read").into())
+        );
+        assert_eq!(
+            map.get(&"keys".into()),
+            Some(&BulkString::from("~cache:*").into())
+        );
+        assert_eq!(
+            map.get(&"channels".into()),
+            Some(&BulkString::from("").into())
+        );
+
+        let getuser = AclGetUser {
+            name: "nobody".into(),
+        };
+        assert_eq!(getuser.execute(&backend)?, RespNull.into());
+
+        let setuser = AclSetUser {
+            name: "alice".into(),
+            rules: vec!["+@nope".into()],
+        };
+        assert_eq!(
+            setuser.execute(&backend).unwrap_err().to_string(),
+            "ERR Error in ACL SETUSER modifier '+@nope': Unknown command or category name in ACL"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_acl_list_users_deluser() -> Result<()> {
+        let backend = Backend::new();
+        backend.acl().set_user("alice", &["on", "nopass"])?;
+
+        assert_eq!(
+            AclList.execute(&backend)?,
+            RespArray::new(vec![
+                BulkString::from("user alice on nopass resetchannels -@all").into(),
+                BulkString::from("user default on nopass ~* &* +@all").into(),
+            ])
+            .into()
+        );
+        assert_eq!(
+            AclUsers.execute(&backend)?,
+            RespArray::new(vec![
+                BulkString::from("alice").into(),
+                BulkString::from("default").into(),
+            ])
+            .into()
+        );
+
+        let mut cmd = bytes::BytesMut::from(
+            &b"*4\r\n$3\r\nacl\r\n$7\r\ndeluser\r\n$5\r\nalice\r\n$3\r\nbob\r\n"[..],
+        );
+        let cmd = RespArray::decode(&mut cmd)?;
+        assert_eq!(AclDelUser::try_from(cmd)?.execute(&backend)?, 1.into());
+        let deluser = AclDelUser {
+            names: vec!["default".into()],
+        };
+        assert!(deluser.execute(&backend).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_acl_cat() -> Result<()> {
+        let mut cmd = bytes::BytesMut::from(&b"*3\r\n$3\r\nacl\r\n$3\r\ncat\r\n$4\r\nhash\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        assert_eq!(
+            AclCat::try_from(cmd)?.execute(&Backend::new())?,
+            RespArray::new(vec![
+                BulkString::from("hget").into(),
+                BulkString::from("hgetall").into(),
+                BulkString::from("hmget").into(),
+                BulkString::from("hset").into(),
+            ])
+            .into()
+        );
+
+        let RespFrame::Array(categories) = (AclCat { category: None }).execute(&Backend::new())?
+        else {
+            panic!("Expected Array");
+        };
+        assert_eq!(categories.len(), CATEGORIES.len());
+
+        let cat = AclCat {
+            category: Some("nope".into()),
+        };
+        assert_eq!(
+            cat.execute(&Backend::new()).unwrap_err().to_string(),
+            "ERR Unknown category 'nope'"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_acl_log_and_whoami() -> Result<()> {
+        let backend = Backend::new();
+        backend
+            .acl()
+            .set_user("alice", &["on", ">pw", "+get", "~public:*"])?;
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(backend.clone(), tx);
+        assert!(session.authenticate("alice", "pw"));
+        assert!(!session.authenticate("alice", "guess"));
+
+        let ret = crate::cmd::ContextExecutor::execute(AclWhoAmI, &mut session).await?;
+        assert_eq!(ret, vec![BulkString::from("alice").into()]);
+
+        let mut cmd = bytes::BytesMut::from(&b"*3\r\n$3\r\nacl\r\n$3\r\nlog\r\n$1\r\n5\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        let RespFrame::Array(entries) = AclLog::try_from(cmd)?.execute(&backend)? else {
+            panic!("Expected Array");
+        };
+        assert_eq!(entries.len(), 1);
+        let RespFrame::Map(entry) = &entries[0] else {
+            panic!("Expected Map");
+        };
+        assert_eq!(
+            entry.get(&"reason".into()),
+            Some(&BulkString::from("auth").into())
+        );
+        assert_eq!(
+            entry.get(&"object".into()),
+            Some(&BulkString::from("AUTH").into())
+        );
+
+        assert_eq!((AclLog { count: None }).execute(&backend)?, RESP_OK.clone());
+        assert_eq!(
+            (AclLog { count: Some(10) }).execute(&backend)?,
+            RespArray::new(vec![]).into()
+        );
+
+        let mut cmd = bytes::BytesMut::from(&b"*3\r\n$3\r\nacl\r\n$3\r\nlog\r\n$2\r\n-1\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        assert!(AclLog::try_from(cmd).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_acl_save_load() -> Result<()> {
+        let err = AclSave.execute(&Backend::new()).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("ERR This Redis instance is not configured to use an ACL file."));
+
+        let path = std::env::temp_dir().join(format!("r-redis-cmd-{}.acl", std::process::id()));
+        let backend = Backend::with_config(ServerConfig {
+            aclfile: path.display().to_string(),
+            ..Default::default()
+        });
+        backend.acl().set_user("alice", &["on", "+get"])?;
+        assert_eq!(AclSave.execute(&backend)?, RESP_OK.clone());
+        backend.acl().del_users(&["alice".into()])?;
+        assert_eq!(AclLoad.execute(&backend)?, RESP_OK.clone());
+        assert!(backend.acl().user("alice").is_some());
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use crate::cmd::{
+    extract_args, extract_strings, validate_command, CommandError, ContextExecutor, ExecFuture,
+    RESP_OK,
+};
+use crate::network::Session;
+use crate::RespArray;
+
+#[derive(Debug)]
+pub struct Auth {
+    username: Option<String>,
+    password: String,
+}
+
+impl ContextExecutor for Auth {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        Box::pin(async move {
+            let username = match self.username {
+                Some(username) => username,
+                None if !session.backend().acl().auth_required() => {
+                    return Err(CommandError::InvalidCmd(
+                        "AUTH <password> called without any password configured for the default user. Are you sure your configuration is correct?"
+                            .to_string(),
+                    ))
+                }
+                None => "default".to_string(),
+            };
+            if !session.authenticate(&username, &self.password) {
+                return Err(CommandError::WrongPass);
+            }
+            Ok(vec![RESP_OK.clone()])
+        })
+    }
+}
+
+// auth default secret
+// *3\r\n$4\r\nauth\r\n$7\r\ndefault\r\n$6\r\nsecret\r\n
+impl TryFrom<RespArray> for Auth {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["auth"])?;
+
+        let mut args = extract_strings(extract_args(value, 1)?)?;
+        match args.len() {
+            1 => Ok(Auth {
+                username: None,
+                password: args.remove(0),
+            }),
+            2 => Ok(Auth {
+                password: args.remove(1),
+                username: Some(args.remove(0)),
+            }),
+            _ => Err(CommandError::Syntax),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::{Backend, RespDecode};
+    use anyhow::Result;
+    use tokio::sync::mpsc;
+
+    fn session(requirepass: &str) -> Session {
+        let backend = Backend::with_config(ServerConfig {
+            requirepass: requirepass.to_string(),
+            ..Default::default()
+        });
+        let (tx, _rx) = mpsc::unbounded_channel();
+        Session::new(backend, tx)
+    }
+
+    #[tokio::test]
+    async fn test_auth_command() -> Result<()> {
+        let mut cmd =
+            bytes::BytesMut::from(&b"*3\r\n$4\r\nauth\r\n$7\r\ndefault\r\n$6\r\nsecret\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        let auth = Auth::try_from(cmd)?;
+        assert_eq!(auth.username.as_deref(), Some("default"));
+        assert_eq!(auth.password, "secret");
+
+        let mut session = session("secret");
+        assert!(session.auth_required());
+        assert_eq!(auth.execute(&mut session).await?, vec![RESP_OK.clone()]);
+        assert!(!session.auth_required());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_auth_command_errors() -> Result<()> {
+        let mut session = session("secret");
+        let auth = Auth {
+            username: None,
+            password: "guess".into(),
+        };
+        assert_eq!(
+            auth.execute(&mut session).await.unwrap_err(),
+            CommandError::WrongPass
+        );
+        let auth = Auth {
+            username: Some("alice".into()),
+            password: "secret".into(),
+        };
+        assert_eq!(
+            auth.execute(&mut session).await.unwrap_err(),
+            CommandError::WrongPass
+        );
+        assert!(session.auth_required());
+
+        let mut session = self::session("");
+        let auth = Auth {
+            username: None,
+            password: "secret".into(),
+        };
+        assert!(auth
+            .execute(&mut session)
+            .await
+            .unwrap_err()
+            .to_string()
+            .starts_with("ERR AUTH <password> called without any password configured"));
+
+        // auth a b c
+        let mut cmd =
+            bytes::BytesMut::from(&b"*4\r\n$4\r\nauth\r\n$1\r\na\r\n$1\r\nb\r\n$1\r\nc\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        assert_eq!(Auth::try_from(cmd).unwrap_err(), CommandError::Syntax);
+        Ok(())
+    }
+}
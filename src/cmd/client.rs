@@ -0,0 +1,642 @@
+use crate::backend::{Backend, ClientInfo, PauseMode};
+use crate::cmd::{
+    extract_args, extract_strings, validate_command, CommandError, CommandExecutor,
+    ContextExecutor, ExecFuture, RESP_OK,
+};
+use crate::network::{ReplyMode, Session};
+use crate::{BulkString, RespArray, RespFrame, RespNull};
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+pub struct ClientId;
+
+#[derive(Debug)]
+pub struct ClientGetName;
+
+#[derive(Debug)]
+pub struct ClientSetName {
+    name: String,
+}
+
+#[derive(Debug)]
+pub struct ClientInfoCmd;
+
+#[derive(Debug)]
+pub struct ClientList {
+    client_type: Option<String>,
+    ids: Vec<u64>,
+}
+
+#[derive(Debug)]
+pub struct ClientKill {
+    filter: KillFilter,
+    // the old form naming a single address, answered with OK rather than a count
+    legacy: bool,
+}
+
+#[derive(Debug)]
+pub struct ClientPause {
+    timeout: Duration,
+    mode: PauseMode,
+}
+
+#[derive(Debug)]
+pub struct ClientUnpause;
+
+#[derive(Debug)]
+pub struct ClientNoEvict {
+    on: bool,
+}
+
+#[derive(Debug)]
+pub struct ClientReply {
+    mode: ReplyMode,
+}
+
+// the clients CLIENT KILL closes, every condition given has to match
+#[derive(Debug, Default)]
+struct KillFilter {
+    id: Option<u64>,
+    client_type: Option<String>,
+    user: Option<String>,
+    addr: Option<String>,
+    laddr: Option<String>,
+    max_age: Option<u64>,
+    skip_me: bool,
+}
+
+impl ContextExecutor for ClientId {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        let id = (session.id() as i64).into();
+        Box::pin(std::future::ready(Ok(vec![id])))
+    }
+}
+
+impl ContextExecutor for ClientGetName {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        let name = match session.name() {
+            Some(name) => BulkString::from(name).into(),
+            None => RespNull.into(),
+        };
+        Box::pin(std::future::ready(Ok(vec![name])))
+    }
+}
+
+impl ContextExecutor for ClientSetName {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        let reply = validate_client_name(&self.name).map(|_| {
+            session.set_name(Some(self.name).filter(|name| !name.is_empty()));
+            vec![RESP_OK.clone()]
+        });
+        Box::pin(std::future::ready(reply))
+    }
+}
+
+impl ContextExecutor for ClientInfoCmd {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        let info = BulkString::from(format!("{}\n", session.client_info())).into();
+        Box::pin(std::future::ready(Ok(vec![info])))
+    }
+}
+
+impl CommandExecutor for ClientList {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        let mut list = String::new();
+        for client in backend.clients().list() {
+            if self
+                .client_type
+                .as_ref()
+                .is_some_and(|t| !type_matches(t, &client))
+                || (!self.ids.is_empty() && !self.ids.contains(&client.id))
+            {
+                continue;
+            }
+            list.push_str(&format!("{}\n", client));
+        }
+        Ok(BulkString::from(list).into())
+    }
+}
+
+impl ContextExecutor for ClientKill {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        let own_id = session.id();
+        let clients = session.backend().clients();
+        let killed = clients.kill(|client| self.filter.matches(client, own_id));
+        let reply = match (self.legacy, killed) {
+            (true, 0) => Err(CommandError::InvalidCmd("No such client".to_string())),
+            (true, _) => Ok(RESP_OK.clone()),
+            (false, killed) => Ok((killed as i64).into()),
+        };
+        Box::pin(std::future::ready(reply.map(|frame| vec![frame])))
+    }
+}
+
+impl CommandExecutor for ClientPause {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        backend
+            .clients()
+            .pause(self.mode, Instant::now() + self.timeout);
+        Ok(RESP_OK.clone())
+    }
+}
+
+impl CommandExecutor for ClientUnpause {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        backend.clients().unpause();
+        Ok(RESP_OK.clone())
+    }
+}
+
+impl ContextExecutor for ClientNoEvict {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        session.set_no_evict(self.on);
+        Box::pin(std::future::ready(Ok(vec![RESP_OK.clone()])))
+    }
+}
+
+impl ContextExecutor for ClientReply {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        // the OK is dropped again unless replies were turned on
+        session.set_reply(self.mode);
+        Box::pin(std::future::ready(Ok(vec![RESP_OK.clone()])))
+    }
+}
+
+impl KillFilter {
+    fn matches(&self, client: &ClientInfo, own_id: u64) -> bool {
+        let addr = client.addr.as_ref();
+        !(self.skip_me && client.id == own_id)
+            && self.id.is_none_or(|id| id == client.id)
+            && self
+                .client_type
+                .as_ref()
+                .is_none_or(|t| type_matches(t, client))
+            && self.user.as_ref().is_none_or(|user| *user == client.user)
+            && self
+                .addr
+                .as_ref()
+                .is_none_or(|a| addr.is_some_and(|addr| addr.addr() == *a))
+            && self
+                .laddr
+                .as_ref()
+                .is_none_or(|a| addr.is_some_and(|addr| addr.laddr() == *a))
+            && self
+                .max_age
+                .is_none_or(|age| client.created.elapsed().as_secs() >= age)
+    }
+}
+
+// there are no masters or replicas among the clients, those types never match
+fn type_matches(client_type: &str, client: &ClientInfo) -> bool {
+    client_type == client.client_type()
+}
+
+fn parse_client_type(value: &str) -> Result<String, CommandError> {
+    match value.to_ascii_lowercase().as_str() {
+        t @ ("normal" | "master" | "replica" | "pubsub") => Ok(t.to_string()),
+        "slave" => Ok("replica".to_string()),
+        _ => Err(CommandError::InvalidCmd(format!(
+            "Unknown client type '{}'",
+            value
+        ))),
+    }
+}
+
+/// Client names may be empty, which clears them, or else printable without spaces.
+pub(crate) fn validate_client_name(name: &str) -> Result<(), CommandError> {
+    if name.bytes().all(|c| (b'!'..=b'~').contains(&c)) {
+        return Ok(());
+    }
+    Err(CommandError::InvalidCmd(
+        "Client names cannot contain spaces, newlines or special characters.".to_string(),
+    ))
+}
+
+// client id
+// *2\r\n$6\r\nclient\r\n$2\r\nid\r\n
+impl TryFrom<RespArray> for ClientId {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["client", "id"])?;
+
+        Ok(ClientId)
+    }
+}
+
+// client getname
+// *2\r\n$6\r\nclient\r\n$7\r\ngetname\r\n
+impl TryFrom<RespArray> for ClientGetName {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["client", "getname"])?;
+
+        Ok(ClientGetName)
+    }
+}
+
+// client setname worker
+// *3\r\n$6\r\nclient\r\n$7\r\nsetname\r\n$6\r\nworker\r\n
+impl TryFrom<RespArray> for ClientSetName {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["client", "setname"])?;
+
+        let mut args = extract_strings(extract_args(value, 2)?)?;
+        Ok(ClientSetName {
+            name: args.remove(0),
+        })
+    }
+}
+
+// client info
+// *2\r\n$6\r\nclient\r\n$4\r\ninfo\r\n
+impl TryFrom<RespArray> for ClientInfoCmd {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["client", "info"])?;
+
+        Ok(ClientInfoCmd)
+    }
+}
+
+// client list type normal id 1 2
+// *7\r\n$6\r\nclient\r\n$4\r\nlist\r\n$4\r\ntype\r\n$6\r\nnormal\r\n$2\r\nid\r\n$1\r\n1\r\n$1\r\n2\r\n
+impl TryFrom<RespArray> for ClientList {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["client", "list"])?;
+
+        let args = extract_strings(extract_args(value, 2)?)?;
+        let mut list = ClientList {
+            client_type: None,
+            ids: vec![],
+        };
+        let mut i = 0;
+        while i < args.len() {
+            match args[i].to_ascii_lowercase().as_str() {
+                "type" if i + 1 < args.len() => {
+                    list.client_type = Some(parse_client_type(&args[i + 1])?);
+                    i += 2;
+                }
+                "id" if i + 1 < args.len() => {
+                    for id in &args[i + 1..] {
+                        match id.parse::<u64>() {
+                            Ok(id) if id > 0 => list.ids.push(id),
+                            _ => {
+                                return Err(CommandError::InvalidCmd(
+                                    "Invalid client ID".to_string(),
+                                ))
+                            }
+                        }
+                    }
+                    i = args.len();
+                }
+                _ => return Err(CommandError::Syntax),
+            }
+        }
+        Ok(list)
+    }
+}
+
+// client kill user alice skipme no
+// *6\r\n$6\r\nclient\r\n$4\r\nkill\r\n$4\r\nuser\r\n$5\r\nalice\r\n$6\r\nskipme\r\n$2\r\nno\r\n
+impl TryFrom<RespArray> for ClientKill {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["client", "kill"])?;
+
+        let args = extract_strings(extract_args(value, 2)?)?;
+        if let [addr] = args.as_slice() {
+            let filter = KillFilter {
+                addr: Some(addr.clone()),
+                ..Default::default()
+            };
+            return Ok(ClientKill {
+                filter,
+                legacy: true,
+            });
+        }
+        if args.len() % 2 != 0 {
+            return Err(CommandError::Syntax);
+        }
+
+        let mut filter = KillFilter {
+            skip_me: true,
+            ..Default::default()
+        };
+        for pair in args.chunks(2) {
+            let value = &pair[1];
+            match pair[0].to_ascii_lowercase().as_str() {
+                "id" => match value.parse::<u64>() {
+                    Ok(id) if id > 0 => filter.id = Some(id),
+                    _ => {
+                        return Err(CommandError::InvalidCmd(
+                            "client-id should be greater than 0".to_string(),
+                        ))
+                    }
+                },
+                "type" => filter.client_type = Some(parse_client_type(value)?),
+                "user" => filter.user = Some(value.clone()),
+                "addr" => filter.addr = Some(value.clone()),
+                "laddr" => filter.laddr = Some(value.clone()),
+                "maxage" => {
+                    filter.max_age = Some(value.parse::<u64>().map_err(|_| {
+                        CommandError::InvalidCmd(
+                            "value is not an integer or out of range".to_string(),
+                        )
+                    })?)
+                }
+                "skipme" => match value.to_ascii_lowercase().as_str() {
+                    "yes" => filter.skip_me = true,
+                    "no" => filter.skip_me = false,
+                    _ => return Err(CommandError::Syntax),
+                },
+                _ => return Err(CommandError::Syntax),
+            }
+        }
+        Ok(ClientKill {
+            filter,
+            legacy: false,
+        })
+    }
+}
+
+// client pause 1000 write
+// *4\r\n$6\r\nclient\r\n$5\r\npause\r\n$4\r\n1000\r\n$5\r\nwrite\r\n
+impl TryFrom<RespArray> for ClientPause {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["client", "pause"])?;
+
+        let args = extract_strings(extract_args(value, 2)?)?;
+        let timeout = match args[0].parse::<i64>() {
+            Ok(ms) if ms >= 0 => Duration::from_millis(ms as u64),
+            Ok(_) => return Err(CommandError::InvalidCmd("timeout is negative".to_string())),
+            Err(_) => {
+                return Err(CommandError::InvalidCmd(
+                    "timeout is not an integer or out of range".to_string(),
+                ))
+            }
+        };
+        let mode = match args.get(1).map(|mode| mode.to_ascii_lowercase()).as_deref() {
+            None | Some("all") if args.len() <= 2 => PauseMode::All,
+            Some("write") if args.len() == 2 => PauseMode::Write,
+            _ => return Err(CommandError::Syntax),
+        };
+        Ok(ClientPause { timeout, mode })
+    }
+}
+
+// client unpause
+// *2\r\n$6\r\nclient\r\n$7\r\nunpause\r\n
+impl TryFrom<RespArray> for ClientUnpause {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["client", "unpause"])?;
+
+        Ok(ClientUnpause)
+    }
+}
+
+// client no-evict on
+// *3\r\n$6\r\nclient\r\n$8\r\nno-evict\r\n$2\r\non\r\n
+impl TryFrom<RespArray> for ClientNoEvict {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["client", "no-evict"])?;
+
+        let args = extract_strings(extract_args(value, 2)?)?;
+        match args[0].to_ascii_lowercase().as_str() {
+            "on" => Ok(ClientNoEvict { on: true }),
+            "off" => Ok(ClientNoEvict { on: false }),
+            _ => Err(CommandError::Syntax),
+        }
+    }
+}
+
+// client reply skip
+// *3\r\n$6\r\nclient\r\n$5\r\nreply\r\n$4\r\nskip\r\n
+impl TryFrom<RespArray> for ClientReply {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["client", "reply"])?;
+
+        let args = extract_strings(extract_args(value, 2)?)?;
+        let mode = match args[0].to_ascii_lowercase().as_str() {
+            "on" => ReplyMode::On,
+            "off" => ReplyMode::Off,
+            "skip" => ReplyMode::Skip,
+            _ => return Err(CommandError::Syntax),
+        };
+        Ok(ClientReply { mode })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespDecode;
+    use anyhow::Result;
+    use tokio::sync::mpsc;
+
+    fn session(backend: &Backend) -> Session {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        Session::new(backend.clone(), tx)
+    }
+
+    #[tokio::test]
+    async fn test_client_setname_getname() -> Result<()> {
+        let backend = Backend::new();
+        let mut session = session(&backend);
+
+        let mut cmd =
+            bytes::BytesMut::from(&b"*3\r\n$6\r\nclient\r\n$7\r\nsetname\r\n$6\r\nworker\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        let setname = ClientSetName::try_from(cmd)?;
+        assert_eq!(
+            ContextExecutor::execute(setname, &mut session).await?,
+            vec![RESP_OK.clone()]
+        );
+        assert_eq!(
+            ContextExecutor::execute(ClientGetName, &mut session).await?,
+            vec![BulkString::from("worker").into()]
+        );
+
+        let setname = ClientSetName { name: "a b".into() };
+        assert!(ContextExecutor::execute(setname, &mut session)
+            .await
+            .is_err());
+        let setname = ClientSetName { name: "".into() };
+        ContextExecutor::execute(setname, &mut session).await?;
+        assert_eq!(
+            ContextExecutor::execute(ClientGetName, &mut session).await?,
+            vec![RespNull.into()]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_list_and_info() -> Result<()> {
+        let backend = Backend::new();
+        let mut first = session(&backend);
+        let second = session(&backend);
+        first.set_name(Some("first".into()));
+
+        let ret = ContextExecutor::execute(ClientInfoCmd, &mut first).await?;
+        let expected = format!(
+            "id={} addr= laddr= name=first age=0 idle=0 flags=N",
+            first.id()
+        );
+        let [RespFrame::BulkString(info)] = &ret[..] else {
+            panic!("Expected BulkString");
+        };
+        assert!(String::from_utf8_lossy(info).starts_with(&expected));
+
+        let mut cmd = bytes::BytesMut::from(
+            &b"*7\r\n$6\r\nclient\r\n$4\r\nlist\r\n$4\r\ntype\r\n$6\r\nnormal\r\n$2\r\nid\r\n$1\r\n1\r\n$1\r\n2\r\n"[..],
+        );
+        let cmd = RespArray::decode(&mut cmd)?;
+        let list = ClientList::try_from(cmd)?;
+        assert_eq!(list.ids, [1, 2]);
+
+        let list = ClientList {
+            client_type: None,
+            ids: vec![second.id()],
+        };
+        let RespFrame::BulkString(lines) = CommandExecutor::execute(list, &backend)? else {
+            panic!("Expected BulkString");
+        };
+        let lines = String::from_utf8_lossy(&lines).into_owned();
+        assert_eq!(lines.lines().count(), 1);
+        assert!(lines.starts_with(&format!("id={} ", second.id())));
+        assert!(lines.ends_with('\n'));
+
+        let list = ClientList {
+            client_type: Some("pubsub".into()),
+            ids: vec![],
+        };
+        assert_eq!(
+            CommandExecutor::execute(list, &backend)?,
+            BulkString::from("").into()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_kill() -> Result<()> {
+        let backend = Backend::new();
+        let mut admin = session(&backend);
+        let victim = session(&backend);
+
+        let mut cmd = bytes::BytesMut::from(
+            &b"*6\r\n$6\r\nclient\r\n$4\r\nkill\r\n$4\r\nuser\r\n$5\r\nalice\r\n$6\r\nskipme\r\n$2\r\nno\r\n"[..],
+        );
+        let cmd = RespArray::decode(&mut cmd)?;
+        let kill = ClientKill::try_from(cmd)?;
+        assert!(!kill.filter.skip_me);
+        assert_eq!(
+            ContextExecutor::execute(kill, &mut admin).await?,
+            vec![0.into()]
+        );
+
+        // the killer itself is skipped unless told otherwise
+        let kill = ClientKill {
+            filter: KillFilter {
+                client_type: Some("normal".into()),
+                skip_me: true,
+                ..Default::default()
+            },
+            legacy: false,
+        };
+        assert_eq!(
+            ContextExecutor::execute(kill, &mut admin).await?,
+            vec![1.into()]
+        );
+        assert!(victim.client().is_killed());
+        assert!(!admin.client().is_killed());
+
+        let kill = ClientKill {
+            filter: KillFilter {
+                addr: Some("127.0.0.1:1".into()),
+                ..Default::default()
+            },
+            legacy: true,
+        };
+        assert_eq!(
+            ContextExecutor::execute(kill, &mut admin)
+                .await
+                .unwrap_err()
+                .to_string(),
+            "ERR No such client"
+        );
+
+        let mut cmd = bytes::BytesMut::from(
+            &b"*4\r\n$6\r\nclient\r\n$4\r\nkill\r\n$4\r\ntype\r\n$4\r\nnope\r\n"[..],
+        );
+        let cmd = RespArray::decode(&mut cmd)?;
+        assert_eq!(
+            ClientKill::try_from(cmd).unwrap_err().to_string(),
+            "ERR Unknown client type 'nope'"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_pause_args() -> Result<()> {
+        let mut cmd = bytes::BytesMut::from(
+            &b"*4\r\n$6\r\nclient\r\n$5\r\npause\r\n$4\r\n1000\r\n$5\r\nwrite\r\n"[..],
+        );
+        let cmd = RespArray::decode(&mut cmd)?;
+        let pause = ClientPause::try_from(cmd)?;
+        assert_eq!(pause.timeout, Duration::from_secs(1));
+        assert_eq!(pause.mode, PauseMode::Write);
+
+        let mut cmd =
+            bytes::BytesMut::from(&b"*3\r\n$6\r\nclient\r\n$5\r\npause\r\n$2\r\n-1\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        assert_eq!(
+            ClientPause::try_from(cmd).unwrap_err().to_string(),
+            "ERR timeout is negative"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_reply() -> Result<()> {
+        let backend = Backend::new();
+        let mut session = session(&backend);
+        let ok = || vec![RESP_OK.clone()];
+
+        let mut cmd =
+            bytes::BytesMut::from(&b"*3\r\n$6\r\nclient\r\n$5\r\nreply\r\n$4\r\nskip\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        let reply = ClientReply::try_from(cmd)?;
+        let ret = ContextExecutor::execute(reply, &mut session).await?;
+        assert!(session.filter_replies(ret).is_empty());
+        assert!(session.filter_replies(ok()).is_empty());
+        assert_eq!(session.filter_replies(ok()), ok());
+
+        let reply = ClientReply {
+            mode: ReplyMode::Off,
+        };
+        let ret = ContextExecutor::execute(reply, &mut session).await?;
+        assert!(session.filter_replies(ret).is_empty());
+        assert!(session.filter_replies(ok()).is_empty());
+        let reply = ClientReply {
+            mode: ReplyMode::On,
+        };
+        let ret = ContextExecutor::execute(reply, &mut session).await?;
+        assert_eq!(session.filter_replies(ret), ok());
+        Ok(())
+    }
+}
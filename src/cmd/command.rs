@@ -0,0 +1,418 @@
+use crate::backend::Backend;
+use crate::cmd::table::{self, CommandSpec, COMMAND_TABLE};
+use crate::cmd::{extract_args, extract_strings, validate_command, CommandError, CommandExecutor};
+use crate::glob::glob_match;
+use crate::{BulkString, RespArray, RespFrame, RespMap, RespNull, RespSet, SimpleString};
+
+#[derive(Debug)]
+pub struct CommandCount;
+
+/// COMMAND INFO, and COMMAND alone when `names` is empty.
+#[derive(Debug)]
+pub struct CommandInfo {
+    names: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct CommandDocs {
+    names: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct CommandList {
+    filter: Option<ListFilter>,
+}
+
+#[derive(Debug)]
+enum ListFilter {
+    Module,
+    AclCat(String),
+    Pattern(String),
+}
+
+#[derive(Debug)]
+pub struct CommandGetKeys {
+    args: RespArray,
+}
+
+impl CommandExecutor for CommandCount {
+    fn execute(self, _: &Backend) -> Result<RespFrame, CommandError> {
+        Ok((COMMAND_TABLE.len() as i64).into())
+    }
+}
+
+impl CommandExecutor for CommandInfo {
+    fn execute(self, _: &Backend) -> Result<RespFrame, CommandError> {
+        let infos = if self.names.is_empty() {
+            COMMAND_TABLE.iter().map(info_reply).collect()
+        } else {
+            self.names
+                .iter()
+                .map(|name| table::lookup(name).map_or_else(|| RespNull.into(), info_reply))
+                .collect()
+        };
+        Ok(RespArray::new(infos).into())
+    }
+}
+
+impl CommandExecutor for CommandDocs {
+    fn execute(self, _: &Backend) -> Result<RespFrame, CommandError> {
+        let mut docs = RespMap::new();
+        if self.names.is_empty() {
+            for spec in COMMAND_TABLE {
+                docs.insert(spec.name.into(), docs_reply(spec));
+            }
+        } else {
+            // unknown commands are left out of the reply
+            for spec in self.names.iter().filter_map(|name| table::lookup(name)) {
+                docs.insert(spec.name.into(), docs_reply(spec));
+            }
+        }
+        Ok(docs.into())
+    }
+}
+
+impl CommandExecutor for CommandList {
+    fn execute(self, _: &Backend) -> Result<RespFrame, CommandError> {
+        let names = COMMAND_TABLE
+            .iter()
+            .flat_map(|spec| std::iter::once(spec).chain(spec.subcommands))
+            .filter(|spec| match &self.filter {
+                None => true,
+                // there are no modules to load commands from
+                Some(ListFilter::Module) => false,
+                Some(ListFilter::AclCat(cat)) => spec
+                    .acl_categories
+                    .iter()
+                    .any(|c| c[1..].eq_ignore_ascii_case(cat)),
+                Some(ListFilter::Pattern(pattern)) => {
+                    glob_match(pattern.as_bytes(), spec.name.as_bytes())
+                }
+            })
+            .map(|spec| BulkString::from(spec.name).into())
+            .collect();
+        Ok(RespArray::new(names).into())
+    }
+}
+
+impl CommandExecutor for CommandGetKeys {
+    fn execute(self, _: &Backend) -> Result<RespFrame, CommandError> {
+        let spec = table::resolve(&self.args).map_err(|e| match e {
+            CommandError::WrongArity(_) => CommandError::InvalidCmd(
+                "Invalid number of arguments specified for command".to_string(),
+            ),
+            _ => CommandError::InvalidCmd("Invalid command specified".to_string()),
+        })?;
+        let positions = spec.key_positions(self.args.len());
+        if positions.is_empty() {
+            return Err(CommandError::InvalidCmd(
+                "The command has no key arguments".to_string(),
+            ));
+        }
+        let keys = positions
+            .into_iter()
+            .map(|i| self.args[i].clone())
+            .collect();
+        Ok(RespArray::new(keys).into())
+    }
+}
+
+// [name, arity, flags, first key, last key, step, acl categories, tips, key specs, subcommands]
+fn info_reply(spec: &CommandSpec) -> RespFrame {
+    RespArray::new(vec![
+        BulkString::from(spec.name).into(),
+        spec.arity.into(),
+        simple_set(spec.flags),
+        spec.first_key.into(),
+        spec.last_key.into(),
+        spec.key_step.into(),
+        simple_set(spec.acl_categories),
+        RespArray::new(vec![]).into(),
+        RespArray::new(key_specs_reply(spec)).into(),
+        RespArray::new(spec.subcommands.iter().map(info_reply).collect()).into(),
+    ])
+    .into()
+}
+
+// the key specs derived from the legacy first, last and step positions
+fn key_specs_reply(spec: &CommandSpec) -> Vec<RespFrame> {
+    if spec.first_key <= 0 {
+        return vec![];
+    }
+
+    let mut begin_search = RespMap::new();
+    begin_search.insert("type".into(), BulkString::from("index").into());
+    let mut index = RespMap::new();
+    index.insert("index".into(), spec.first_key.into());
+    begin_search.insert("spec".into(), index.into());
+
+    // find_keys counts the last key from the first one, unless it counts from the end
+    let last_key = if spec.last_key < 0 {
+        spec.last_key
+    } else {
+        spec.last_key - spec.first_key
+    };
+    let mut find_keys = RespMap::new();
+    find_keys.insert("type".into(), BulkString::from("range").into());
+    let mut range = RespMap::new();
+    range.insert("lastkey".into(), last_key.into());
+    range.insert("keystep".into(), spec.key_step.into());
+    range.insert("limit".into(), 0.into());
+    find_keys.insert("spec".into(), range.into());
+
+    let mut key_spec = RespMap::new();
+    key_spec.insert("flags".into(), simple_set(spec.key_flags));
+    key_spec.insert("begin_search".into(), begin_search.into());
+    key_spec.insert("find_keys".into(), find_keys.into());
+    vec![key_spec.into()]
+}
+
+fn docs_reply(spec: &CommandSpec) -> RespFrame {
+    let mut doc = RespMap::new();
+    doc.insert("summary".into(), BulkString::from(spec.summary).into());
+    doc.insert("since".into(), BulkString::from(spec.since).into());
+    doc.insert("group".into(), BulkString::from(spec.group).into());
+    doc.insert(
+        "complexity".into(),
+        BulkString::from(spec.complexity).into(),
+    );
+    if !spec.subcommands.is_empty() {
+        let mut subcommands = RespMap::new();
+        for sub in spec.subcommands {
+            subcommands.insert(sub.name.into(), docs_reply(sub));
+        }
+        doc.insert("subcommands".into(), subcommands.into());
+    }
+    doc.into()
+}
+
+fn simple_set(items: &[&str]) -> RespFrame {
+    let mut set = RespSet::new();
+    for item in items {
+        set.insert(SimpleString::from(*item).into());
+    }
+    set.into()
+}
+
+impl CommandInfo {
+    pub fn all() -> Self {
+        CommandInfo { names: vec![] }
+    }
+}
+
+// command info get hset
+// *4\r\n$7\r\ncommand\r\n$4\r\ninfo\r\n$3\r\nget\r\n$4\r\nhset\r\n
+impl TryFrom<RespArray> for CommandInfo {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["command", "info"])?;
+
+        let names = extract_strings(extract_args(value, 2)?)?;
+        Ok(CommandInfo { names })
+    }
+}
+
+// command docs get
+// *3\r\n$7\r\ncommand\r\n$4\r\ndocs\r\n$3\r\nget\r\n
+impl TryFrom<RespArray> for CommandDocs {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["command", "docs"])?;
+
+        let names = extract_strings(extract_args(value, 2)?)?;
+        Ok(CommandDocs { names })
+    }
+}
+
+// command list filterby aclcat hash
+// *5\r\n$7\r\ncommand\r\n$4\r\nlist\r\n$8\r\nfilterby\r\n$6\r\naclcat\r\n$4\r\nhash\r\n
+impl TryFrom<RespArray> for CommandList {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["command", "list"])?;
+
+        let args = extract_strings(extract_args(value, 2)?)?;
+        let filter = match args.as_slice() {
+            [] => None,
+            [filterby, kind, arg] if filterby.eq_ignore_ascii_case("filterby") => {
+                match kind.to_ascii_lowercase().as_str() {
+                    "module" => Some(ListFilter::Module),
+                    "aclcat" => Some(ListFilter::AclCat(arg.clone())),
+                    "pattern" => Some(ListFilter::Pattern(arg.clone())),
+                    _ => return Err(CommandError::Syntax),
+                }
+            }
+            _ => return Err(CommandError::Syntax),
+        };
+        Ok(CommandList { filter })
+    }
+}
+
+// command getkeys get key
+// *4\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n$3\r\nget\r\n$3\r\nkey\r\n
+impl TryFrom<RespArray> for CommandGetKeys {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["command", "getkeys"])?;
+
+        let args = RespArray::new(extract_args(value, 2)?);
+        Ok(CommandGetKeys { args })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::{Command, ContextExecutor};
+    use crate::network::Session;
+    use crate::RespDecode;
+    use anyhow::Result;
+
+    async fn execute(data: &[u8]) -> Result<RespFrame, CommandError> {
+        let mut buf = bytes::BytesMut::from(data);
+        let cmd = Command::try_from(RespFrame::decode(&mut buf).unwrap())?;
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut session = Session::new(Backend::new(), tx);
+        let mut frames = cmd.execute(&mut session).await?;
+        Ok(frames.remove(0))
+    }
+
+    fn names(frame: RespFrame) -> Vec<String> {
+        match frame {
+            RespFrame::Array(array) => array
+                .iter()
+                .map(|name| match name {
+                    RespFrame::BulkString(name) => String::from_utf8_lossy(name).into(),
+                    frame => panic!("unexpected frame {:?}", frame),
+                })
+                .collect(),
+            frame => panic!("unexpected frame {:?}", frame),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_command_count() -> Result<()> {
+        // COMMAND COUNT
+        let resp = execute(b"*2\r\n$7\r\nCOMMAND\r\n$5\r\nCOUNT\r\n").await?;
+        assert_eq!(resp, (COMMAND_TABLE.len() as i64).into());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_command_info() -> Result<()> {
+        // command info get nope
+        let resp =
+            execute(b"*4\r\n$7\r\ncommand\r\n$4\r\ninfo\r\n$3\r\nget\r\n$4\r\nnope\r\n").await?;
+        let RespFrame::Array(infos) = resp else {
+            panic!("expected an array");
+        };
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[1], RespNull.into());
+        let RespFrame::Array(get) = &infos[0] else {
+            panic!("expected an array");
+        };
+        assert_eq!(get.len(), 10);
+        assert_eq!(get[0], BulkString::from("get").into());
+        assert_eq!(get[1], 2.into());
+        assert_eq!(get[2], simple_set(&["readonly", "fast"]));
+        assert_eq!(get[3..6], [1.into(), 1.into(), 1.into()]);
+
+        // command
+        let resp = execute(b"*1\r\n$7\r\ncommand\r\n").await?;
+        let RespFrame::Array(infos) = resp else {
+            panic!("expected an array");
+        };
+        assert_eq!(infos.len(), COMMAND_TABLE.len());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_command_docs() -> Result<()> {
+        // command docs config
+        let resp = execute(b"*3\r\n$7\r\ncommand\r\n$4\r\ndocs\r\n$6\r\nconfig\r\n").await?;
+        let RespFrame::Map(docs) = resp else {
+            panic!("expected a map");
+        };
+        let Some(RespFrame::Map(config)) = docs.get(&"config".into()) else {
+            panic!("expected the config docs");
+        };
+        assert_eq!(
+            config.get(&"group".into()),
+            Some(&BulkString::from("server").into())
+        );
+        let Some(RespFrame::Map(subcommands)) = config.get(&"subcommands".into()) else {
+            panic!("expected the config subcommands");
+        };
+        assert!(subcommands.contains_key(&"config|get".into()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_command_list() -> Result<()> {
+        // command list filterby pattern config*
+        let resp = execute(
+            b"*5\r\n$7\r\ncommand\r\n$4\r\nlist\r\n$8\r\nfilterby\r\n$7\r\npattern\r\n$7\r\nconfig*\r\n",
+        )
+        .await?;
+        assert_eq!(
+            names(resp),
+            [
+                "config",
+                "config|get",
+                "config|resetstat",
+                "config|rewrite",
+                "config|set"
+            ]
+        );
+
+        // command list filterby aclcat hash
+        let resp = execute(
+            b"*5\r\n$7\r\ncommand\r\n$4\r\nlist\r\n$8\r\nfilterby\r\n$6\r\naclcat\r\n$4\r\nhash\r\n",
+        )
+        .await?;
+        assert_eq!(names(resp), ["hget", "hgetall", "hmget", "hset"]);
+
+        // command list filterby
+        let resp = execute(b"*3\r\n$7\r\ncommand\r\n$4\r\nlist\r\n$8\r\nfilterby\r\n").await;
+        assert_eq!(resp, Err(CommandError::Syntax));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_command_getkeys() -> Result<()> {
+        // command getkeys hset key field value
+        let resp = execute(
+            b"*6\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n$4\r\nhset\r\n$3\r\nkey\r\n$5\r\nfield\r\n$5\r\nvalue\r\n",
+        )
+        .await?;
+        assert_eq!(names(resp), ["key"]);
+
+        // command getkeys echo hello
+        let resp =
+            execute(b"*4\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n$4\r\necho\r\n$5\r\nhello\r\n").await;
+        assert_eq!(
+            resp.unwrap_err().to_string(),
+            "ERR The command has no key arguments"
+        );
+
+        // command getkeys get
+        let resp = execute(b"*3\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n$3\r\nget\r\n").await;
+        assert_eq!(
+            resp.unwrap_err().to_string(),
+            "ERR Invalid number of arguments specified for command"
+        );
+
+        // command getkeys nope key
+        let resp =
+            execute(b"*4\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n$4\r\nnope\r\n$3\r\nkey\r\n").await;
+        assert_eq!(
+            resp.unwrap_err().to_string(),
+            "ERR Invalid command specified"
+        );
+        Ok(())
+    }
+}
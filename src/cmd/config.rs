@@ -0,0 +1,291 @@
+use crate::backend::Backend;
+use crate::cmd::RESP_OK;
+use crate::cmd::{extract_args, extract_strings, validate_command, CommandError, CommandExecutor};
+use crate::config::ConfigError;
+use crate::network::tls;
+use crate::{BulkString, RespArray, RespFrame};
+use tracing::warn;
+
+#[derive(Debug)]
+pub struct ConfigGet {
+    patterns: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ConfigSet {
+    params: Vec<(String, String)>,
+}
+
+#[derive(Debug)]
+pub struct ConfigRewrite;
+
+#[derive(Debug)]
+pub struct ConfigResetStat;
+
+impl CommandExecutor for ConfigGet {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        let mut ret: Vec<RespFrame> = Vec::new();
+        for (name, value) in backend.config().get(&self.patterns) {
+            ret.push(BulkString::from(name).into());
+            ret.push(BulkString::from(value).into());
+        }
+        Ok(RespArray::new(ret).into())
+    }
+}
+
+impl CommandExecutor for ConfigSet {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        let mut config = backend.config_mut();
+        let old = config.clone();
+        config.set(&self.params).map_err(config_error)?;
+
+        // like redis, new certificates only take effect once they load, or nothing changes
+        let tls_param = self
+            .params
+            .iter()
+            .find(|(name, _)| name.to_ascii_lowercase().starts_with("tls-"))
+            .filter(|_| config.tls_port != 0);
+        if let Some((name, _)) = tls_param {
+            match tls::load(&config) {
+                Ok(tls) => backend.set_tls(tls),
+                Err(e) => {
+                    *config = old;
+                    // logging may read the config, so not before the lock is released
+                    drop(config);
+                    warn!("Failed to update TLS configuration: {:#}", e);
+                    return Err(config_error(ConfigError::Tls(name.clone())));
+                }
+            }
+        }
+        let changed = |param: &str| {
+            self.params
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case(param))
+        };
+        if changed("requirepass") {
+            backend.acl().set_requirepass(&config.requirepass);
+        }
+        if changed("loglevel") {
+            backend.set_loglevel(config.loglevel);
+        }
+        if changed("notify-keyspace-events") {
+            backend.set_notify_keyspace_events(config.notify_keyspace_events);
+        }
+        Ok(RESP_OK.clone())
+    }
+}
+
+impl CommandExecutor for ConfigRewrite {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        backend.config().rewrite().map_err(config_error)?;
+        Ok(RESP_OK.clone())
+    }
+}
+
+impl CommandExecutor for ConfigResetStat {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        backend.stats().reset();
+        Ok(RESP_OK.clone())
+    }
+}
+
+fn config_error(e: ConfigError) -> CommandError {
+    CommandError::InvalidCmd(e.to_string())
+}
+
+// config get notify-*
+// *3\r\n$6\r\nconfig\r\n$3\r\nget\r\n$8\r\nnotify-*\r\n
+impl TryFrom<RespArray> for ConfigGet {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["config", "get"])?;
+
+        let patterns = extract_strings(extract_args(value, 2)?)?;
+        Ok(ConfigGet { patterns })
+    }
+}
+
+// config set notify-keyspace-events KEA
+// *4\r\n$6\r\nconfig\r\n$3\r\nset\r\n$22\r\nnotify-keyspace-events\r\n$3\r\nKEA\r\n
+impl TryFrom<RespArray> for ConfigSet {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["config", "set"])?;
+
+        let args = extract_strings(extract_args(value, 2)?)?;
+        if args.len() % 2 != 0 {
+            return Err(CommandError::WrongArity("config|set".to_string()));
+        }
+        let params = args
+            .chunks(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect();
+        Ok(ConfigSet { params })
+    }
+}
+
+// config rewrite
+// *2\r\n$6\r\nconfig\r\n$7\r\nrewrite\r\n
+impl TryFrom<RespArray> for ConfigRewrite {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["config", "rewrite"])?;
+
+        Ok(ConfigRewrite)
+    }
+}
+
+// config resetstat
+// *2\r\n$6\r\nconfig\r\n$9\r\nresetstat\r\n
+impl TryFrom<RespArray> for ConfigResetStat {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["config", "resetstat"])?;
+
+        Ok(ConfigResetStat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{LogLevel, ServerConfig};
+    use crate::RespDecode;
+    use anyhow::Result;
+
+    #[test]
+    fn test_config_set_and_get() -> Result<()> {
+        let backend = Backend::new();
+
+        let mut cmd = bytes::BytesMut::from(
+            &b"*4\r\n$6\r\nconfig\r\n$3\r\nset\r\n$22\r\nnotify-keyspace-events\r\n$3\r\nKEA\r\n"[..],
+        );
+        let cmd = RespArray::decode(&mut cmd)?;
+        let set = ConfigSet::try_from(cmd)?;
+        assert_eq!(
+            set.params,
+            [("notify-keyspace-events".into(), "KEA".into())]
+        );
+        assert_eq!(set.execute(&backend)?, RESP_OK.clone());
+
+        let mut cmd =
+            bytes::BytesMut::from(&b"*3\r\n$6\r\nconfig\r\n$3\r\nget\r\n$8\r\nnotify-*\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        let get = ConfigGet::try_from(cmd)?;
+        assert_eq!(
+            get.execute(&backend)?,
+            RespArray::new(vec![
+                BulkString::from("notify-keyspace-events").into(),
+                BulkString::from("AKE").into(),
+            ])
+            .into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_memory_limits() -> Result<()> {
+        let backend = Backend::new();
+        let set = ConfigSet {
+            params: vec![
+                ("proto-max-bulk-len".into(), "2mb".into()),
+                ("CLIENT-QUERY-BUFFER-LIMIT".into(), "1048576".into()),
+            ],
+        };
+        assert_eq!(set.execute(&backend)?, RESP_OK.clone());
+        assert_eq!(backend.resp_limits().max_bulk_len, 2 * 1024 * 1024);
+        assert_eq!(backend.resp_limits().max_query_buffer, 1024 * 1024);
+
+        let get = ConfigGet {
+            patterns: vec!["proto-*".into()],
+        };
+        assert_eq!(
+            get.execute(&backend)?,
+            RespArray::new(vec![
+                BulkString::from("proto-max-bulk-len").into(),
+                BulkString::from("2097152").into(),
+            ])
+            .into()
+        );
+
+        for value in ["1kb", "lots", "-1", "1tb"] {
+            let set = ConfigSet {
+                params: vec![("proto-max-bulk-len".into(), value.into())],
+            };
+            assert!(set.execute(&backend).is_err(), "{}", value);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_loglevel() -> Result<()> {
+        let backend = Backend::new();
+        assert_eq!(backend.loglevel(), LogLevel::Notice);
+        let set = ConfigSet {
+            params: vec![("LOGLEVEL".into(), "warning".into())],
+        };
+        assert_eq!(set.execute(&backend)?, RESP_OK.clone());
+        assert_eq!(backend.loglevel(), LogLevel::Warning);
+
+        let set = ConfigSet {
+            params: vec![("loglevel".into(), "loud".into())],
+        };
+        assert!(set.execute(&backend).is_err());
+        assert_eq!(backend.loglevel(), LogLevel::Warning);
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_notify_keyspace_events() -> Result<()> {
+        let backend = Backend::new();
+        assert_eq!(backend.notify_keyspace_events(), 0);
+        let set = ConfigSet {
+            params: vec![("notify-keyspace-events".into(), "KEA".into())],
+        };
+        assert_eq!(set.execute(&backend)?, RESP_OK.clone());
+        assert_eq!(
+            backend.notify_keyspace_events(),
+            crate::notify::parse_notify_flags("KEA")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_set_invalid() {
+        let backend = Backend::new();
+        let set = ConfigSet {
+            params: vec![("notify-keyspace-events".into(), "KEq".into())],
+        };
+        assert!(set.execute(&backend).is_err());
+
+        let set = ConfigSet {
+            params: vec![("unknown".into(), "value".into())],
+        };
+        assert!(set.execute(&backend).is_err());
+    }
+
+    #[test]
+    fn test_config_rewrite() -> Result<()> {
+        let err = ConfigRewrite.execute(&Backend::new()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR The server is running without a config file"
+        );
+
+        let path = std::env::temp_dir().join(format!("r-redis-{}.conf", std::process::id()));
+        std::fs::write(&path, "# keep me\ntimeout 0\n")?;
+        let backend = Backend::with_config(ServerConfig::load(Some(path.clone()), "")?.0);
+        let set = ConfigSet {
+            params: vec![("timeout".into(), "5".into())],
+        };
+        set.execute(&backend)?;
+        assert_eq!(ConfigRewrite.execute(&backend)?, RESP_OK.clone());
+        assert_eq!(std::fs::read_to_string(&path)?, "# keep me\ntimeout 5\n");
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
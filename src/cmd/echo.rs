@@ -1,46 +1,36 @@
 use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor};
-use crate::{Backend, RespArray, RespFrame, SimpleString};
+use crate::{Backend, RespArray, RespFrame};
 
 #[derive(Debug)]
 pub struct Echo {
-    arg: String,
+    message: RespFrame,
 }
 
 impl CommandExecutor for Echo {
     fn execute(self, _: &Backend) -> Result<RespFrame, CommandError> {
-        Ok(SimpleString::from(self.arg).into())
+        Ok(self.message)
     }
 }
 
+// echo hello
+// *2\r\n$4\r\necho\r\n$5\r\nhello\r\n
 impl TryFrom<RespArray> for Echo {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["echo"], 1)?;
+        validate_command(&value, &["echo"])?;
 
-        let args = extract_args(value, 1)?.into_iter();
-        let mut input = String::new();
-        args.for_each(|arg| {
-            if let RespFrame::BulkString(arg) = arg {
-                input.push_str(&String::from_utf8_lossy(arg.as_slice()));
-                input.push(' ');
-            }
-        });
-        let input = input.trim().to_string();
-        Ok(Echo::new(input))
-    }
-}
-
-impl Echo {
-    pub fn new(arg: String) -> Self {
-        Echo { arg }
+        match extract_args(value, 1)?.into_iter().next() {
+            Some(message) => Ok(Echo { message }),
+            None => Err(CommandError::Syntax),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::RespDecode;
+    use crate::{BulkString, RespDecode};
     use anyhow::Result;
 
     #[test]
@@ -50,15 +40,22 @@ mod tests {
         let echo = Echo::try_from(cmd)?;
         let resp = echo.execute(&Backend::new())?;
 
-        assert_eq!(resp, SimpleString::from("hello").into());
+        assert_eq!(resp, BulkString::from("hello").into());
 
-        let mut cmd =
-            bytes::BytesMut::from(&b"*3\r\n$4\r\necho\r\n$5\r\nhello\r\n$4\r\nboys\r\n"[..]);
+        // the message is returned as it is, spaces and line breaks included
+        let mut cmd = bytes::BytesMut::from(&b"*2\r\n$4\r\necho\r\n$7\r\nhi\r\nyou\r\n"[..]);
         let cmd = RespArray::decode(&mut cmd)?;
         let echo = Echo::try_from(cmd)?;
         let resp = echo.execute(&Backend::new())?;
+        assert_eq!(resp, BulkString::from("hi\r\nyou").into());
 
-        assert_eq!(resp, SimpleString::from("hello boys").into());
+        let mut cmd =
+            bytes::BytesMut::from(&b"*3\r\n$4\r\necho\r\n$5\r\nhello\r\n$4\r\nboys\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        assert_eq!(
+            Echo::try_from(cmd).unwrap_err(),
+            CommandError::WrongArity("echo".to_string())
+        );
         Ok(())
     }
 }
@@ -0,0 +1,271 @@
+use crate::cmd::{
+    extract_args, resolve, validate_command, Command, CommandError, ContextExecutor, ExecFuture,
+};
+use crate::network::Session;
+use crate::{BulkString, RespArray, RespFrame, SimpleError};
+use bytes::Bytes;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub struct FCall {
+    function: String,
+    keys: Vec<Bytes>,
+    args: Vec<Bytes>,
+    read_only: bool,
+}
+
+impl ContextExecutor for FCall {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        // the function runs to its end on this thread, whose other tasks tokio moves elsewhere
+        let ret = tokio::task::block_in_place(|| self.call(session));
+        Box::pin(std::future::ready(ret.map(|frame| vec![frame])))
+    }
+}
+
+impl FCall {
+    /// FCALL_RO is parsed as a read-only FCALL.
+    pub fn name(&self) -> &'static str {
+        if self.read_only {
+            "fcall_ro"
+        } else {
+            "fcall"
+        }
+    }
+
+    fn call(self, session: &mut Session) -> Result<RespFrame, CommandError> {
+        let backend = session.backend().clone();
+        let Some((library, function)) = backend.functions().function(&self.function) else {
+            return Err(CommandError::InvalidCmd("Function not found".to_string()));
+        };
+        let no_writes = function.flags.iter().any(|flag| flag == "no-writes");
+        if self.read_only && !no_writes {
+            return Err(CommandError::InvalidCmd(
+                "Can not execute a script with write flag using *_ro command.".to_string(),
+            ));
+        }
+
+        let busy_threshold = Duration::from_millis(backend.config().busy_reply_threshold);
+        let mut call = |args: Vec<Bytes>| script_call(session, args, no_writes);
+        match backend.functions().call(
+            &library,
+            &self.function,
+            &self.keys,
+            &self.args,
+            busy_threshold,
+            &mut call,
+        ) {
+            Ok(RespFrame::Error(e)) | Err(e) => Err(CommandError::InvalidArgs(e.to_string())),
+            Ok(frame) => Ok(frame),
+        }
+    }
+}
+
+// runs a command sent by redis.call or redis.pcall, with the permissions of the client
+fn script_call(
+    session: &mut Session,
+    args: Vec<Bytes>,
+    read_only: bool,
+) -> Result<RespFrame, SimpleError> {
+    let args = RespArray::new(
+        args.into_iter()
+            .map(|arg| BulkString::from(arg).into())
+            .collect(),
+    );
+    let spec = match resolve(&args) {
+        Ok(spec) => spec,
+        Err(CommandError::UnknownCommand(..)) => {
+            return Err(SimpleError::new(
+                "ERR Unknown Redis command called from script",
+            ))
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if spec.flags.contains(&"noscript") {
+        return Err(SimpleError::new(
+            "ERR This Redis command is not allowed from script",
+        ));
+    }
+    if read_only && spec.flags.contains(&"write") {
+        return Err(SimpleError::new(
+            "ERR Write commands are not allowed from read-only scripts.",
+        ));
+    }
+    session.check_permissions(spec, &args)?;
+    let cmd = Command::try_from(RespFrame::from(args))?;
+    if spec.flags.contains(&"write") {
+        session.backend().functions().record_write();
+    }
+
+    Ok(cmd.execute_sync(session)?)
+}
+
+// fcall myfunc 1 key arg
+// *5\r\n$5\r\nfcall\r\n$6\r\nmyfunc\r\n$1\r\n1\r\n$3\r\nkey\r\n$3\r\narg\r\n
+impl TryFrom<RespArray> for FCall {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let read_only = matches!(&value.first(), Some(RespFrame::BulkString(name)) if name.eq_ignore_ascii_case(b"fcall_ro"));
+        validate_command(&value, &[if read_only { "fcall_ro" } else { "fcall" }])?;
+
+        let mut args = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| match arg {
+                RespFrame::BulkString(arg) => Ok(Bytes::copy_from_slice(&arg)),
+                _ => Err(CommandError::Syntax),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let function = String::from_utf8(args.remove(0).to_vec())?;
+        let numkeys = std::str::from_utf8(&args.remove(0))
+            .ok()
+            .and_then(|n| n.parse::<i64>().ok())
+            .ok_or_else(|| {
+                CommandError::InvalidCmd("value is not an integer or out of range".to_string())
+            })?;
+        if numkeys < 0 {
+            return Err(CommandError::InvalidCmd(
+                "Number of keys can't be negative".to_string(),
+            ));
+        }
+        if numkeys as usize > args.len() {
+            return Err(CommandError::InvalidCmd(
+                "Number of keys can't be greater than number of args".to_string(),
+            ));
+        }
+        let keys = args.drain(..numkeys as usize).collect();
+        Ok(FCall {
+            function,
+            keys,
+            args,
+            read_only,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::Backend;
+    use crate::{RespDecode, SimpleString};
+    use anyhow::Result;
+    use tokio::sync::mpsc;
+
+    const LIBRARY: &str = "#!lua name=mylib
+redis.register_function('setget', function(keys, args)
+    redis.call('set', keys[1], args[1])
+    return redis.call('get', keys[1])
+end)
+redis.register_function{function_name='trywrite', flags={'no-writes'}, callback=function(keys)
+    return redis.pcall('set', keys[1], 'x')
+end}
+redis.register_function('nested', function() return redis.call('fcall', 'setget', 0) end)
+redis.register_function('unknown', function() return redis.call('nosuchcommand') end)
+redis.register_function('ping', function() return redis.call('ping') end)";
+
+    fn fcall(session: &mut Session, cmd: &[u8]) -> Result<RespFrame, CommandError> {
+        let cmd = RespArray::decode(&mut bytes::BytesMut::from(cmd)).unwrap();
+        FCall::try_from(cmd)?.call(session)
+    }
+
+    #[test]
+    fn test_fcall_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.functions().load(LIBRARY, false)?;
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(backend.clone(), tx);
+
+        // fcall setget 1 key value
+        let ret = fcall(
+            &mut session,
+            b"*5\r\n$5\r\nfcall\r\n$6\r\nsetget\r\n$1\r\n1\r\n$3\r\nkey\r\n$5\r\nvalue\r\n",
+        )?;
+        assert_eq!(ret, BulkString::from("value").into());
+        assert_eq!(backend.get("key"), Some(BulkString::from("value").into()));
+
+        // fcall_ro trywrite 1 key
+        assert_eq!(
+            fcall(
+                &mut session,
+                b"*4\r\n$8\r\nfcall_ro\r\n$8\r\ntrywrite\r\n$1\r\n1\r\n$3\r\nkey\r\n",
+            ),
+            Err(CommandError::InvalidArgs(
+                "ERR Write commands are not allowed from read-only scripts.".into()
+            ))
+        );
+
+        // fcall_ro setget 1 key value
+        assert_eq!(
+            fcall(
+                &mut session,
+                b"*5\r\n$8\r\nfcall_ro\r\n$6\r\nsetget\r\n$1\r\n1\r\n$3\r\nkey\r\n$5\r\nvalue\r\n",
+            ),
+            Err(CommandError::InvalidCmd(
+                "Can not execute a script with write flag using *_ro command.".into()
+            ))
+        );
+
+        // fcall nested 0
+        assert_eq!(
+            fcall(
+                &mut session,
+                b"*3\r\n$5\r\nfcall\r\n$6\r\nnested\r\n$1\r\n0\r\n"
+            ),
+            Err(CommandError::InvalidArgs(
+                "ERR This Redis command is not allowed from script".into()
+            ))
+        );
+
+        // fcall ping 0
+        assert_eq!(
+            fcall(
+                &mut session,
+                b"*3\r\n$5\r\nfcall\r\n$4\r\nping\r\n$1\r\n0\r\n"
+            )?,
+            SimpleString::new("PONG").into()
+        );
+
+        // fcall unknown 0
+        assert_eq!(
+            fcall(
+                &mut session,
+                b"*3\r\n$5\r\nfcall\r\n$7\r\nunknown\r\n$1\r\n0\r\n"
+            ),
+            Err(CommandError::InvalidArgs(
+                "ERR Unknown Redis command called from script".into()
+            ))
+        );
+
+        // fcall missing 0
+        assert_eq!(
+            fcall(
+                &mut session,
+                b"*3\r\n$5\r\nfcall\r\n$7\r\nmissing\r\n$1\r\n0\r\n"
+            ),
+            Err(CommandError::InvalidCmd("Function not found".into()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fcall_numkeys() {
+        let parse = |cmd: &[u8]| {
+            FCall::try_from(RespArray::decode(&mut bytes::BytesMut::from(cmd)).unwrap())
+                .unwrap_err()
+        };
+        // fcall f x
+        assert_eq!(
+            parse(b"*3\r\n$5\r\nfcall\r\n$1\r\nf\r\n$1\r\nx\r\n"),
+            CommandError::InvalidCmd("value is not an integer or out of range".into())
+        );
+        // fcall f -1
+        assert_eq!(
+            parse(b"*3\r\n$5\r\nfcall\r\n$1\r\nf\r\n$2\r\n-1\r\n"),
+            CommandError::InvalidCmd("Number of keys can't be negative".into())
+        );
+        // fcall f 2 key
+        assert_eq!(
+            parse(b"*4\r\n$5\r\nfcall\r\n$1\r\nf\r\n$1\r\n2\r\n$3\r\nkey\r\n"),
+            CommandError::InvalidCmd("Number of keys can't be greater than number of args".into())
+        );
+    }
+}
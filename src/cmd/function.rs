@@ -0,0 +1,439 @@
+use crate::backend::{Backend, FunctionError, RestorePolicy};
+use crate::cmd::{
+    extract_args, extract_strings, validate_command, CommandError, CommandExecutor, RESP_OK,
+};
+use crate::glob::glob_match;
+use crate::{BulkString, RespArray, RespFrame, RespMap, RespNull, RespSet};
+
+#[derive(Debug)]
+pub struct FunctionLoad {
+    code: String,
+    replace: bool,
+}
+
+#[derive(Debug)]
+pub struct FunctionList {
+    pattern: Option<String>,
+    with_code: bool,
+}
+
+#[derive(Debug)]
+pub struct FunctionDelete {
+    library: String,
+}
+
+#[derive(Debug)]
+pub struct FunctionFlush;
+
+#[derive(Debug)]
+pub struct FunctionDump;
+
+#[derive(Debug)]
+pub struct FunctionKill;
+
+#[derive(Debug)]
+pub struct FunctionRestore {
+    payload: BulkString,
+    policy: RestorePolicy,
+}
+
+impl CommandExecutor for FunctionLoad {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        let name = backend
+            .functions()
+            .load(&self.code, self.replace)
+            .map_err(function_error)?;
+        Ok(BulkString::from(name).into())
+    }
+}
+
+impl CommandExecutor for FunctionList {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        let mut ret = vec![];
+        for library in backend.functions().list() {
+            if let Some(pattern) = &self.pattern {
+                if !glob_match(pattern.as_bytes(), library.name.as_bytes()) {
+                    continue;
+                }
+            }
+            let functions = library
+                .functions
+                .iter()
+                .map(|function| {
+                    let mut map = RespMap::new();
+                    map.insert(
+                        "name".into(),
+                        BulkString::from(function.name.as_str()).into(),
+                    );
+                    let description = match &function.description {
+                        Some(description) => BulkString::from(description.as_str()).into(),
+                        None => RespNull.into(),
+                    };
+                    map.insert("description".into(), description);
+                    let flags = function
+                        .flags
+                        .iter()
+                        .map(|flag| BulkString::from(flag.as_str()).into())
+                        .collect::<std::collections::BTreeSet<RespFrame>>();
+                    map.insert("flags".into(), RespSet::from(flags).into());
+                    map.into()
+                })
+                .collect();
+
+            let mut map = RespMap::new();
+            map.insert(
+                "library_name".into(),
+                BulkString::from(library.name.as_str()).into(),
+            );
+            map.insert("engine".into(), BulkString::from("LUA").into());
+            map.insert("functions".into(), RespArray::new(functions).into());
+            if self.with_code {
+                map.insert(
+                    "library_code".into(),
+                    BulkString::from(library.code.as_str()).into(),
+                );
+            }
+            ret.push(map.into());
+        }
+        Ok(RespArray::new(ret).into())
+    }
+}
+
+impl CommandExecutor for FunctionDelete {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        backend
+            .functions()
+            .delete(&self.library)
+            .map_err(function_error)?;
+        Ok(RESP_OK.clone())
+    }
+}
+
+impl CommandExecutor for FunctionFlush {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        backend.functions().flush();
+        Ok(RESP_OK.clone())
+    }
+}
+
+impl CommandExecutor for FunctionDump {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        Ok(BulkString::new(backend.functions().dump()).into())
+    }
+}
+
+impl CommandExecutor for FunctionRestore {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        backend
+            .functions()
+            .restore(&self.payload, self.policy)
+            .map_err(function_error)?;
+        Ok(RESP_OK.clone())
+    }
+}
+
+impl CommandExecutor for FunctionKill {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        backend.functions().kill().map_err(function_error)?;
+        Ok(RESP_OK.clone())
+    }
+}
+
+fn function_error(e: FunctionError) -> CommandError {
+    match e {
+        FunctionError::NotBusy => CommandError::InvalidArgs(format!("NOTBUSY {}", e)),
+        FunctionError::Unkillable => CommandError::InvalidArgs(format!("UNKILLABLE {}", e)),
+        e => CommandError::InvalidCmd(e.to_string()),
+    }
+}
+
+// function load replace "#!lua name=mylib\n..."
+// *4\r\n$8\r\nfunction\r\n$4\r\nload\r\n$7\r\nreplace\r\n$<len>\r\n#!lua name=mylib\n...\r\n
+impl TryFrom<RespArray> for FunctionLoad {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["function", "load"])?;
+
+        let mut args = extract_strings(extract_args(value, 2)?)?;
+        let code = args.pop().unwrap_or_default();
+        match args.as_slice() {
+            [] => Ok(FunctionLoad {
+                code,
+                replace: false,
+            }),
+            [arg] if arg.eq_ignore_ascii_case("replace") => Ok(FunctionLoad {
+                code,
+                replace: true,
+            }),
+            [arg, ..] => Err(CommandError::InvalidCmd(format!(
+                "Unknown option given: {}",
+                arg
+            ))),
+        }
+    }
+}
+
+// function list libraryname my* withcode
+// *5\r\n$8\r\nfunction\r\n$4\r\nlist\r\n$11\r\nlibraryname\r\n$3\r\nmy*\r\n$8\r\nwithcode\r\n
+impl TryFrom<RespArray> for FunctionList {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["function", "list"])?;
+
+        let mut list = FunctionList {
+            pattern: None,
+            with_code: false,
+        };
+        let mut args = extract_strings(extract_args(value, 2)?)?.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.to_ascii_lowercase().as_str() {
+                "withcode" if !list.with_code => list.with_code = true,
+                "libraryname" if list.pattern.is_some() => {
+                    return Err(CommandError::InvalidCmd(
+                        "library name can be given once".to_string(),
+                    ))
+                }
+                "libraryname" => match args.next() {
+                    Some(pattern) => list.pattern = Some(pattern),
+                    None => {
+                        return Err(CommandError::InvalidCmd(
+                            "library name argument was not given".to_string(),
+                        ))
+                    }
+                },
+                _ => {
+                    return Err(CommandError::InvalidCmd(format!(
+                        "Unknown argument {}",
+                        arg
+                    )))
+                }
+            }
+        }
+        Ok(list)
+    }
+}
+
+// function delete mylib
+// *3\r\n$8\r\nfunction\r\n$6\r\ndelete\r\n$5\r\nmylib\r\n
+impl TryFrom<RespArray> for FunctionDelete {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["function", "delete"])?;
+
+        let mut args = extract_strings(extract_args(value, 2)?)?;
+        Ok(FunctionDelete {
+            library: args.remove(0),
+        })
+    }
+}
+
+// function flush async
+// *3\r\n$8\r\nfunction\r\n$5\r\nflush\r\n$5\r\nasync\r\n
+impl TryFrom<RespArray> for FunctionFlush {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["function", "flush"])?;
+
+        // the libraries are always freed right away, ASYNC makes no difference
+        match extract_strings(extract_args(value, 2)?)?.as_slice() {
+            [] => Ok(FunctionFlush),
+            [mode] if mode.eq_ignore_ascii_case("sync") || mode.eq_ignore_ascii_case("async") => {
+                Ok(FunctionFlush)
+            }
+            _ => Err(CommandError::InvalidCmd(
+                "FUNCTION FLUSH only supports SYNC|ASYNC option".to_string(),
+            )),
+        }
+    }
+}
+
+// function dump
+// *2\r\n$8\r\nfunction\r\n$4\r\ndump\r\n
+impl TryFrom<RespArray> for FunctionDump {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["function", "dump"])?;
+
+        Ok(FunctionDump)
+    }
+}
+
+// function kill
+// *2\r\n$8\r\nfunction\r\n$4\r\nkill\r\n
+impl TryFrom<RespArray> for FunctionKill {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["function", "kill"])?;
+
+        Ok(FunctionKill)
+    }
+}
+
+// function restore <payload> replace
+// *4\r\n$8\r\nfunction\r\n$7\r\nrestore\r\n$<len>\r\n<payload>\r\n$7\r\nreplace\r\n
+impl TryFrom<RespArray> for FunctionRestore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["function", "restore"])?;
+
+        let mut args = extract_args(value, 2)?.into_iter();
+        let Some(RespFrame::BulkString(payload)) = args.next() else {
+            return Err(CommandError::Syntax);
+        };
+        let policy =
+            match extract_strings(args.collect())?.as_slice() {
+                [] => RestorePolicy::Append,
+                [policy] if policy.eq_ignore_ascii_case("append") => RestorePolicy::Append,
+                [policy] if policy.eq_ignore_ascii_case("replace") => RestorePolicy::Replace,
+                [policy] if policy.eq_ignore_ascii_case("flush") => RestorePolicy::Flush,
+                [_] => return Err(CommandError::InvalidCmd(
+                    "Wrong restore policy given, value should be either FLUSH, APPEND or REPLACE."
+                        .to_string(),
+                )),
+                _ => return Err(CommandError::Syntax),
+            };
+        Ok(FunctionRestore { payload, policy })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespDecode;
+    use anyhow::Result;
+
+    const LOAD: &[u8] = b"*3\r\n$8\r\nfunction\r\n$4\r\nload\r\n$70\r\n#!lua name=mylib\nredis.register_function('f', function() return 1 end)\r\n";
+
+    fn decode(cmd: &[u8]) -> Result<RespArray> {
+        Ok(RespArray::decode(&mut bytes::BytesMut::from(cmd))?)
+    }
+
+    #[test]
+    fn test_function_load_and_list() -> Result<()> {
+        let backend = Backend::new();
+        let load = FunctionLoad::try_from(decode(LOAD)?)?;
+        assert!(!load.replace);
+        assert_eq!(load.execute(&backend)?, BulkString::from("mylib").into());
+        assert_eq!(
+            FunctionLoad::try_from(decode(LOAD)?)?.execute(&backend),
+            Err(CommandError::InvalidCmd(
+                "Library 'mylib' already exists".into()
+            ))
+        );
+
+        // function load REPLACE ...
+        let load = [b"*4\r\n", &LOAD[4..28], b"$7\r\nREPLACE\r\n", &LOAD[28..]].concat();
+        let load = FunctionLoad::try_from(decode(&load)?)?;
+        assert!(load.replace);
+        load.execute(&backend)?;
+
+        // function list withcode
+        let list = FunctionList::try_from(decode(
+            b"*3\r\n$8\r\nfunction\r\n$4\r\nlist\r\n$8\r\nwithcode\r\n",
+        )?)?;
+        let RespFrame::Array(libraries) = list.execute(&backend)? else {
+            panic!("Expected Array");
+        };
+        let RespFrame::Map(library) = &libraries[0] else {
+            panic!("Expected Map");
+        };
+        assert_eq!(
+            library.get(&"library_name".into()),
+            Some(&BulkString::from("mylib").into())
+        );
+        assert_eq!(
+            library.get(&"engine".into()),
+            Some(&BulkString::from("LUA").into())
+        );
+        assert!(library.get(&"library_code".into()).is_some());
+        let Some(RespFrame::Array(functions)) = library.get(&"functions".into()) else {
+            panic!("Expected Array");
+        };
+        let RespFrame::Map(function) = &functions[0] else {
+            panic!("Expected Map");
+        };
+        assert_eq!(
+            function.get(&"name".into()),
+            Some(&BulkString::from("f").into())
+        );
+        assert_eq!(function.get(&"description".into()), Some(&RespNull.into()));
+        assert_eq!(function.get(&"flags".into()), Some(&RespSet::new().into()));
+
+        // function list libraryname other*
+        let list = FunctionList::try_from(decode(
+            b"*4\r\n$8\r\nfunction\r\n$4\r\nlist\r\n$11\r\nlibraryname\r\n$6\r\nother*\r\n",
+        )?)?;
+        assert_eq!(list.execute(&backend)?, RespArray::new(vec![]).into());
+
+        // function list libraryname
+        assert_eq!(
+            FunctionList::try_from(decode(
+                b"*3\r\n$8\r\nfunction\r\n$4\r\nlist\r\n$11\r\nlibraryname\r\n"
+            )?)
+            .unwrap_err(),
+            CommandError::InvalidCmd("library name argument was not given".into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_function_delete_dump_restore() -> Result<()> {
+        let backend = Backend::new();
+        FunctionLoad::try_from(decode(LOAD)?)?.execute(&backend)?;
+        let RespFrame::BulkString(payload) = FunctionDump.execute(&backend)? else {
+            panic!("Expected BulkString");
+        };
+
+        // function delete mylib
+        let delete = b"*3\r\n$8\r\nfunction\r\n$6\r\ndelete\r\n$5\r\nmylib\r\n";
+        FunctionDelete::try_from(decode(delete)?)?.execute(&backend)?;
+        assert_eq!(
+            FunctionDelete::try_from(decode(delete)?)?.execute(&backend),
+            Err(CommandError::InvalidCmd("Library not found".into()))
+        );
+
+        let restore = FunctionRestore {
+            payload: payload.clone(),
+            policy: RestorePolicy::Append,
+        };
+        assert_eq!(restore.execute(&backend)?, RESP_OK.clone());
+        assert!(backend.functions().function("f").is_some());
+        let restore = FunctionRestore {
+            payload: BulkString::from("garbage"),
+            policy: RestorePolicy::Flush,
+        };
+        assert_eq!(
+            restore.execute(&backend),
+            Err(CommandError::InvalidCmd(
+                "payload version or checksum are wrong".into()
+            ))
+        );
+
+        // function flush sync
+        let flush = b"*3\r\n$8\r\nfunction\r\n$5\r\nflush\r\n$4\r\nsync\r\n";
+        FunctionFlush::try_from(decode(flush)?)?.execute(&backend)?;
+        assert!(backend.functions().list().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_function_kill_not_busy() -> Result<()> {
+        let backend = Backend::new();
+        // function kill
+        let kill = FunctionKill::try_from(decode(b"*2\r\n$8\r\nfunction\r\n$4\r\nkill\r\n")?)?;
+        assert_eq!(
+            kill.execute(&backend),
+            Err(CommandError::InvalidArgs(
+                "NOTBUSY No scripts in execution right now.".into()
+            ))
+        );
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
 use crate::backend::Backend;
-use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor, RESP_EMPTY};
+use crate::cmd::{
+    ensure_type, extract_args, validate_command, CommandError, CommandExecutor, RESP_EMPTY,
+};
 use crate::{RespArray, RespFrame};
 
 #[derive(Debug)]
@@ -9,6 +11,7 @@ pub struct Get {
 
 impl CommandExecutor for Get {
     fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        ensure_type(backend, &self.key, "string")?;
         match backend.get(&self.key) {
             Some(value) => Ok(value),
             None => Ok(RESP_EMPTY.clone()),
@@ -22,14 +25,14 @@ impl TryFrom<RespArray> for Get {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["get"], 1)?;
+        validate_command(&value, &["get"])?;
 
         let mut args = extract_args(value, 1)?.into_iter();
         match args.next() {
             Some(RespFrame::BulkString(key)) => Ok(Get {
                 key: String::from_utf8(key.to_vec())?,
             }),
-            _ => Err(CommandError::InvalidArgs("Invalid arguments".to_string())),
+            _ => Err(CommandError::Syntax),
         }
     }
 }
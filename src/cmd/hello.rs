@@ -0,0 +1,199 @@
+use crate::cmd::client::validate_client_name;
+use crate::cmd::{
+    extract_args, extract_strings, validate_command, CommandError, ContextExecutor, ExecFuture,
+    REDIS_VERSION,
+};
+use crate::network::Session;
+use crate::{BulkString, RespArray, RespMap, RespProtocol};
+
+#[derive(Debug)]
+pub struct Hello {
+    protocol: Option<i64>,
+    auth: Option<(String, String)>,
+    name: Option<String>,
+}
+
+impl ContextExecutor for Hello {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        Box::pin(async move {
+            let protocol = match self.protocol {
+                Some(version) => RespProtocol::try_from(version).map_err(|_| {
+                    CommandError::InvalidArgs("NOPROTO unsupported protocol version".to_string())
+                })?,
+                None => session.protocol(),
+            };
+
+            match self.auth {
+                Some((username, password)) if !session.authenticate(&username, &password) => {
+                    return Err(CommandError::WrongPass);
+                }
+                None if session.auth_required() => {
+                    return Err(CommandError::InvalidArgs(
+                        "NOAUTH HELLO must be called with the client already authenticated, otherwise the HELLO <proto> AUTH <user> <pass> option can be used to authenticate the client and select the RESP protocol version at the same time"
+                            .to_string(),
+                    ))
+                }
+                _ => {}
+            }
+
+            if let Some(name) = self.name {
+                validate_client_name(&name)?;
+                session.set_name(Some(name).filter(|name| !name.is_empty()));
+            }
+            session.set_protocol(protocol);
+
+            let mut map = RespMap::new();
+            map.insert("server".into(), BulkString::from("redis").into());
+            map.insert("version".into(), BulkString::from(REDIS_VERSION).into());
+            map.insert("proto".into(), protocol.version().into());
+            map.insert("id".into(), (session.id() as i64).into());
+            map.insert("mode".into(), BulkString::from("standalone").into());
+            map.insert("role".into(), BulkString::from("master").into());
+            map.insert("modules".into(), RespArray::new(vec![]).into());
+            Ok(vec![map.into()])
+        })
+    }
+}
+
+// hello 3 auth default password setname myclient
+// *7\r\n$5\r\nhello\r\n$1\r\n3\r\n$4\r\nauth\r\n$7\r\ndefault\r\n$8\r\npassword\r\n$7\r\nsetname\r\n$8\r\nmyclient\r\n
+impl TryFrom<RespArray> for Hello {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hello"])?;
+
+        let args = extract_strings(extract_args(value, 1)?)?;
+        let mut hello = Hello {
+            protocol: None,
+            auth: None,
+            name: None,
+        };
+        let Some(version) = args.first() else {
+            return Ok(hello);
+        };
+        hello.protocol = Some(version.parse::<i64>().map_err(|_| {
+            CommandError::InvalidArgs(
+                "ERR Protocol version is not an integer or out of range".to_string(),
+            )
+        })?);
+
+        let mut i = 1;
+        while i < args.len() {
+            let option = args[i].to_ascii_lowercase();
+            match option.as_str() {
+                "auth" if i + 2 < args.len() => {
+                    hello.auth = Some((args[i + 1].clone(), args[i + 2].clone()));
+                    i += 3;
+                }
+                "setname" if i + 1 < args.len() => {
+                    hello.name = Some(args[i + 1].clone());
+                    i += 2;
+                }
+                _ => {
+                    return Err(CommandError::InvalidArgs(format!(
+                        "ERR Syntax error in HELLO option '{}'",
+                        args[i]
+                    )))
+                }
+            }
+        }
+        Ok(hello)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespFrame;
+    use crate::{Backend, RespDecode};
+    use anyhow::Result;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_hello_command() -> Result<()> {
+        let mut cmd = bytes::BytesMut::from(
+            &b"*7\r\n$5\r\nhello\r\n$1\r\n3\r\n$4\r\nauth\r\n$7\r\ndefault\r\n$8\r\npassword\r\n$7\r\nsetname\r\n$8\r\nmyclient\r\n"[..],
+        );
+        let cmd = RespArray::decode(&mut cmd)?;
+        let hello = Hello::try_from(cmd)?;
+        assert_eq!(hello.protocol, Some(3));
+        assert_eq!(hello.auth, Some(("default".into(), "password".into())));
+        assert_eq!(hello.name, Some("myclient".into()));
+
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(Backend::new(), tx);
+        let ret = hello.execute(&mut session).await?;
+        assert_eq!(session.protocol(), RespProtocol::Resp3);
+        assert_eq!(session.name(), Some("myclient"));
+
+        let RespFrame::Map(map) = &ret[0] else {
+            panic!("Expected Map");
+        };
+        assert_eq!(map.get(&"proto".into()), Some(&3.into()));
+        assert_eq!(map.get(&"id".into()), Some(&(session.id() as i64).into()));
+        assert_eq!(
+            map.get(&"modules".into()),
+            Some(&RespArray::new(vec![]).into())
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hello_command_setname_before_auth() -> Result<()> {
+        let mut cmd = bytes::BytesMut::from(
+            &b"*6\r\n$5\r\nhello\r\n$1\r\n2\r\n$7\r\nSETNAME\r\n$1\r\nx\r\n$4\r\nAUTH\r\n$7\r\ndefault\r\n"[..],
+        );
+        let cmd = RespArray::decode(&mut cmd)?;
+        // AUTH is missing its password
+        assert!(Hello::try_from(cmd).is_err());
+
+        let mut cmd = bytes::BytesMut::from(
+            &b"*4\r\n$5\r\nhello\r\n$1\r\n2\r\n$7\r\nSETNAME\r\n$1\r\nx\r\n"[..],
+        );
+        let cmd = RespArray::decode(&mut cmd)?;
+        let hello = Hello::try_from(cmd)?;
+        assert_eq!(hello.name, Some("x".into()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hello_command_errors() -> Result<()> {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(Backend::new(), tx);
+
+        let hello = Hello {
+            protocol: Some(4),
+            auth: None,
+            name: None,
+        };
+        assert!(hello.execute(&mut session).await.is_err());
+
+        let hello = Hello {
+            protocol: Some(3),
+            auth: Some(("alice".into(), "secret".into())),
+            name: None,
+        };
+        assert!(hello.execute(&mut session).await.is_err());
+
+        let hello = Hello {
+            protocol: Some(3),
+            auth: None,
+            name: Some("my client".into()),
+        };
+        assert!(hello.execute(&mut session).await.is_err());
+        assert_eq!(session.protocol(), RespProtocol::Resp2);
+
+        let hello = Hello {
+            protocol: None,
+            auth: None,
+            name: None,
+        };
+        let ret = hello.execute(&mut session).await?;
+        let RespFrame::Map(map) = &ret[0] else {
+            panic!("Expected Map");
+        };
+        assert_eq!(map.get(&"proto".into()), Some(&2.into()));
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
 use crate::backend::Backend;
-use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor, RESP_EMPTY};
+use crate::cmd::{
+    ensure_type, extract_args, validate_command, CommandError, CommandExecutor, RESP_EMPTY,
+};
 use crate::{RespArray, RespFrame};
 
 #[derive(Debug)]
@@ -10,6 +12,7 @@ pub struct HGet {
 
 impl CommandExecutor for HGet {
     fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        ensure_type(backend, &self.key, "hash")?;
         match backend.hget(&self.key, &self.field) {
             Some(value) => Ok(value),
             None => Ok(RESP_EMPTY.clone()),
@@ -23,7 +26,7 @@ impl TryFrom<RespArray> for HGet {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hget"], 2)?;
+        validate_command(&value, &["hget"])?;
 
         let mut args = extract_args(value, 1)?.into_iter();
         match (args.next(), args.next()) {
@@ -31,7 +34,7 @@ impl TryFrom<RespArray> for HGet {
                 key: String::from_utf8(key.to_vec())?,
                 field: String::from_utf8(field.to_vec())?,
             }),
-            _ => Err(CommandError::InvalidArgs("Invalid arguments".to_string())),
+            _ => Err(CommandError::Syntax),
         }
     }
 }
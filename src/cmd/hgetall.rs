@@ -1,5 +1,7 @@
 use crate::backend::Backend;
-use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor, RESP_EMPTY};
+use crate::cmd::{
+    ensure_type, extract_args, validate_command, CommandError, CommandExecutor, RESP_EMPTY,
+};
 use crate::{BulkString, RespArray, RespFrame};
 
 #[derive(Debug)]
@@ -9,6 +11,7 @@ pub struct HGetAll {
 
 impl CommandExecutor for HGetAll {
     fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        ensure_type(backend, &self.key, "hash")?;
         match backend.hget_all(&self.key) {
             Some(map) => {
                 let mut ret: Vec<RespFrame> = Vec::with_capacity(map.len() * 2);
@@ -30,14 +33,14 @@ impl TryFrom<RespArray> for HGetAll {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hgetall"], 1)?;
+        validate_command(&value, &["hgetall"])?;
 
         let mut args = extract_args(value, 1)?.into_iter();
         match args.next() {
             Some(RespFrame::BulkString(key)) => Ok(HGetAll {
                 key: String::from_utf8(key.to_vec())?,
             }),
-            _ => Err(CommandError::InvalidArgs("Invalid arguments".to_string())),
+            _ => Err(CommandError::Syntax),
         }
     }
 }
@@ -1,5 +1,7 @@
 use crate::backend::Backend;
-use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor, RESP_EMPTY};
+use crate::cmd::{
+    ensure_type, extract_args, validate_command, CommandError, CommandExecutor, RESP_EMPTY,
+};
 use crate::{RespArray, RespFrame};
 
 #[derive(Debug)]
@@ -10,6 +12,7 @@ pub struct HMGet {
 
 impl CommandExecutor for HMGet {
     fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        ensure_type(backend, &self.key, "hash")?;
         match backend.hmget(&self.key, self.fields.as_slice()) {
             Some(value) => Ok(RespArray::from(value).into()),
             None => Ok(RESP_EMPTY.clone()),
@@ -23,19 +26,19 @@ impl TryFrom<RespArray> for HMGet {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hmget"], 2)?;
+        validate_command(&value, &["hmget"])?;
 
         let mut args = extract_args(value, 1)?.into_iter();
         let key = match args.next() {
             Some(RespFrame::BulkString(key)) => String::from_utf8(key.to_vec())?,
-            _ => return Err(CommandError::InvalidArgs("Invalid arguments".to_string())),
+            _ => return Err(CommandError::Syntax),
         };
 
         let mut fields: Vec<String> = Vec::with_capacity(args.len() - 1);
         for f in args {
             match f {
                 RespFrame::BulkString(field) => fields.push(String::from_utf8(field.to_vec())?),
-                _ => return Err(CommandError::InvalidArgs("Invalid arguments".to_string())),
+                _ => return Err(CommandError::Syntax),
             }
         }
 
@@ -64,10 +67,11 @@ mod tests {
 
         backend.hset(
             "key".into(),
-            "hello".into(),
-            BulkString::from("world").into(),
-        );
-        backend.hset("key".into(), "hi".into(), BulkString::from("rust").into());
+            vec![
+                ("hello".into(), BulkString::from("world").into()),
+                ("hi".into(), BulkString::from("rust").into()),
+            ],
+        )?;
 
         let ret = cmd.execute(&backend)?;
         let ret = match ret {
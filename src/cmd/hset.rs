@@ -1,18 +1,17 @@
 use crate::backend::Backend;
-use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor, RESP_OK};
+use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor};
 use crate::{RespArray, RespFrame};
 
 #[derive(Debug)]
 pub struct HSet {
     key: String,
-    field: String,
-    value: RespFrame,
+    fields: Vec<(String, RespFrame)>,
 }
 
 impl CommandExecutor for HSet {
     fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
-        backend.hset(self.key, self.field, self.value);
-        Ok(RESP_OK.clone())
+        let added = backend.hset(self.key, self.fields)?;
+        Ok(RespFrame::Integer(added as i64))
     }
 }
 
@@ -22,20 +21,28 @@ impl TryFrom<RespArray> for HSet {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hset"], 3)?;
+        validate_command(&value, &["hset"])?;
+
+        // field value pairs after the key
+        if !value.len().is_multiple_of(2) {
+            return Err(CommandError::WrongArity("hset".to_string()));
+        }
 
         let mut args = extract_args(value, 1)?.into_iter();
-        match (args.next(), args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field)), Some(value)) => {
-                Ok(HSet {
-                    key: String::from_utf8(key.to_vec())?,
-                    field: String::from_utf8(field.to_vec())?,
-                    value,
-                })
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.to_vec())?,
+            _ => return Err(CommandError::Syntax),
+        };
+        let mut fields = Vec::new();
+        while let (Some(field), Some(value)) = (args.next(), args.next()) {
+            match field {
+                RespFrame::BulkString(field) => {
+                    fields.push((String::from_utf8(field.to_vec())?, value))
+                }
+                _ => return Err(CommandError::Syntax),
             }
-
-            _ => Err(CommandError::InvalidArgs("Invalid arguments".to_string())),
         }
+        Ok(HSet { key, fields })
     }
 }
 
@@ -55,8 +62,10 @@ mod tests {
         let cmd = RespArray::decode(&mut cmd)?;
         let set = HSet::try_from(cmd)?;
         assert_eq!(set.key, "key");
-        assert_eq!(set.field, "hello");
-        assert_eq!(set.value, BulkString::new(b"world").into());
+        assert_eq!(
+            set.fields,
+            [("hello".to_string(), BulkString::new(b"world").into())]
+        );
         Ok(())
     }
 
@@ -70,6 +79,34 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_hset_command_pairs() -> Result<()> {
+        let backend = Backend::new();
+
+        // hset key a 1 b 2
+        let mut cmd = bytes::BytesMut::from(
+            &b"*6\r\n$4\r\nhset\r\n$3\r\nkey\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n$1\r\n2\r\n"[..],
+        );
+        let cmd = RespArray::decode(&mut cmd)?;
+        assert_eq!(
+            HSet::try_from(cmd)?.execute(&backend)?,
+            RespFrame::Integer(2)
+        );
+        assert_eq!(backend.hget("key", "a"), Some(BulkString::new(b"1").into()));
+        assert_eq!(backend.hget("key", "b"), Some(BulkString::new(b"2").into()));
+
+        // hset key a 1 b
+        let mut cmd = bytes::BytesMut::from(
+            &b"*5\r\n$4\r\nhset\r\n$3\r\nkey\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n"[..],
+        );
+        let cmd = RespArray::decode(&mut cmd)?;
+        assert_eq!(
+            HSet::try_from(cmd).unwrap_err(),
+            CommandError::WrongArity("hset".into())
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_execute_hset_and_hget() -> Result<()> {
         let backend = Backend::new();
@@ -78,9 +115,12 @@ mod tests {
             &b"*4\r\n$4\r\nhset\r\n$3\r\nkey\r\n$5\r\nhello\r\n$5\r\nworld\r\n"[..],
         );
         let cmd = RespArray::decode(&mut cmd)?;
-        let set = HSet::try_from(cmd)?;
+        let set = HSet::try_from(cmd.clone())?;
         let ret = set.execute(&backend)?;
-        assert_eq!(ret, RESP_OK.clone());
+        assert_eq!(ret, RespFrame::Integer(1));
+        // the field is updated, not added
+        let ret = HSet::try_from(cmd)?.execute(&backend)?;
+        assert_eq!(ret, RespFrame::Integer(0));
 
         let mut cmd =
             bytes::BytesMut::from(&b"*3\r\n$4\r\nhget\r\n$3\r\nkey\r\n$5\r\nhello\r\n"[..]);
@@ -90,7 +130,7 @@ mod tests {
         assert_eq!(ret, BulkString::new(b"world").into());
 
         let mut cmd = bytes::BytesMut::from(
-            &b"*3\r\n$4\r\nhget\r\n$3\r\nkey\r\n$5\r\nnot_exist_field\r\n"[..],
+            &b"*3\r\n$4\r\nhget\r\n$3\r\nkey\r\n$15\r\nnot_exist_field\r\n"[..],
         );
         let cmd = RespArray::decode(&mut cmd)?;
         let get = HGet::try_from(cmd)?;
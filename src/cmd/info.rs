@@ -0,0 +1,492 @@
+use crate::backend::Backend;
+use crate::cmd::{
+    extract_args, extract_strings, validate_command, CommandError, CommandExecutor, REDIS_VERSION,
+};
+use crate::{RespArray, RespFrame, VerbatimString};
+use std::ffi::CStr;
+use std::fmt::{Display, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// every section in the order INFO lists them
+const SECTIONS: &[&str] = &[
+    "server",
+    "clients",
+    "memory",
+    "persistence",
+    "stats",
+    "replication",
+    "cpu",
+    "modules",
+    "commandstats",
+    "errorstats",
+    "latencystats",
+    "cluster",
+    "keyspace",
+];
+
+// the sections left out unless asked for by name, `all` or `everything`
+const NOT_DEFAULT: &[&str] = &["commandstats", "latencystats"];
+
+// the percentiles of latencystats, as redis reports them by default
+const LATENCY_PERCENTILES: &[(&str, f64)] = &[("p50", 50.0), ("p99", 99.0), ("p99.9", 99.9)];
+
+#[derive(Debug)]
+pub struct Info {
+    sections: Vec<&'static str>,
+}
+
+impl CommandExecutor for Info {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        let mut info = String::new();
+        for section in self.sections {
+            if !info.is_empty() {
+                info.push_str("\r\n");
+            }
+            let mut out = Section::new(&mut info, section);
+            match section {
+                "server" => server(backend, &mut out),
+                "clients" => clients(backend, &mut out),
+                "memory" => memory(backend, &mut out),
+                "persistence" => persistence(backend, &mut out),
+                "stats" => stats(backend, &mut out),
+                "replication" => replication(backend, &mut out),
+                "cpu" => cpu(&mut out),
+                "commandstats" => commandstats(backend, &mut out),
+                "errorstats" => errorstats(backend, &mut out),
+                "latencystats" => latencystats(backend, &mut out),
+                "cluster" => out.field("cluster_enabled", 0),
+                "keyspace" => keyspace(backend, &mut out),
+                // no modules are ever loaded
+                _ => {}
+            }
+        }
+        Ok(VerbatimString::text(info).into())
+    }
+}
+
+// writes the header of a section, then its `field:value` lines
+struct Section<'a>(&'a mut String);
+
+impl<'a> Section<'a> {
+    fn new(out: &'a mut String, name: &str) -> Self {
+        let title = match name {
+            "cpu" => "CPU".to_string(),
+            name => name[..1].to_ascii_uppercase() + &name[1..],
+        };
+        let _ = write!(out, "# {}\r\n", title);
+        Section(out)
+    }
+
+    fn field(&mut self, name: impl Display, value: impl Display) {
+        let _ = write!(self.0, "{}:{}\r\n", name, value);
+    }
+}
+
+fn server(backend: &Backend, out: &mut Section) {
+    let stats = backend.stats();
+    let uptime = stats.uptime().as_secs();
+    let executable = std::env::current_exe().unwrap_or_default();
+    let config = backend.config();
+    let config_file = config.file.clone().unwrap_or_default();
+
+    out.field("redis_version", REDIS_VERSION);
+    out.field("redis_git_sha1", "00000000");
+    out.field("redis_git_dirty", 0);
+    out.field("redis_mode", "standalone");
+    out.field("os", os());
+    out.field("arch_bits", usize::BITS);
+    out.field("process_id", std::process::id());
+    out.field("run_id", stats.run_id());
+    out.field("tcp_port", config.port);
+    // unixsocket is immutable, so it is the path of the socket being listened on
+    out.field("unixsocket", &config.unixsocket);
+    out.field("server_time_usec", unix_time().as_micros());
+    out.field("uptime_in_seconds", uptime);
+    out.field("uptime_in_days", uptime / 86400);
+    out.field("executable", executable.display());
+    out.field("config_file", config_file.display());
+    out.field("io_threads_active", 0);
+}
+
+fn clients(backend: &Backend, out: &mut Section) {
+    let clients = backend.clients().list();
+    let max_input = clients.iter().map(|c| c.qbuf).max().unwrap_or_default();
+    let max_output = clients.iter().map(|c| c.omem).max().unwrap_or_default();
+    let pubsub = clients.iter().filter(|c| c.is_subscribed()).count();
+
+    out.field("connected_clients", clients.len());
+    out.field("cluster_connections", 0);
+    out.field("maxclients", backend.config().maxclients);
+    out.field("client_recent_max_input_buffer", max_input);
+    out.field("client_recent_max_output_buffer", max_output);
+    out.field("blocked_clients", 0);
+    out.field("tracking_clients", 0);
+    out.field("pubsub_clients", pubsub);
+    out.field("watching_clients", 0);
+    out.field("clients_in_timeout_table", 0);
+    out.field("total_watched_keys", 0);
+    out.field("total_blocking_keys", 0);
+}
+
+fn memory(backend: &Backend, out: &mut Section) {
+    // there are no allocator statistics, the resident set size stands in for the memory used
+    let used = resident_memory();
+    let peak = backend.stats().peak_memory(used);
+    let maxmemory = backend.config().maxmemory as u64;
+
+    out.field("used_memory", used);
+    out.field("used_memory_human", bytes_to_human(used));
+    out.field("used_memory_rss", used);
+    out.field("used_memory_rss_human", bytes_to_human(used));
+    out.field("used_memory_peak", peak);
+    out.field("used_memory_peak_human", bytes_to_human(peak));
+    out.field("maxmemory", maxmemory);
+    out.field("maxmemory_human", bytes_to_human(maxmemory));
+    out.field("maxmemory_policy", "noeviction");
+    out.field("mem_fragmentation_ratio", "1.00");
+    out.field("mem_allocator", "libc");
+}
+
+fn persistence(backend: &Backend, out: &mut Section) {
+    // the dataset is never saved, like a fresh redis the last save is when the server started
+    let start_time = backend
+        .stats()
+        .start_time()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    out.field("loading", 0);
+    out.field("async_loading", 0);
+    out.field("rdb_changes_since_last_save", backend.stats().dirty.get());
+    out.field("rdb_bgsave_in_progress", 0);
+    out.field("rdb_last_save_time", start_time.as_secs());
+    out.field("rdb_last_bgsave_status", "ok");
+    out.field("rdb_last_bgsave_time_sec", -1);
+    out.field("rdb_current_bgsave_time_sec", -1);
+    out.field("aof_enabled", 0);
+    out.field("aof_rewrite_in_progress", 0);
+    out.field("aof_rewrite_scheduled", 0);
+    out.field("aof_last_rewrite_time_sec", -1);
+    out.field("aof_current_rewrite_time_sec", -1);
+    out.field("aof_last_bgrewrite_status", "ok");
+    out.field("aof_last_write_status", "ok");
+}
+
+fn stats(backend: &Backend, out: &mut Section) {
+    let stats = backend.stats();
+    let pubsub = backend.pubsub();
+
+    out.field(
+        "total_connections_received",
+        stats.connections_received.get(),
+    );
+    out.field("total_commands_processed", stats.commands_processed.get());
+    out.field("total_net_input_bytes", stats.net_input_bytes.get());
+    out.field("total_net_output_bytes", stats.net_output_bytes.get());
+    out.field("rejected_connections", stats.rejected_connections.get());
+    out.field("expired_keys", 0);
+    out.field("evicted_keys", 0);
+    out.field("evicted_clients", 0);
+    out.field("keyspace_hits", stats.keyspace_hits.get());
+    out.field("keyspace_misses", stats.keyspace_misses.get());
+    out.field("pubsub_channels", pubsub.channels(None).len());
+    out.field("pubsub_patterns", pubsub.numpat());
+    out.field("pubsub_shardchannels", pubsub.shard_channels(None).len());
+    out.field("total_forks", 0);
+    out.field("total_error_replies", stats.error_replies.get());
+    out.field("total_reads_processed", stats.reads_processed.get());
+    out.field("total_writes_processed", stats.writes_processed.get());
+    out.field(
+        "client_query_buffer_limit_disconnections",
+        stats.query_buffer_limit_disconnections.get(),
+    );
+    out.field(
+        "client_output_buffer_limit_disconnections",
+        stats.output_buffer_limit_disconnections.get(),
+    );
+    out.field("acl_access_denied_auth", stats.acl_denied_auth.get());
+    out.field("acl_access_denied_cmd", stats.acl_denied_cmd.get());
+    out.field("acl_access_denied_key", stats.acl_denied_key.get());
+    out.field("acl_access_denied_channel", stats.acl_denied_channel.get());
+}
+
+fn replication(backend: &Backend, out: &mut Section) {
+    out.field("role", "master");
+    out.field("connected_slaves", 0);
+    out.field("master_failover_state", "no-failover");
+    out.field("master_replid", backend.stats().replication_id());
+    out.field("master_replid2", "0".repeat(40));
+    out.field("master_repl_offset", 0);
+    out.field("second_repl_offset", -1);
+    out.field("repl_backlog_active", 0);
+    out.field("repl_backlog_size", 1048576);
+    out.field("repl_backlog_first_byte_offset", 0);
+    out.field("repl_backlog_histlen", 0);
+}
+
+fn cpu(out: &mut Section) {
+    let (sys, user) = cpu_time(libc::RUSAGE_SELF);
+    let (sys_children, user_children) = cpu_time(libc::RUSAGE_CHILDREN);
+    out.field("used_cpu_sys", format!("{:.6}", sys));
+    out.field("used_cpu_user", format!("{:.6}", user));
+    out.field("used_cpu_sys_children", format!("{:.6}", sys_children));
+    out.field("used_cpu_user_children", format!("{:.6}", user_children));
+}
+
+fn commandstats(backend: &Backend, out: &mut Section) {
+    for (name, stats) in backend.stats().commands() {
+        let usec_per_call = match stats.calls {
+            0 => 0.0,
+            calls => stats.usec as f64 / calls as f64,
+        };
+        out.field(
+            format!("cmdstat_{}", name),
+            format!(
+                "calls={},usec={},usec_per_call={:.2},rejected_calls={},failed_calls={}",
+                stats.calls, stats.usec, usec_per_call, stats.rejected_calls, stats.failed_calls
+            ),
+        );
+    }
+}
+
+fn errorstats(backend: &Backend, out: &mut Section) {
+    for (code, count) in backend.stats().errors() {
+        out.field(format!("errorstat_{}", code), format!("count={}", count));
+    }
+}
+
+fn latencystats(backend: &Backend, out: &mut Section) {
+    for (name, stats) in backend.stats().commands() {
+        if stats.latency.is_empty() {
+            continue;
+        }
+        let percentiles: Vec<String> = LATENCY_PERCENTILES
+            .iter()
+            .map(|(label, p)| format!("{}={:.3}", label, stats.latency.percentile(*p) as f64))
+            .collect();
+        out.field(
+            format!("latency_percentiles_usec_{}", name),
+            percentiles.join(","),
+        );
+    }
+}
+
+fn keyspace(backend: &Backend, out: &mut Section) {
+    // like redis, an empty db is left out, and no key ever expires
+    let keys = backend.dbsize();
+    if keys > 0 {
+        out.field(
+            "db0",
+            format!("keys={},expires=0,avg_ttl=0,subexpiry=0", keys),
+        );
+    }
+}
+
+fn unix_time() -> std::time::Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+// like redis, the kernel name, release and machine
+fn os() -> String {
+    let mut uts: libc::utsname = unsafe { std::mem::zeroed() };
+    // SAFETY: uname fills in the struct it is given, its fields are NUL terminated
+    if unsafe { libc::uname(&mut uts) } != 0 {
+        return std::env::consts::OS.to_string();
+    }
+    let field = |field: &[libc::c_char]| {
+        unsafe { CStr::from_ptr(field.as_ptr()) }
+            .to_string_lossy()
+            .into_owned()
+    };
+    format!(
+        "{} {} {}",
+        field(&uts.sysname),
+        field(&uts.release),
+        field(&uts.machine)
+    )
+}
+
+// seconds of system and user CPU time used by `who`
+fn cpu_time(who: libc::c_int) -> (f64, f64) {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    // SAFETY: getrusage only writes to the struct it is given
+    if unsafe { libc::getrusage(who, &mut usage) } != 0 {
+        return (0.0, 0.0);
+    }
+    let seconds = |time: libc::timeval| time.tv_sec as f64 + time.tv_usec as f64 / 1e6;
+    (seconds(usage.ru_stime), seconds(usage.ru_utime))
+}
+
+// the resident set size of the process, 0 where /proc is not there to tell it
+fn resident_memory() -> u64 {
+    let pages = std::fs::read_to_string("/proc/self/statm")
+        .ok()
+        .and_then(|statm| statm.split_whitespace().nth(1)?.parse::<u64>().ok())
+        .unwrap_or_default();
+    // SAFETY: sysconf has no preconditions
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    pages * page_size.max(0) as u64
+}
+
+// like redis, `1.50M` and the like
+fn bytes_to_human(bytes: u64) -> String {
+    const UNITS: &[&str] = &["K", "M", "G", "T", "P"];
+    if bytes < 1024 {
+        return format!("{}B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    for unit in &UNITS[..UNITS.len() - 1] {
+        if value < 1024.0 {
+            return format!("{:.2}{}", value, unit);
+        }
+        value /= 1024.0;
+    }
+    format!("{:.2}{}", value, UNITS[UNITS.len() - 1])
+}
+
+// info [section [section ...]]
+// *3\r\n$4\r\ninfo\r\n$6\r\nserver\r\n$7\r\nclients\r\n
+impl TryFrom<RespArray> for Info {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["info"])?;
+
+        let mut args = extract_strings(extract_args(value, 1)?)?;
+        if args.is_empty() {
+            args.push("default".to_string());
+        }
+        // sections are listed once each in their usual order, unknown ones are left out
+        let mut wanted = vec![false; SECTIONS.len()];
+        for arg in args {
+            let arg = arg.to_ascii_lowercase();
+            for (i, section) in SECTIONS.iter().enumerate() {
+                wanted[i] |= match arg.as_str() {
+                    "all" | "everything" => true,
+                    "default" => !NOT_DEFAULT.contains(section),
+                    arg => arg == *section,
+                };
+            }
+        }
+        let sections = SECTIONS
+            .iter()
+            .zip(wanted)
+            .filter_map(|(section, wanted)| wanted.then_some(*section))
+            .collect();
+        Ok(Info { sections })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::config::ConfigResetStat;
+    use crate::config::ServerConfig;
+    use crate::{BulkString, RespDecode};
+    use anyhow::Result;
+    use std::time::Duration;
+
+    fn run(backend: &Backend, cmd: &[u8]) -> Result<String> {
+        let mut cmd = bytes::BytesMut::from(cmd);
+        let info = Info::try_from(RespArray::decode(&mut cmd)?)?;
+        let RespFrame::VerbatimString(reply) = info.execute(backend)? else {
+            panic!("Expected VerbatimString");
+        };
+        Ok(String::from_utf8(reply.to_vec())?)
+    }
+
+    #[test]
+    fn test_info_sections() -> Result<()> {
+        // info
+        let info = Info::try_from(RespArray::decode(&mut bytes::BytesMut::from(
+            &b"*1\r\n$4\r\ninfo\r\n"[..],
+        ))?)?;
+        assert_eq!(
+            info.sections,
+            [
+                "server",
+                "clients",
+                "memory",
+                "persistence",
+                "stats",
+                "replication",
+                "cpu",
+                "modules",
+                "errorstats",
+                "cluster",
+                "keyspace"
+            ]
+        );
+        // info everything
+        let info = Info::try_from(RespArray::decode(&mut bytes::BytesMut::from(
+            &b"*2\r\n$4\r\ninfo\r\n$10\r\neverything\r\n"[..],
+        ))?)?;
+        assert_eq!(info.sections, SECTIONS);
+
+        let backend = Backend::new();
+        backend.set("a".into(), BulkString::from("1").into());
+        // info keyspace CLUSTER nosuch keyspace
+        let reply = run(
+            &backend,
+            b"*5\r\n$4\r\ninfo\r\n$8\r\nkeyspace\r\n$7\r\nCLUSTER\r\n$6\r\nnosuch\r\n$8\r\nkeyspace\r\n",
+        )?;
+        assert_eq!(
+            reply,
+            "# Cluster\r\ncluster_enabled:0\r\n\r\n# Keyspace\r\ndb0:keys=1,expires=0,avg_ttl=0,subexpiry=0\r\n"
+        );
+
+        let reply = run(&backend, b"*2\r\n$4\r\ninfo\r\n$6\r\nserver\r\n")?;
+        assert!(reply.starts_with("# Server\r\nredis_version:7.4.0\r\n"));
+        assert!(reply.contains(&format!("run_id:{}\r\n", backend.stats().run_id())));
+        assert!(reply.contains("tcp_port:6379\r\nunixsocket:\r\n"));
+
+        let mut config = ServerConfig::default();
+        config.apply("unixsocket /tmp/redis.sock\n")?;
+        let backend = Backend::with_config(config);
+        let reply = run(&backend, b"*2\r\n$4\r\ninfo\r\n$6\r\nserver\r\n")?;
+        assert!(reply.contains("unixsocket:/tmp/redis.sock\r\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_info_stats_and_resetstat() -> Result<()> {
+        let backend = Backend::new();
+        backend.get("a");
+        let stats = backend.stats();
+        stats.record_call("get", Duration::from_micros(3), false);
+        stats.record_rejected("set");
+        stats.record_error("ERR syntax error");
+
+        // info commandstats errorstats latencystats
+        let cmd = b"*4\r\n$4\r\ninfo\r\n$12\r\ncommandstats\r\n$10\r\nerrorstats\r\n$12\r\nlatencystats\r\n";
+        assert_eq!(
+            run(&backend, cmd)?,
+            "# Commandstats\r\n\
+             cmdstat_get:calls=1,usec=3,usec_per_call=3.00,rejected_calls=0,failed_calls=0\r\n\
+             cmdstat_set:calls=0,usec=0,usec_per_call=0.00,rejected_calls=1,failed_calls=0\r\n\
+             \r\n# Errorstats\r\nerrorstat_ERR:count=1\r\n\
+             \r\n# Latencystats\r\nlatency_percentiles_usec_get:p50=3.000,p99=3.000,p99.9=3.000\r\n"
+        );
+        let reply = run(&backend, b"*2\r\n$4\r\ninfo\r\n$5\r\nstats\r\n")?;
+        assert!(reply.contains("total_commands_processed:1\r\n"));
+        assert!(reply.contains("keyspace_misses:1\r\n"));
+
+        ConfigResetStat.execute(&backend)?;
+        assert_eq!(
+            run(&backend, cmd)?,
+            "# Commandstats\r\n\r\n# Errorstats\r\n\r\n# Latencystats\r\n"
+        );
+        let reply = run(&backend, b"*2\r\n$4\r\ninfo\r\n$5\r\nstats\r\n")?;
+        assert!(reply.contains("keyspace_misses:0\r\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bytes_to_human() {
+        assert_eq!(bytes_to_human(1000), "1000B");
+        assert_eq!(bytes_to_human(1536), "1.50K");
+        assert_eq!(bytes_to_human(100 * 1024 * 1024), "100.00M");
+    }
+}
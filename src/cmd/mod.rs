@@ -1,23 +1,89 @@
-use crate::backend::Backend;
+use crate::backend::{Backend, WrongType};
+use crate::cmd::acl::{
+    AclCat, AclDelUser, AclGetUser, AclList, AclLoad, AclLog, AclSave, AclSetUser, AclUsers,
+    AclWhoAmI,
+};
+use crate::cmd::auth::Auth;
+use crate::cmd::client::{
+    ClientGetName, ClientId, ClientInfoCmd, ClientKill, ClientList, ClientNoEvict, ClientPause,
+    ClientReply, ClientSetName, ClientUnpause,
+};
+use crate::cmd::command::{CommandCount, CommandDocs, CommandGetKeys, CommandInfo, CommandList};
+use crate::cmd::config::{ConfigGet, ConfigResetStat, ConfigRewrite, ConfigSet};
 use crate::cmd::echo::Echo;
+use crate::cmd::fcall::FCall;
+use crate::cmd::function::{
+    FunctionDelete, FunctionDump, FunctionFlush, FunctionKill, FunctionList, FunctionLoad,
+    FunctionRestore,
+};
 use crate::cmd::get::Get;
+use crate::cmd::hello::Hello;
 use crate::cmd::hget::HGet;
 use crate::cmd::hgetall::HGetAll;
 use crate::cmd::hmget::HMGet;
 use crate::cmd::hset::HSet;
+use crate::cmd::info::Info;
+use crate::cmd::ping::Ping;
+use crate::cmd::psubscribe::PSubscribe;
+use crate::cmd::publish::Publish;
+use crate::cmd::pubsub::{
+    PubSubChannels, PubSubNumPat, PubSubNumSub, PubSubShardChannels, PubSubShardNumSub,
+};
+use crate::cmd::punsubscribe::PUnsubscribe;
+use crate::cmd::quit::Quit;
+use crate::cmd::reset::Reset;
 use crate::cmd::set::Set;
-use crate::{RespArray, RespFrame, RespNull};
+use crate::cmd::shutdown::Shutdown;
+use crate::cmd::spublish::SPublish;
+use crate::cmd::ssubscribe::SSubscribe;
+use crate::cmd::subscribe::Subscribe;
+use crate::cmd::sunsubscribe::SUnsubscribe;
+use crate::cmd::time::Time;
+use crate::cmd::unsubscribe::Unsubscribe;
+use crate::network::Session;
+use crate::{BulkString, RespArray, RespEncode, RespFrame, RespNull, RespPush, SimpleError};
 use enum_dispatch::enum_dispatch;
 use lazy_static::lazy_static;
+use std::future::Future;
+use std::pin::Pin;
 use thiserror::Error;
 
+mod acl;
+mod auth;
+mod client;
+mod command;
+mod config;
 mod echo;
+mod fcall;
+mod function;
 mod get;
+mod hello;
 mod hget;
 mod hgetall;
 mod hmget;
 mod hset;
+mod info;
+mod ping;
+mod psubscribe;
+mod publish;
+mod pubsub;
+mod punsubscribe;
+mod quit;
+mod reset;
 mod set;
+mod shutdown;
+mod spublish;
+mod ssubscribe;
+mod subscribe;
+mod sunsubscribe;
+mod table;
+mod time;
+mod unsubscribe;
+
+pub(crate) use table::{lookup, resolve, CommandSpec, COMMAND_TABLE};
+
+// the Redis version whose behaviour this server follows, reported to clients
+pub const REDIS_VERSION: &str = "7.4.0";
 
 lazy_static! {
     static ref RESP_OK: RespFrame = RespFrame::SimpleString("OK".into());
@@ -26,10 +92,7 @@ lazy_static! {
 }
 
 #[derive(Debug)]
-pub struct Unrecognized;
-
-#[derive(Debug)]
-#[enum_dispatch(CommandExecutor)]
+#[enum_dispatch(ContextExecutor)]
 pub enum Command {
     Echo(Echo),
     Get(Get),
@@ -38,29 +101,178 @@ pub enum Command {
     HMGet(HMGet),
     HSet(HSet),
     HSetAll(HGetAll),
-    Unrecognized(Unrecognized),
+    FCall(FCall),
+    FunctionDelete(FunctionDelete),
+    FunctionDump(FunctionDump),
+    FunctionFlush(FunctionFlush),
+    FunctionKill(FunctionKill),
+    FunctionList(FunctionList),
+    FunctionLoad(FunctionLoad),
+    FunctionRestore(FunctionRestore),
+    Info(Info),
+    Publish(Publish),
+    PubSubChannels(PubSubChannels),
+    PubSubNumSub(PubSubNumSub),
+    PubSubNumPat(PubSubNumPat),
+    SPublish(SPublish),
+    PubSubShardChannels(PubSubShardChannels),
+    PubSubShardNumSub(PubSubShardNumSub),
+    ConfigGet(ConfigGet),
+    ConfigSet(ConfigSet),
+    ConfigRewrite(ConfigRewrite),
+    ConfigResetStat(ConfigResetStat),
+    CommandCount(CommandCount),
+    CommandInfo(CommandInfo),
+    CommandDocs(CommandDocs),
+    CommandList(CommandList),
+    CommandGetKeys(CommandGetKeys),
+    Subscribe(Subscribe),
+    Unsubscribe(Unsubscribe),
+    PSubscribe(PSubscribe),
+    PUnsubscribe(PUnsubscribe),
+    SSubscribe(SSubscribe),
+    SUnsubscribe(SUnsubscribe),
+    Hello(Hello),
+    Auth(Auth),
+    AclCat(AclCat),
+    AclDelUser(AclDelUser),
+    AclGetUser(AclGetUser),
+    AclList(AclList),
+    AclLoad(AclLoad),
+    AclLog(AclLog),
+    AclSave(AclSave),
+    AclSetUser(AclSetUser),
+    AclUsers(AclUsers),
+    AclWhoAmI(AclWhoAmI),
+    ClientGetName(ClientGetName),
+    ClientId(ClientId),
+    ClientInfo(ClientInfoCmd),
+    ClientKill(ClientKill),
+    ClientList(ClientList),
+    ClientNoEvict(ClientNoEvict),
+    ClientPause(ClientPause),
+    ClientReply(ClientReply),
+    ClientSetName(ClientSetName),
+    ClientUnpause(ClientUnpause),
+    Shutdown(Shutdown),
+    Ping(Ping),
+    Quit(Quit),
+    Reset(Reset),
+    Time(Time),
 }
 
+/// Errors are displayed as the exact text of the error reply redis sends for them.
 #[derive(Error, Debug, PartialEq, Eq)]
 pub enum CommandError {
-    #[error("Invalid frame: {0}")]
+    #[error("ERR Protocol error: {0}")]
     InvalidFrame(String),
-    #[error("Invalid frame type: {0}")]
+    #[error("ERR {0}")]
     InvalidCmd(String),
-    #[error("Invalid frame args: {0}")]
+    // the whole reply, starting with its error code
+    #[error("{0}")]
     InvalidArgs(String),
-    #[error("From utf8 error: {0}")]
+    #[error("ERR unknown command '{0}', with args beginning with: {1}")]
+    UnknownCommand(String, String),
+    #[error("ERR unknown subcommand '{0}'. Try {1} HELP.")]
+    UnknownSubcommand(String, String),
+    #[error("ERR wrong number of arguments for '{0}' command")]
+    WrongArity(String),
+    #[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("ERR syntax error")]
+    Syntax,
+    #[error(
+        "BUSY Redis is busy running a script. You can only call FUNCTION KILL or SHUTDOWN NOSAVE."
+    )]
+    Busy,
+    #[error("NOAUTH Authentication required.")]
+    NoAuth,
+    #[error("WRONGPASS invalid username-password pair or user is disabled.")]
+    WrongPass,
+    #[error("NOPERM {0}")]
+    NoPerm(String),
+    #[error("ERR {0}")]
     FromUtf8Error(#[from] std::string::FromUtf8Error),
 }
 
+impl From<WrongType> for CommandError {
+    fn from(_: WrongType) -> Self {
+        CommandError::WrongType
+    }
+}
+
+impl CommandError {
+    fn unknown_command(frame: &RespArray) -> Self {
+        // like redis, quote the arguments until the list grows past 128 bytes
+        let mut args = String::new();
+        for arg in frame.iter().skip(1) {
+            if args.len() >= 128 {
+                break;
+            }
+            args.push_str(&format!("'{}' ", frame_lossy(arg)));
+        }
+        CommandError::UnknownCommand(frame.first().map(frame_lossy).unwrap_or_default(), args)
+    }
+
+    fn unknown_subcommand(frame: &RespArray) -> Self {
+        CommandError::UnknownSubcommand(
+            frame.get(1).map(frame_lossy).unwrap_or_default(),
+            frame_lossy(&frame[0]).to_ascii_uppercase(),
+        )
+    }
+}
+
+impl From<CommandError> for SimpleError {
+    fn from(e: CommandError) -> Self {
+        // arguments echoed in the message must not break the reply onto several lines
+        SimpleError::new(e.to_string().replace(['\r', '\n'], " "))
+    }
+}
+
+fn frame_lossy(frame: &RespFrame) -> String {
+    match frame {
+        RespFrame::BulkString(s) => String::from_utf8_lossy(s).into(),
+        frame => String::from_utf8_lossy(&frame.encode()).trim_end().into(),
+    }
+}
+
+/// The replies of a command, which may await I/O or other clients before it resolves.
+pub type ExecFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Vec<RespFrame>, CommandError>> + Send + 'a>>;
+
+/// Runs a command on behalf of the connection it was sent on, which gives access to the
+/// backend, the protocol, the selected db and the client id. A command may reply with any
+/// number of frames, including none.
 #[enum_dispatch]
+pub trait ContextExecutor {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_>;
+
+    /// Runs the command to its end without awaiting, as a script calls it. Commands that
+    /// have to await are flagged noscript and keep this refusal.
+    fn execute_sync(self, _session: &mut Session) -> Result<RespFrame, CommandError>
+    where
+        Self: Sized,
+    {
+        Err(CommandError::InvalidCmd(
+            "This Redis command is not allowed from script".to_string(),
+        ))
+    }
+}
+
+/// Commands that only read or write the backend and reply with a single frame, every such
+/// command is a `ContextExecutor` as well.
 pub trait CommandExecutor {
     fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError>;
 }
 
-impl CommandExecutor for Unrecognized {
-    fn execute(self, _: &Backend) -> Result<RespFrame, CommandError> {
-        Ok(RESP_OK.clone())
+impl<T: CommandExecutor + Send + 'static> ContextExecutor for T {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        let reply = CommandExecutor::execute(self, session.backend());
+        Box::pin(std::future::ready(reply.map(|frame| vec![frame])))
+    }
+
+    fn execute_sync(self, session: &mut Session) -> Result<RespFrame, CommandError> {
+        CommandExecutor::execute(self, session.backend())
     }
 }
 
@@ -73,32 +285,184 @@ impl TryFrom<RespFrame> for Command {
             _ => return Err(CommandError::InvalidFrame("Invalid frame type".to_string())),
         };
 
-        match frame.first() {
-            Some(RespFrame::BulkString(cmd)) => match cmd.as_ref() {
-                b"echo" => Ok(Command::Echo(Echo::try_from(frame)?)),
-                b"get" => Ok(Command::Get(Get::try_from(frame)?)),
-                b"set" => Ok(Command::Set(Set::try_from(frame)?)),
-                b"hget" => Ok(Command::HGet(HGet::try_from(frame)?)),
-                b"hmget" => Ok(Command::HMGet(HMGet::try_from(frame)?)),
-                b"hset" => Ok(Command::HSet(HSet::try_from(frame)?)),
-                b"hgetall" => Ok(Command::HSetAll(HGetAll::try_from(frame)?)),
-                _ => Ok(Command::Unrecognized(Unrecognized)),
-            },
-            _ => Err(CommandError::InvalidArgs("Invalid arguments".to_string())),
+        match table::resolve(&frame)?.name {
+            "echo" => Ok(Command::Echo(Echo::try_from(frame)?)),
+            "get" => Ok(Command::Get(Get::try_from(frame)?)),
+            "set" => Ok(Command::Set(Set::try_from(frame)?)),
+            "hget" => Ok(Command::HGet(HGet::try_from(frame)?)),
+            "hmget" => Ok(Command::HMGet(HMGet::try_from(frame)?)),
+            "hset" => Ok(Command::HSet(HSet::try_from(frame)?)),
+            "hgetall" => Ok(Command::HSetAll(HGetAll::try_from(frame)?)),
+            "fcall" => Ok(Command::FCall(FCall::try_from(frame)?)),
+            "fcall_ro" => Ok(Command::FCall(FCall::try_from(frame)?)),
+            "function|delete" => Ok(Command::FunctionDelete(FunctionDelete::try_from(frame)?)),
+            "function|dump" => Ok(Command::FunctionDump(FunctionDump::try_from(frame)?)),
+            "function|flush" => Ok(Command::FunctionFlush(FunctionFlush::try_from(frame)?)),
+            "function|kill" => Ok(Command::FunctionKill(FunctionKill::try_from(frame)?)),
+            "function|list" => Ok(Command::FunctionList(FunctionList::try_from(frame)?)),
+            "function|load" => Ok(Command::FunctionLoad(FunctionLoad::try_from(frame)?)),
+            "function|restore" => Ok(Command::FunctionRestore(FunctionRestore::try_from(frame)?)),
+            "info" => Ok(Command::Info(Info::try_from(frame)?)),
+            "publish" => Ok(Command::Publish(Publish::try_from(frame)?)),
+            "spublish" => Ok(Command::SPublish(SPublish::try_from(frame)?)),
+            "config|get" => Ok(Command::ConfigGet(ConfigGet::try_from(frame)?)),
+            "config|set" => Ok(Command::ConfigSet(ConfigSet::try_from(frame)?)),
+            "config|rewrite" => Ok(Command::ConfigRewrite(ConfigRewrite::try_from(frame)?)),
+            "config|resetstat" => Ok(Command::ConfigResetStat(ConfigResetStat::try_from(frame)?)),
+            "pubsub|channels" => Ok(Command::PubSubChannels(PubSubChannels::try_from(frame)?)),
+            "pubsub|numsub" => Ok(Command::PubSubNumSub(PubSubNumSub::try_from(frame)?)),
+            "pubsub|numpat" => Ok(Command::PubSubNumPat(PubSubNumPat::try_from(frame)?)),
+            "pubsub|shardchannels" => Ok(Command::PubSubShardChannels(
+                PubSubShardChannels::try_from(frame)?,
+            )),
+            "pubsub|shardnumsub" => Ok(Command::PubSubShardNumSub(PubSubShardNumSub::try_from(
+                frame,
+            )?)),
+            "command" => Ok(Command::CommandInfo(CommandInfo::all())),
+            "command|count" => Ok(Command::CommandCount(CommandCount)),
+            "command|info" => Ok(Command::CommandInfo(CommandInfo::try_from(frame)?)),
+            "command|docs" => Ok(Command::CommandDocs(CommandDocs::try_from(frame)?)),
+            "command|list" => Ok(Command::CommandList(CommandList::try_from(frame)?)),
+            "command|getkeys" => Ok(Command::CommandGetKeys(CommandGetKeys::try_from(frame)?)),
+            "subscribe" => Ok(Command::Subscribe(Subscribe::try_from(frame)?)),
+            "unsubscribe" => Ok(Command::Unsubscribe(Unsubscribe::try_from(frame)?)),
+            "psubscribe" => Ok(Command::PSubscribe(PSubscribe::try_from(frame)?)),
+            "punsubscribe" => Ok(Command::PUnsubscribe(PUnsubscribe::try_from(frame)?)),
+            "ssubscribe" => Ok(Command::SSubscribe(SSubscribe::try_from(frame)?)),
+            "sunsubscribe" => Ok(Command::SUnsubscribe(SUnsubscribe::try_from(frame)?)),
+            "hello" => Ok(Command::Hello(Hello::try_from(frame)?)),
+            "auth" => Ok(Command::Auth(Auth::try_from(frame)?)),
+            "acl|cat" => Ok(Command::AclCat(AclCat::try_from(frame)?)),
+            "acl|deluser" => Ok(Command::AclDelUser(AclDelUser::try_from(frame)?)),
+            "acl|getuser" => Ok(Command::AclGetUser(AclGetUser::try_from(frame)?)),
+            "acl|list" => Ok(Command::AclList(AclList::try_from(frame)?)),
+            "acl|load" => Ok(Command::AclLoad(AclLoad::try_from(frame)?)),
+            "acl|log" => Ok(Command::AclLog(AclLog::try_from(frame)?)),
+            "acl|save" => Ok(Command::AclSave(AclSave::try_from(frame)?)),
+            "acl|setuser" => Ok(Command::AclSetUser(AclSetUser::try_from(frame)?)),
+            "acl|users" => Ok(Command::AclUsers(AclUsers::try_from(frame)?)),
+            "acl|whoami" => Ok(Command::AclWhoAmI(AclWhoAmI::try_from(frame)?)),
+            "client|getname" => Ok(Command::ClientGetName(ClientGetName::try_from(frame)?)),
+            "client|id" => Ok(Command::ClientId(ClientId::try_from(frame)?)),
+            "client|info" => Ok(Command::ClientInfo(ClientInfoCmd::try_from(frame)?)),
+            "client|kill" => Ok(Command::ClientKill(ClientKill::try_from(frame)?)),
+            "client|list" => Ok(Command::ClientList(ClientList::try_from(frame)?)),
+            "client|no-evict" => Ok(Command::ClientNoEvict(ClientNoEvict::try_from(frame)?)),
+            "client|pause" => Ok(Command::ClientPause(ClientPause::try_from(frame)?)),
+            "client|reply" => Ok(Command::ClientReply(ClientReply::try_from(frame)?)),
+            "client|setname" => Ok(Command::ClientSetName(ClientSetName::try_from(frame)?)),
+            "client|unpause" => Ok(Command::ClientUnpause(ClientUnpause::try_from(frame)?)),
+            "shutdown" => Ok(Command::Shutdown(Shutdown::try_from(frame)?)),
+            "ping" => Ok(Command::Ping(Ping::try_from(frame)?)),
+            "quit" => Ok(Command::Quit(Quit::try_from(frame)?)),
+            "reset" => Ok(Command::Reset(Reset::try_from(frame)?)),
+            "time" => Ok(Command::Time(Time::try_from(frame)?)),
+            // every command in the table is parsed above
+            _ => Err(CommandError::unknown_command(&frame)),
+        }
+    }
+}
+
+impl Command {
+    /// The full name of the command, `container|subcommand` for subcommands.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Echo(_) => "echo",
+            Command::Get(_) => "get",
+            Command::Set(_) => "set",
+            Command::HGet(_) => "hget",
+            Command::HMGet(_) => "hmget",
+            Command::HSet(_) => "hset",
+            Command::HSetAll(_) => "hgetall",
+            Command::FCall(fcall) => fcall.name(),
+            Command::FunctionDelete(_) => "function|delete",
+            Command::FunctionDump(_) => "function|dump",
+            Command::FunctionFlush(_) => "function|flush",
+            Command::FunctionKill(_) => "function|kill",
+            Command::FunctionList(_) => "function|list",
+            Command::FunctionLoad(_) => "function|load",
+            Command::FunctionRestore(_) => "function|restore",
+            Command::Info(_) => "info",
+            Command::Publish(_) => "publish",
+            Command::SPublish(_) => "spublish",
+            Command::PubSubChannels(_) => "pubsub|channels",
+            Command::PubSubNumSub(_) => "pubsub|numsub",
+            Command::PubSubNumPat(_) => "pubsub|numpat",
+            Command::PubSubShardChannels(_) => "pubsub|shardchannels",
+            Command::PubSubShardNumSub(_) => "pubsub|shardnumsub",
+            Command::ConfigGet(_) => "config|get",
+            Command::ConfigSet(_) => "config|set",
+            Command::ConfigRewrite(_) => "config|rewrite",
+            Command::ConfigResetStat(_) => "config|resetstat",
+            Command::CommandCount(_) => "command|count",
+            Command::CommandInfo(_) => "command|info",
+            Command::CommandDocs(_) => "command|docs",
+            Command::CommandList(_) => "command|list",
+            Command::CommandGetKeys(_) => "command|getkeys",
+            Command::Subscribe(_) => "subscribe",
+            Command::Unsubscribe(_) => "unsubscribe",
+            Command::PSubscribe(_) => "psubscribe",
+            Command::PUnsubscribe(_) => "punsubscribe",
+            Command::SSubscribe(_) => "ssubscribe",
+            Command::SUnsubscribe(_) => "sunsubscribe",
+            Command::Hello(_) => "hello",
+            Command::Auth(_) => "auth",
+            Command::AclCat(_) => "acl|cat",
+            Command::AclDelUser(_) => "acl|deluser",
+            Command::AclGetUser(_) => "acl|getuser",
+            Command::AclList(_) => "acl|list",
+            Command::AclLoad(_) => "acl|load",
+            Command::AclLog(_) => "acl|log",
+            Command::AclSave(_) => "acl|save",
+            Command::AclSetUser(_) => "acl|setuser",
+            Command::AclUsers(_) => "acl|users",
+            Command::AclWhoAmI(_) => "acl|whoami",
+            Command::ClientGetName(_) => "client|getname",
+            Command::ClientId(_) => "client|id",
+            Command::ClientInfo(_) => "client|info",
+            Command::ClientKill(_) => "client|kill",
+            Command::ClientList(_) => "client|list",
+            Command::ClientNoEvict(_) => "client|no-evict",
+            Command::ClientPause(_) => "client|pause",
+            Command::ClientReply(_) => "client|reply",
+            Command::ClientSetName(_) => "client|setname",
+            Command::ClientUnpause(_) => "client|unpause",
+            Command::Shutdown(_) => "shutdown",
+            Command::Ping(_) => "ping",
+            Command::Quit(_) => "quit",
+            Command::Reset(_) => "reset",
+            Command::Time(_) => "time",
         }
     }
+
+    /// Whether a RESP2 client may send the command while it is subscribed to channels.
+    pub fn allowed_when_subscribed(&self) -> bool {
+        matches!(
+            self,
+            Command::Subscribe(_)
+                | Command::Unsubscribe(_)
+                | Command::PSubscribe(_)
+                | Command::PUnsubscribe(_)
+                | Command::SSubscribe(_)
+                | Command::SUnsubscribe(_)
+                | Command::Ping(_)
+                | Command::Quit(_)
+                | Command::Reset(_)
+        )
+    }
 }
 
+// checks the names a command was parsed with, then its arity against the command table
 pub(crate) fn validate_command(
     cmd: &RespArray,
     names: &[&'static str],
-    args_len: usize,
 ) -> Result<(), CommandError> {
-    if cmd.len() < names.len() + args_len {
-        return Err(CommandError::InvalidArgs("Invalid arguments".to_string()));
-    }
     validate_command_names(names, cmd)?;
-    Ok(())
+    let name = names.join("|");
+    match table::lookup(&name) {
+        Some(spec) if !spec.check_arity(cmd.len()) => Err(CommandError::WrongArity(name)),
+        _ => Ok(()),
+    }
 }
 
 fn validate_command_names(names: &[&'static str], cmds: &RespArray) -> Result<(), CommandError> {
@@ -114,8 +478,8 @@ fn validate_command_names(names: &[&'static str], cmds: &RespArray) -> Result<()
                 }
             }
             _ => {
-                return Err(CommandError::InvalidArgs(format!(
-                    "Invalid arguments: expected {}, got {:?}",
+                return Err(CommandError::InvalidCmd(format!(
+                    "Invalid command: expected {}, got {:?}",
                     name, arg
                 )))
             }
@@ -132,6 +496,38 @@ pub(crate) fn extract_args(value: RespArray, start: usize) -> Result<Vec<RespFra
         .collect::<Vec<RespFrame>>())
 }
 
+pub(crate) fn extract_strings(args: Vec<RespFrame>) -> Result<Vec<String>, CommandError> {
+    args.into_iter()
+        .map(|arg| match arg {
+            RespFrame::BulkString(arg) => Ok(String::from_utf8(arg.to_vec())?),
+            _ => Err(CommandError::Syntax),
+        })
+        .collect()
+}
+
+// fails with WRONGTYPE unless `key` is missing or holds a value of the `expected` type
+pub(crate) fn ensure_type(
+    backend: &Backend,
+    key: &str,
+    expected: &str,
+) -> Result<(), CommandError> {
+    match backend.key_type(key) {
+        Some(actual) if actual != expected => Err(CommandError::WrongType),
+        _ => Ok(()),
+    }
+}
+
+// [kind, channel, count], the reply to every (un)subscribe of a single channel
+pub(crate) fn subscription_reply(kind: &str, channel: Option<String>, count: usize) -> RespFrame {
+    let channel = channel.map_or_else(|| RespNull.into(), |c| BulkString::from(c).into());
+    RespPush::new(vec![
+        BulkString::from(kind).into(),
+        channel,
+        (count as i64).into(),
+    ])
+    .into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -151,4 +547,84 @@ mod tests {
         assert_eq!(args[2], BulkString::new(b"world").into());
         Ok(())
     }
+
+    fn command_error(data: &[u8]) -> String {
+        let mut buf = bytes::BytesMut::from(data);
+        let frame = RespFrame::decode(&mut buf).unwrap();
+        let err = Command::try_from(frame).unwrap_err();
+        SimpleError::from(err).to_string()
+    }
+
+    #[test]
+    fn test_command_error_replies() {
+        // foo bar baz
+        assert_eq!(
+            command_error(b"*3\r\n$3\r\nfoo\r\n$3\r\nbar\r\n$3\r\nbaz\r\n"),
+            "ERR unknown command 'foo', with args beginning with: 'bar' 'baz' "
+        );
+        // FOO
+        assert_eq!(
+            command_error(b"*1\r\n$3\r\nFOO\r\n"),
+            "ERR unknown command 'FOO', with args beginning with: "
+        );
+        // config what
+        assert_eq!(
+            command_error(b"*2\r\n$6\r\nconfig\r\n$4\r\nwhat\r\n"),
+            "ERR unknown subcommand 'what'. Try CONFIG HELP."
+        );
+        // get
+        assert_eq!(
+            command_error(b"*1\r\n$3\r\nget\r\n"),
+            "ERR wrong number of arguments for 'get' command"
+        );
+        // config set notify-keyspace-events
+        assert_eq!(
+            command_error(b"*3\r\n$6\r\nconfig\r\n$3\r\nset\r\n$22\r\nnotify-keyspace-events\r\n"),
+            "ERR wrong number of arguments for 'config|set' command"
+        );
+        // "fo\r\no"
+        assert_eq!(
+            command_error(b"*1\r\n$4\r\nfo\r\n\r\n"),
+            "ERR unknown command 'fo  ', with args beginning with: "
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wrong_type() -> Result<()> {
+        let backend = Backend::new();
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut session = Session::new(backend.clone(), tx);
+        backend.set("key".into(), BulkString::from("value").into());
+        backend.hset(
+            "hash".into(),
+            vec![("field".into(), BulkString::from("value").into())],
+        )?;
+
+        // hget key field
+        let mut buf =
+            bytes::BytesMut::from(&b"*3\r\n$4\r\nhget\r\n$3\r\nkey\r\n$5\r\nfield\r\n"[..]);
+        let cmd = Command::try_from(RespFrame::decode(&mut buf)?)?;
+        assert_eq!(
+            cmd.execute(&mut session).await,
+            Err(CommandError::WrongType)
+        );
+        assert_eq!(
+            CommandError::WrongType.to_string(),
+            "WRONGTYPE Operation against a key holding the wrong kind of value"
+        );
+
+        // get hash
+        let mut buf = bytes::BytesMut::from(&b"*2\r\n$3\r\nget\r\n$4\r\nhash\r\n"[..]);
+        let cmd = Command::try_from(RespFrame::decode(&mut buf)?)?;
+        assert_eq!(
+            cmd.execute(&mut session).await,
+            Err(CommandError::WrongType)
+        );
+
+        // set overwrites the hash
+        backend.set("hash".into(), BulkString::from("value").into());
+        assert_eq!(backend.key_type("hash"), Some("string"));
+        assert_eq!(backend.hget("hash", "field"), None);
+        Ok(())
+    }
 }
@@ -0,0 +1,96 @@
+use crate::cmd::{extract_args, validate_command, CommandError, ContextExecutor, ExecFuture};
+use crate::network::Session;
+use crate::{BulkString, RespArray, RespFrame, RespProtocol, SimpleString};
+
+#[derive(Debug)]
+pub struct Ping {
+    message: Option<RespFrame>,
+}
+
+impl ContextExecutor for Ping {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        let reply = self.execute_sync(session);
+        Box::pin(std::future::ready(reply.map(|frame| vec![frame])))
+    }
+
+    fn execute_sync(self, session: &mut Session) -> Result<RespFrame, CommandError> {
+        // like redis, a subscribed RESP2 client gets a pong it can tell apart from messages
+        let reply = if session.is_subscribed() && session.protocol() == RespProtocol::Resp2 {
+            let message = self.message.unwrap_or_else(|| BulkString::from("").into());
+            RespArray::new(vec![BulkString::from("pong").into(), message]).into()
+        } else {
+            self.message
+                .unwrap_or_else(|| SimpleString::new("PONG").into())
+        };
+        Ok(reply)
+    }
+}
+
+// ping hello
+// *2\r\n$4\r\nping\r\n$5\r\nhello\r\n
+impl TryFrom<RespArray> for Ping {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["ping"])?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (message, None) => Ok(Ping { message }),
+            _ => Err(CommandError::WrongArity("ping".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Backend, RespDecode};
+    use anyhow::Result;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_ping_command() -> Result<()> {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(Backend::new(), tx);
+
+        let mut cmd = bytes::BytesMut::from(&b"*1\r\n$4\r\nping\r\n"[..]);
+        let ping = Ping::try_from(RespArray::decode(&mut cmd)?)?;
+        assert_eq!(
+            ping.execute(&mut session).await?,
+            vec![SimpleString::new("PONG").into()]
+        );
+
+        let mut cmd = bytes::BytesMut::from(&b"*2\r\n$4\r\nping\r\n$5\r\nhello\r\n"[..]);
+        let ping = Ping::try_from(RespArray::decode(&mut cmd)?)?;
+        assert_eq!(
+            ping.execute(&mut session).await?,
+            vec![BulkString::from("hello").into()]
+        );
+
+        session.subscribe("news".into());
+        let ping = Ping { message: None };
+        assert_eq!(
+            ping.execute(&mut session).await?,
+            vec![RespArray::new(vec![
+                BulkString::from("pong").into(),
+                BulkString::from("").into()
+            ])
+            .into()]
+        );
+        session.set_protocol(RespProtocol::Resp3);
+        let ping = Ping { message: None };
+        assert_eq!(
+            ping.execute(&mut session).await?,
+            vec![SimpleString::new("PONG").into()]
+        );
+
+        let mut cmd = bytes::BytesMut::from(&b"*3\r\n$4\r\nping\r\n$1\r\na\r\n$1\r\nb\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        assert_eq!(
+            Ping::try_from(cmd).unwrap_err(),
+            CommandError::WrongArity("ping".to_string())
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use crate::cmd::{
+    extract_args, extract_strings, subscription_reply, validate_command, CommandError,
+    ContextExecutor, ExecFuture,
+};
+use crate::network::Session;
+use crate::RespArray;
+
+#[derive(Debug)]
+pub struct PSubscribe {
+    patterns: Vec<String>,
+}
+
+impl ContextExecutor for PSubscribe {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        Box::pin(async move {
+            Ok(self
+                .patterns
+                .into_iter()
+                .map(|pattern| {
+                    let count = session.psubscribe(pattern.clone());
+                    subscription_reply("psubscribe", Some(pattern), count)
+                })
+                .collect())
+        })
+    }
+}
+
+// psubscribe events.*
+// *2\r\n$10\r\npsubscribe\r\n$8\r\nevents.*\r\n
+impl TryFrom<RespArray> for PSubscribe {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["psubscribe"])?;
+
+        let patterns = extract_strings(extract_args(value, 1)?)?;
+        Ok(PSubscribe { patterns })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Backend, BulkString, RespDecode, RespPush};
+    use anyhow::Result;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_psubscribe_command() -> Result<()> {
+        let mut cmd = bytes::BytesMut::from(&b"*2\r\n$10\r\npsubscribe\r\n$8\r\nevents.*\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        let psubscribe = PSubscribe::try_from(cmd)?;
+        assert_eq!(psubscribe.patterns, ["events.*"]);
+
+        let backend = Backend::new();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(backend.clone(), tx);
+        session.subscribe("news".into());
+        let ret = psubscribe.execute(&mut session).await?;
+        assert_eq!(
+            ret,
+            vec![subscription_reply("psubscribe", Some("events.*".into()), 2)]
+        );
+
+        assert_eq!(
+            backend
+                .pubsub()
+                .publish("events.login", BulkString::from("alice").into()),
+            1
+        );
+        assert_eq!(
+            rx.try_recv()?,
+            RespPush::new(vec![
+                BulkString::from("pmessage").into(),
+                BulkString::from("events.*").into(),
+                BulkString::from("events.login").into(),
+                BulkString::from("alice").into(),
+            ])
+            .into()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use crate::backend::Backend;
+use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor};
+use crate::{RespArray, RespFrame};
+
+#[derive(Debug)]
+pub struct Publish {
+    channel: String,
+    message: RespFrame,
+}
+
+impl CommandExecutor for Publish {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        let receivers = backend.pubsub().publish(&self.channel, self.message);
+        Ok((receivers as i64).into())
+    }
+}
+
+// publish news hello
+// *3\r\n$7\r\npublish\r\n$4\r\nnews\r\n$5\r\nhello\r\n
+impl TryFrom<RespArray> for Publish {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["publish"])?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(channel)), Some(message)) => Ok(Publish {
+                channel: String::from_utf8(channel.to_vec())?,
+                message,
+            }),
+            _ => Err(CommandError::Syntax),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespDecode};
+    use anyhow::Result;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_publish_command() -> Result<()> {
+        let mut cmd =
+            bytes::BytesMut::from(&b"*3\r\n$7\r\npublish\r\n$4\r\nnews\r\n$5\r\nhello\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        let publish = Publish::try_from(cmd)?;
+        assert_eq!(publish.channel, "news");
+        assert_eq!(publish.message, BulkString::new("hello").into());
+
+        let backend = Backend::new();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        backend.pubsub().subscribe("news".into(), 1, tx.into());
+
+        let ret = publish.execute(&backend)?;
+        assert_eq!(ret, 1.into());
+        assert!(rx.try_recv().is_ok());
+        Ok(())
+    }
+}
@@ -0,0 +1,279 @@
+use crate::backend::Backend;
+use crate::cmd::{extract_args, extract_strings, validate_command, CommandError, CommandExecutor};
+use crate::{BulkString, RespArray, RespFrame};
+
+#[derive(Debug)]
+pub struct PubSubChannels {
+    pattern: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct PubSubNumSub {
+    channels: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct PubSubNumPat;
+
+#[derive(Debug)]
+pub struct PubSubShardChannels {
+    pattern: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct PubSubShardNumSub {
+    channels: Vec<String>,
+}
+
+impl CommandExecutor for PubSubChannels {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        let channels = backend.pubsub().channels(self.pattern.as_deref());
+        Ok(channels_reply(channels))
+    }
+}
+
+impl CommandExecutor for PubSubNumSub {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        Ok(numsub_reply(self.channels, |channel| {
+            backend.pubsub().numsub(channel)
+        }))
+    }
+}
+
+impl CommandExecutor for PubSubNumPat {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        Ok((backend.pubsub().numpat() as i64).into())
+    }
+}
+
+impl CommandExecutor for PubSubShardChannels {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        let channels = backend.pubsub().shard_channels(self.pattern.as_deref());
+        Ok(channels_reply(channels))
+    }
+}
+
+impl CommandExecutor for PubSubShardNumSub {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        Ok(numsub_reply(self.channels, |channel| {
+            backend.pubsub().shard_numsub(channel)
+        }))
+    }
+}
+
+fn channels_reply(channels: Vec<String>) -> RespFrame {
+    RespArray::new(
+        channels
+            .into_iter()
+            .map(|channel| BulkString::from(channel).into())
+            .collect(),
+    )
+    .into()
+}
+
+// [channel-1, count-1, ..., channel-n, count-n]
+fn numsub_reply(channels: Vec<String>, numsub: impl Fn(&str) -> usize) -> RespFrame {
+    let mut ret: Vec<RespFrame> = Vec::with_capacity(channels.len() * 2);
+    for channel in channels {
+        let count = numsub(&channel);
+        ret.push(BulkString::from(channel).into());
+        ret.push((count as i64).into());
+    }
+    RespArray::new(ret).into()
+}
+
+// pubsub channels events.*
+// *3\r\n$6\r\npubsub\r\n$8\r\nchannels\r\n$8\r\nevents.*\r\n
+impl TryFrom<RespArray> for PubSubChannels {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["pubsub", "channels"])?;
+
+        let pattern = extract_pattern(value, "pubsub|channels")?;
+        Ok(PubSubChannels { pattern })
+    }
+}
+
+// pubsub numsub news sports
+// *4\r\n$6\r\npubsub\r\n$6\r\nnumsub\r\n$4\r\nnews\r\n$6\r\nsports\r\n
+impl TryFrom<RespArray> for PubSubNumSub {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["pubsub", "numsub"])?;
+
+        let channels = extract_strings(extract_args(value, 2)?)?;
+        Ok(PubSubNumSub { channels })
+    }
+}
+
+// pubsub numpat
+// *2\r\n$6\r\npubsub\r\n$6\r\nnumpat\r\n
+impl TryFrom<RespArray> for PubSubNumPat {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["pubsub", "numpat"])?;
+
+        Ok(PubSubNumPat)
+    }
+}
+
+// pubsub shardchannels {user}.*
+// *3\r\n$6\r\npubsub\r\n$13\r\nshardchannels\r\n$8\r\n{user}.*\r\n
+impl TryFrom<RespArray> for PubSubShardChannels {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["pubsub", "shardchannels"])?;
+
+        let pattern = extract_pattern(value, "pubsub|shardchannels")?;
+        Ok(PubSubShardChannels { pattern })
+    }
+}
+
+// pubsub shardnumsub {user}.orders
+// *3\r\n$6\r\npubsub\r\n$11\r\nshardnumsub\r\n$13\r\n{user}.orders\r\n
+impl TryFrom<RespArray> for PubSubShardNumSub {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["pubsub", "shardnumsub"])?;
+
+        let channels = extract_strings(extract_args(value, 2)?)?;
+        Ok(PubSubShardNumSub { channels })
+    }
+}
+
+// the optional pattern of CHANNELS and SHARDCHANNELS
+fn extract_pattern(value: RespArray, name: &str) -> Result<Option<String>, CommandError> {
+    let mut args = extract_strings(extract_args(value, 2)?)?.into_iter();
+    let pattern = args.next();
+    if args.next().is_some() {
+        return Err(CommandError::WrongArity(name.to_string()));
+    }
+    Ok(pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespDecode;
+    use anyhow::Result;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_pubsub_channels_command() -> Result<()> {
+        let mut cmd = bytes::BytesMut::from(
+            &b"*3\r\n$6\r\npubsub\r\n$8\r\nchannels\r\n$8\r\nevents.*\r\n"[..],
+        );
+        let cmd = RespArray::decode(&mut cmd)?;
+        let channels = PubSubChannels::try_from(cmd)?;
+        assert_eq!(channels.pattern, Some("events.*".into()));
+
+        let backend = Backend::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        backend
+            .pubsub()
+            .subscribe("events.login".into(), 1, tx.clone().into());
+        backend.pubsub().subscribe("news".into(), 1, tx.into());
+
+        let ret = channels.execute(&backend)?;
+        assert_eq!(
+            ret,
+            RespArray::new(vec![BulkString::from("events.login").into()]).into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_pubsub_numsub_command() -> Result<()> {
+        let mut cmd = bytes::BytesMut::from(
+            &b"*4\r\n$6\r\npubsub\r\n$6\r\nnumsub\r\n$4\r\nnews\r\n$6\r\nsports\r\n"[..],
+        );
+        let cmd = RespArray::decode(&mut cmd)?;
+        let numsub = PubSubNumSub::try_from(cmd)?;
+        assert_eq!(numsub.channels, ["news", "sports"]);
+
+        let backend = Backend::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        backend
+            .pubsub()
+            .subscribe("news".into(), 1, tx.clone().into());
+        backend.pubsub().subscribe("news".into(), 2, tx.into());
+
+        let ret = numsub.execute(&backend)?;
+        assert_eq!(
+            ret,
+            RespArray::new(vec![
+                BulkString::from("news").into(),
+                2.into(),
+                BulkString::from("sports").into(),
+                0.into(),
+            ])
+            .into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_pubsub_numpat_command() -> Result<()> {
+        let mut cmd = bytes::BytesMut::from(&b"*2\r\n$6\r\npubsub\r\n$6\r\nnumpat\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        let numpat = PubSubNumPat::try_from(cmd)?;
+
+        let backend = Backend::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        backend
+            .pubsub()
+            .psubscribe("events.*".into(), 1, tx.clone().into());
+        backend
+            .pubsub()
+            .psubscribe("events.*".into(), 2, tx.clone().into());
+        backend.pubsub().psubscribe("news.*".into(), 2, tx.into());
+
+        let ret = numpat.execute(&backend)?;
+        assert_eq!(ret, 2.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pubsub_shard_commands() -> Result<()> {
+        let mut cmd = bytes::BytesMut::from(
+            &b"*3\r\n$6\r\npubsub\r\n$13\r\nshardchannels\r\n$8\r\n{user}.*\r\n"[..],
+        );
+        let cmd = RespArray::decode(&mut cmd)?;
+        let shard_channels = PubSubShardChannels::try_from(cmd)?;
+        assert_eq!(shard_channels.pattern, Some("{user}.*".into()));
+
+        let mut cmd = bytes::BytesMut::from(
+            &b"*3\r\n$6\r\npubsub\r\n$11\r\nshardnumsub\r\n$13\r\n{user}.orders\r\n"[..],
+        );
+        let cmd = RespArray::decode(&mut cmd)?;
+        let shard_numsub = PubSubShardNumSub::try_from(cmd)?;
+        assert_eq!(shard_numsub.channels, ["{user}.orders"]);
+
+        let backend = Backend::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        backend
+            .pubsub()
+            .ssubscribe("{user}.orders".into(), 1, tx.clone().into());
+        backend
+            .pubsub()
+            .subscribe("{user}.carts".into(), 1, tx.into());
+
+        let ret = shard_channels.execute(&backend)?;
+        assert_eq!(
+            ret,
+            RespArray::new(vec![BulkString::from("{user}.orders").into()]).into()
+        );
+
+        let ret = shard_numsub.execute(&backend)?;
+        assert_eq!(
+            ret,
+            RespArray::new(vec![BulkString::from("{user}.orders").into(), 1.into()]).into()
+        );
+        Ok(())
+    }
+}
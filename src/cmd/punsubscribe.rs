@@ -0,0 +1,93 @@
+use crate::cmd::{
+    extract_args, extract_strings, subscription_reply, validate_command, CommandError,
+    ContextExecutor, ExecFuture,
+};
+use crate::network::Session;
+use crate::RespArray;
+
+#[derive(Debug)]
+pub struct PUnsubscribe {
+    patterns: Vec<String>,
+}
+
+impl ContextExecutor for PUnsubscribe {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        Box::pin(async move {
+            // without arguments, unsubscribe from every pattern the connection is subscribed to
+            let patterns = if self.patterns.is_empty() {
+                session.patterns()
+            } else {
+                self.patterns
+            };
+            if patterns.is_empty() {
+                return Ok(vec![subscription_reply(
+                    "punsubscribe",
+                    None,
+                    session.subscriptions(),
+                )]);
+            }
+
+            Ok(patterns
+                .into_iter()
+                .map(|pattern| {
+                    let count = session.punsubscribe(&pattern);
+                    subscription_reply("punsubscribe", Some(pattern), count)
+                })
+                .collect())
+        })
+    }
+}
+
+// punsubscribe events.*
+// *2\r\n$12\r\npunsubscribe\r\n$8\r\nevents.*\r\n
+impl TryFrom<RespArray> for PUnsubscribe {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["punsubscribe"])?;
+
+        let patterns = extract_strings(extract_args(value, 1)?)?;
+        Ok(PUnsubscribe { patterns })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Backend, RespDecode};
+    use anyhow::Result;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_punsubscribe_command() -> Result<()> {
+        let mut cmd =
+            bytes::BytesMut::from(&b"*2\r\n$12\r\npunsubscribe\r\n$8\r\nevents.*\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        let punsubscribe = PUnsubscribe::try_from(cmd)?;
+        assert_eq!(punsubscribe.patterns, ["events.*"]);
+
+        let backend = Backend::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(backend.clone(), tx);
+        session.subscribe("news".into());
+        session.psubscribe("events.*".into());
+
+        let ret = punsubscribe.execute(&mut session).await?;
+        assert_eq!(
+            ret,
+            vec![subscription_reply(
+                "punsubscribe",
+                Some("events.*".into()),
+                1
+            )]
+        );
+        assert_eq!(backend.pubsub().numpat(), 0);
+
+        // channel subscriptions are left untouched
+        let ret = PUnsubscribe { patterns: vec![] }
+            .execute(&mut session)
+            .await?;
+        assert_eq!(ret, vec![subscription_reply("punsubscribe", None, 1)]);
+        Ok(())
+    }
+}
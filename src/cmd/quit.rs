@@ -0,0 +1,26 @@
+use crate::cmd::{validate_command, CommandError, ContextExecutor, ExecFuture, RESP_OK};
+use crate::network::Session;
+use crate::RespArray;
+
+#[derive(Debug)]
+pub struct Quit;
+
+impl ContextExecutor for Quit {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        // the connection closes once the reply is written out
+        session.client().kill();
+        Box::pin(std::future::ready(Ok(vec![RESP_OK.clone()])))
+    }
+}
+
+// quit
+// *1\r\n$4\r\nquit\r\n
+impl TryFrom<RespArray> for Quit {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["quit"])?;
+
+        Ok(Quit)
+    }
+}
@@ -0,0 +1,65 @@
+use crate::cmd::{validate_command, CommandError, ContextExecutor, ExecFuture};
+use crate::network::Session;
+use crate::{RespArray, SimpleString};
+
+#[derive(Debug)]
+pub struct Reset;
+
+impl ContextExecutor for Reset {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        session.reset();
+        Box::pin(std::future::ready(Ok(vec![
+            SimpleString::new("RESET").into()
+        ])))
+    }
+}
+
+// reset
+// *1\r\n$5\r\nreset\r\n
+impl TryFrom<RespArray> for Reset {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["reset"])?;
+
+        Ok(Reset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::{Backend, RespDecode, RespProtocol};
+    use anyhow::Result;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_reset_command() -> Result<()> {
+        let backend = Backend::with_config(ServerConfig {
+            requirepass: "secret".into(),
+            ..Default::default()
+        });
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(backend.clone(), tx);
+        assert!(session.authenticate("default", "secret"));
+        session.subscribe("news".into());
+        session.psubscribe("news.*".into());
+        session.set_name(Some("worker".into()));
+        session.set_protocol(RespProtocol::Resp3);
+
+        let mut cmd = bytes::BytesMut::from(&b"*1\r\n$5\r\nreset\r\n"[..]);
+        let reset = Reset::try_from(RespArray::decode(&mut cmd)?)?;
+        assert_eq!(
+            reset.execute(&mut session).await?,
+            vec![SimpleString::new("RESET").into()]
+        );
+        assert!(!session.is_subscribed());
+        assert_eq!(backend.pubsub().numsub("news"), 0);
+        assert_eq!(backend.pubsub().numpat(), 0);
+        assert_eq!(session.name(), None);
+        assert_eq!(session.protocol(), RespProtocol::Resp2);
+        assert!(session.auth_required());
+        Ok(())
+    }
+}
@@ -21,15 +21,16 @@ impl TryFrom<RespArray> for Set {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["set"], 2)?;
+        validate_command(&value, &["set"])?;
 
         let mut args = extract_args(value, 1)?.into_iter();
-        match (args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(value)) => {
+        match (args.next(), args.next(), args.next()) {
+            // none of the EX, NX, GET... options are supported
+            (Some(RespFrame::BulkString(key)), Some(value), None) => {
                 let key = String::from_utf8(key.to_vec())?;
                 Ok(Set { key, value })
             }
-            _ => Err(CommandError::InvalidArgs("Invalid arguments".to_string())),
+            _ => Err(CommandError::Syntax),
         }
     }
 }
@@ -87,7 +88,7 @@ mod tests {
         assert_eq!(ret, BulkString::new(b"world").into());
 
         // get not exist key
-        let mut cmd = bytes::BytesMut::from(&b"*2\r\n$3\r\nget\r\n$5\r\nnot_exist_key\r\n"[..]);
+        let mut cmd = bytes::BytesMut::from(&b"*2\r\n$3\r\nget\r\n$13\r\nnot_exist_key\r\n"[..]);
         let cmd = RespArray::decode(&mut cmd)?;
         let get = Get::try_from(cmd)?;
         let ret = get.execute(&backend)?;
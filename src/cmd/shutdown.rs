@@ -0,0 +1,120 @@
+use crate::cmd::{
+    extract_args, extract_strings, validate_command, CommandError, ContextExecutor, ExecFuture,
+};
+use crate::network::Session;
+use crate::{RespArray, RespFrame};
+use tracing::warn;
+
+#[derive(Debug)]
+pub struct Shutdown {
+    // SAVE or NOSAVE, neither leaves it to the save points of which there are none
+    save: Option<bool>,
+    force: bool,
+    abort: bool,
+}
+
+impl ContextExecutor for Shutdown {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        Box::pin(std::future::ready(self.shutdown(session)))
+    }
+}
+
+impl Shutdown {
+    // the client that asked is closed along with the others and gets no reply
+    fn shutdown(self, session: &Session) -> Result<Vec<RespFrame>, CommandError> {
+        // with no replicas to wait for the server goes down right away, there is never a
+        // shutdown in progress to abort
+        if self.abort {
+            return Err(CommandError::InvalidCmd(
+                "No shutdown in progress.".to_string(),
+            ));
+        }
+        if self.save == Some(true) {
+            warn!("Error trying to save the DB, the dataset is never persisted");
+            if !self.force {
+                return Err(CommandError::InvalidCmd(
+                    "Errors trying to SHUTDOWN. Check logs.".to_string(),
+                ));
+            }
+        }
+        session.backend().shutdown().request();
+        Ok(vec![])
+    }
+}
+
+// shutdown nosave now
+// *3\r\n$8\r\nshutdown\r\n$6\r\nnosave\r\n$3\r\nnow\r\n
+impl TryFrom<RespArray> for Shutdown {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["shutdown"])?;
+
+        let mut shutdown = Shutdown {
+            save: None,
+            force: false,
+            abort: false,
+        };
+        let args = extract_strings(extract_args(value, 1)?)?;
+        for arg in &args {
+            match arg.to_ascii_lowercase().as_str() {
+                "nosave" if shutdown.save != Some(true) => shutdown.save = Some(false),
+                "save" if shutdown.save != Some(false) => shutdown.save = Some(true),
+                // NOW skips waiting for replicas to catch up, there are none
+                "now" => {}
+                "force" => shutdown.force = true,
+                "abort" => shutdown.abort = true,
+                _ => return Err(CommandError::Syntax),
+            }
+        }
+        if shutdown.abort && args.len() > 1 {
+            return Err(CommandError::Syntax);
+        }
+        Ok(shutdown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Backend, RespDecode};
+    use anyhow::Result;
+    use tokio::sync::mpsc;
+
+    fn decode(cmd: &[u8]) -> Result<Shutdown, CommandError> {
+        let mut cmd = bytes::BytesMut::from(cmd);
+        Shutdown::try_from(RespArray::decode(&mut cmd).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_command() -> Result<()> {
+        let backend = Backend::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(backend.clone(), tx);
+
+        // shutdown save
+        let shutdown = decode(b"*2\r\n$8\r\nshutdown\r\n$4\r\nsave\r\n")?;
+        assert_eq!(
+            shutdown.execute(&mut session).await,
+            Err(CommandError::InvalidCmd(
+                "Errors trying to SHUTDOWN. Check logs.".into()
+            ))
+        );
+        // shutdown abort
+        let shutdown = decode(b"*2\r\n$8\r\nshutdown\r\n$5\r\nabort\r\n")?;
+        assert!(shutdown.execute(&mut session).await.is_err());
+        assert!(!backend.shutdown().is_requested());
+
+        // shutdown save nosave
+        let shutdown = decode(b"*3\r\n$8\r\nshutdown\r\n$4\r\nsave\r\n$6\r\nnosave\r\n");
+        assert_eq!(shutdown.unwrap_err(), CommandError::Syntax);
+        // shutdown abort now
+        let shutdown = decode(b"*3\r\n$8\r\nshutdown\r\n$5\r\nabort\r\n$3\r\nnow\r\n");
+        assert_eq!(shutdown.unwrap_err(), CommandError::Syntax);
+
+        let shutdown = decode(b"*3\r\n$8\r\nshutdown\r\n$6\r\nnosave\r\n$3\r\nnow\r\n")?;
+        assert_eq!(shutdown.execute(&mut session).await, Ok(vec![]));
+        assert!(backend.shutdown().is_requested());
+        Ok(())
+    }
+}
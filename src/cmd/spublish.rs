@@ -0,0 +1,70 @@
+use crate::backend::Backend;
+use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor};
+use crate::{RespArray, RespFrame};
+
+#[derive(Debug)]
+pub struct SPublish {
+    channel: String,
+    message: RespFrame,
+}
+
+impl CommandExecutor for SPublish {
+    fn execute(self, backend: &Backend) -> Result<RespFrame, CommandError> {
+        let receivers = backend.pubsub().spublish(&self.channel, self.message);
+        Ok((receivers as i64).into())
+    }
+}
+
+// spublish {user}.orders hello
+// *3\r\n$8\r\nspublish\r\n$13\r\n{user}.orders\r\n$5\r\nhello\r\n
+impl TryFrom<RespArray> for SPublish {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["spublish"])?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(channel)), Some(message)) => Ok(SPublish {
+                channel: String::from_utf8(channel.to_vec())?,
+                message,
+            }),
+            _ => Err(CommandError::Syntax),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespDecode};
+    use anyhow::Result;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_spublish_command() -> Result<()> {
+        let mut cmd = bytes::BytesMut::from(
+            &b"*3\r\n$8\r\nspublish\r\n$13\r\n{user}.orders\r\n$5\r\nhello\r\n"[..],
+        );
+        let cmd = RespArray::decode(&mut cmd)?;
+        let spublish = SPublish::try_from(cmd)?;
+        assert_eq!(spublish.channel, "{user}.orders");
+        assert_eq!(spublish.message, BulkString::new("hello").into());
+
+        let backend = Backend::new();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        backend
+            .pubsub()
+            .ssubscribe("{user}.orders".into(), 1, tx.clone().into());
+        backend
+            .pubsub()
+            .subscribe("{user}.orders".into(), 2, tx.into());
+
+        // regular subscribers of a channel with the same name are not shard subscribers
+        let ret = spublish.execute(&backend)?;
+        assert_eq!(ret, 1.into());
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use crate::cmd::{
+    extract_args, extract_strings, subscription_reply, validate_command, CommandError,
+    ContextExecutor, ExecFuture,
+};
+use crate::network::Session;
+use crate::RespArray;
+
+#[derive(Debug)]
+pub struct SSubscribe {
+    channels: Vec<String>,
+}
+
+impl ContextExecutor for SSubscribe {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        Box::pin(async move {
+            Ok(self
+                .channels
+                .into_iter()
+                .map(|channel| {
+                    let count = session.ssubscribe(channel.clone());
+                    subscription_reply("ssubscribe", Some(channel), count)
+                })
+                .collect())
+        })
+    }
+}
+
+// ssubscribe {user}.orders
+// *2\r\n$10\r\nssubscribe\r\n$13\r\n{user}.orders\r\n
+impl TryFrom<RespArray> for SSubscribe {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["ssubscribe"])?;
+
+        let channels = extract_strings(extract_args(value, 1)?)?;
+        Ok(SSubscribe { channels })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Backend, BulkString, RespDecode, RespPush};
+    use anyhow::Result;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_ssubscribe_command() -> Result<()> {
+        let mut cmd =
+            bytes::BytesMut::from(&b"*2\r\n$10\r\nssubscribe\r\n$13\r\n{user}.orders\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        let ssubscribe = SSubscribe::try_from(cmd)?;
+        assert_eq!(ssubscribe.channels, ["{user}.orders"]);
+
+        let backend = Backend::new();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(backend.clone(), tx);
+        session.subscribe("news".into());
+
+        // shard subscriptions are counted separately from channels and patterns
+        let ret = ssubscribe.execute(&mut session).await?;
+        assert_eq!(
+            ret,
+            vec![subscription_reply(
+                "ssubscribe",
+                Some("{user}.orders".into()),
+                1
+            )]
+        );
+
+        assert_eq!(
+            backend
+                .pubsub()
+                .spublish("{user}.orders", BulkString::from("hi").into()),
+            1
+        );
+        assert_eq!(
+            rx.try_recv()?,
+            RespPush::new(vec![
+                BulkString::from("smessage").into(),
+                BulkString::from("{user}.orders").into(),
+                BulkString::from("hi").into(),
+            ])
+            .into()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use crate::cmd::{
+    extract_args, extract_strings, subscription_reply, validate_command, CommandError,
+    ContextExecutor, ExecFuture,
+};
+use crate::network::Session;
+use crate::RespArray;
+
+#[derive(Debug)]
+pub struct Subscribe {
+    channels: Vec<String>,
+}
+
+impl ContextExecutor for Subscribe {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        Box::pin(async move {
+            Ok(self
+                .channels
+                .into_iter()
+                .map(|channel| {
+                    let count = session.subscribe(channel.clone());
+                    subscription_reply("subscribe", Some(channel), count)
+                })
+                .collect())
+        })
+    }
+}
+
+// subscribe news sports
+// *3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n$6\r\nsports\r\n
+impl TryFrom<RespArray> for Subscribe {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["subscribe"])?;
+
+        let channels = extract_strings(extract_args(value, 1)?)?;
+        Ok(Subscribe { channels })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Backend, BulkString, RespDecode, RespPush};
+    use anyhow::Result;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_subscribe_command() -> Result<()> {
+        let mut cmd =
+            bytes::BytesMut::from(&b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n$6\r\nsports\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        let subscribe = Subscribe::try_from(cmd)?;
+        assert_eq!(subscribe.channels, ["news", "sports"]);
+
+        let backend = Backend::new();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(backend.clone(), tx);
+        let ret = subscribe.execute(&mut session).await?;
+        assert_eq!(
+            ret,
+            vec![
+                subscription_reply("subscribe", Some("news".into()), 1),
+                subscription_reply("subscribe", Some("sports".into()), 2),
+            ]
+        );
+
+        assert_eq!(
+            backend
+                .pubsub()
+                .publish("news", BulkString::from("hi").into()),
+            1
+        );
+        assert_eq!(
+            rx.try_recv()?,
+            RespPush::new(vec![
+                BulkString::from("message").into(),
+                BulkString::from("news").into(),
+                BulkString::from("hi").into(),
+            ])
+            .into()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_command_args_not_enough() -> Result<()> {
+        let mut cmd = bytes::BytesMut::from(&b"*1\r\n$9\r\nsubscribe\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        assert!(Subscribe::try_from(cmd).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use crate::cmd::{
+    extract_args, extract_strings, subscription_reply, validate_command, CommandError,
+    ContextExecutor, ExecFuture,
+};
+use crate::network::Session;
+use crate::RespArray;
+
+#[derive(Debug)]
+pub struct SUnsubscribe {
+    channels: Vec<String>,
+}
+
+impl ContextExecutor for SUnsubscribe {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        Box::pin(async move {
+            // without arguments, unsubscribe from every shard channel the connection is subscribed to
+            let channels = if self.channels.is_empty() {
+                session.shard_channels()
+            } else {
+                self.channels
+            };
+            if channels.is_empty() {
+                return Ok(vec![subscription_reply(
+                    "sunsubscribe",
+                    None,
+                    session.shard_subscriptions(),
+                )]);
+            }
+
+            Ok(channels
+                .into_iter()
+                .map(|channel| {
+                    let count = session.sunsubscribe(&channel);
+                    subscription_reply("sunsubscribe", Some(channel), count)
+                })
+                .collect())
+        })
+    }
+}
+
+// sunsubscribe {user}.orders
+// *2\r\n$12\r\nsunsubscribe\r\n$13\r\n{user}.orders\r\n
+impl TryFrom<RespArray> for SUnsubscribe {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["sunsubscribe"])?;
+
+        let channels = extract_strings(extract_args(value, 1)?)?;
+        Ok(SUnsubscribe { channels })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Backend, RespDecode};
+    use anyhow::Result;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_sunsubscribe_command() -> Result<()> {
+        let mut cmd =
+            bytes::BytesMut::from(&b"*2\r\n$12\r\nsunsubscribe\r\n$13\r\n{user}.orders\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        let sunsubscribe = SUnsubscribe::try_from(cmd)?;
+        assert_eq!(sunsubscribe.channels, ["{user}.orders"]);
+
+        let backend = Backend::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(backend.clone(), tx);
+        session.ssubscribe("{user}.orders".into());
+        session.ssubscribe("news".into());
+
+        let ret = sunsubscribe.execute(&mut session).await?;
+        assert_eq!(
+            ret,
+            vec![subscription_reply(
+                "sunsubscribe",
+                Some("{user}.orders".into()),
+                1
+            )]
+        );
+        assert_eq!(backend.pubsub().shard_numsub("{user}.orders"), 0);
+
+        let ret = SUnsubscribe { channels: vec![] }
+            .execute(&mut session)
+            .await?;
+        assert_eq!(
+            ret,
+            vec![subscription_reply("sunsubscribe", Some("news".into()), 0)]
+        );
+        assert!(!session.is_subscribed());
+        Ok(())
+    }
+}
@@ -0,0 +1,672 @@
+use crate::cmd::CommandError;
+use crate::{RespArray, RespFrame};
+
+/// Static description of a command, the source of arity checks, dispatch and the COMMAND
+/// replies. Arity follows redis: a positive arity is exact, a negative one is a minimum, and
+/// both count the command name itself.
+#[derive(Debug)]
+pub struct CommandSpec {
+    /// The full name, `container|subcommand` for subcommands.
+    pub name: &'static str,
+    pub arity: i64,
+    pub flags: &'static [&'static str],
+    pub acl_categories: &'static [&'static str],
+    /// Key positions as `first`, `last` and `step`, 0 when the command takes no keys and a
+    /// negative `last` counting from the end.
+    pub first_key: i64,
+    pub last_key: i64,
+    pub key_step: i64,
+    pub key_flags: &'static [&'static str],
+    pub group: &'static str,
+    pub since: &'static str,
+    pub summary: &'static str,
+    pub complexity: &'static str,
+    pub subcommands: &'static [CommandSpec],
+}
+
+impl CommandSpec {
+    const fn new(name: &'static str, arity: i64, group: &'static str, since: &'static str) -> Self {
+        Self {
+            name,
+            arity,
+            flags: &[],
+            acl_categories: &[],
+            first_key: 0,
+            last_key: 0,
+            key_step: 0,
+            key_flags: &[],
+            group,
+            since,
+            summary: "",
+            complexity: "",
+            subcommands: &[],
+        }
+    }
+
+    const fn flags(self, flags: &'static [&'static str], acl: &'static [&'static str]) -> Self {
+        Self {
+            flags,
+            acl_categories: acl,
+            ..self
+        }
+    }
+
+    const fn keys(self, first: i64, last: i64, step: i64, flags: &'static [&'static str]) -> Self {
+        Self {
+            first_key: first,
+            last_key: last,
+            key_step: step,
+            key_flags: flags,
+            ..self
+        }
+    }
+
+    const fn docs(self, summary: &'static str, complexity: &'static str) -> Self {
+        Self {
+            summary,
+            complexity,
+            ..self
+        }
+    }
+
+    const fn subcommands(self, subcommands: &'static [CommandSpec]) -> Self {
+        Self {
+            subcommands,
+            ..self
+        }
+    }
+
+    /// The name of a subcommand without its container, `get` for `config|get`.
+    pub fn short_name(&self) -> &'static str {
+        self.name.rsplit('|').next().unwrap_or(self.name)
+    }
+
+    pub fn check_arity(&self, argc: usize) -> bool {
+        let argc = argc as i64;
+        if self.arity >= 0 {
+            argc == self.arity
+        } else {
+            argc >= -self.arity
+        }
+    }
+
+    /// The positions of the keys in a call with `argc` arguments, the name included.
+    pub fn key_positions(&self, argc: usize) -> Vec<usize> {
+        if self.first_key <= 0 {
+            return vec![];
+        }
+        let last = if self.last_key < 0 {
+            argc as i64 + self.last_key
+        } else {
+            self.last_key.min(argc as i64 - 1)
+        };
+        (self.first_key..=last)
+            .step_by(self.key_step.max(1) as usize)
+            .map(|i| i as usize)
+            .collect()
+    }
+}
+
+const PUBSUB_FLAGS: &[&str] = &["pubsub", "loading", "stale"];
+const SUBSCRIBE_FLAGS: &[&str] = &["pubsub", "noscript", "loading", "stale"];
+const ADMIN_FLAGS: &[&str] = &["admin", "noscript", "loading", "stale"];
+const ADMIN_ACL: &[&str] = &["@admin", "@slow", "@dangerous"];
+const CONNECTION_FLAGS: &[&str] = &["noscript", "loading", "stale"];
+const CONNECTION_ADMIN_ACL: &[&str] = &["@admin", "@slow", "@dangerous", "@connection"];
+
+const ACL_SUBCOMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("acl|cat", -2, "server", "6.0.0")
+        .flags(&["noscript", "loading", "stale"], &["@slow"])
+        .docs(
+            "Lists the ACL categories, or the commands inside a category.",
+            "O(1) since the categories and commands are a fixed set.",
+        ),
+    CommandSpec::new("acl|deluser", -3, "server", "6.0.0")
+        .flags(ADMIN_FLAGS, ADMIN_ACL)
+        .docs(
+            "Deletes ACL users, and terminates their connections.",
+            "O(1) amortized time considering the typical user.",
+        ),
+    CommandSpec::new("acl|getuser", 3, "server", "6.0.0")
+        .flags(ADMIN_FLAGS, ADMIN_ACL)
+        .docs(
+            "Lists the ACL rules of a user.",
+            "O(N). Where N is the number of password, command and pattern rules that the user has.",
+        ),
+    CommandSpec::new("acl|list", 2, "server", "6.0.0")
+        .flags(ADMIN_FLAGS, ADMIN_ACL)
+        .docs(
+            "Dumps the effective rules in ACL file format.",
+            "O(N). Where N is the number of configured users.",
+        ),
+    CommandSpec::new("acl|load", 2, "server", "6.0.0")
+        .flags(ADMIN_FLAGS, ADMIN_ACL)
+        .docs(
+            "Reloads the rules from the configured ACL file.",
+            "O(N). Where N is the number of configured users.",
+        ),
+    CommandSpec::new("acl|log", -2, "server", "6.0.0")
+        .flags(ADMIN_FLAGS, ADMIN_ACL)
+        .docs(
+            "Lists recent security events generated due to ACL rules.",
+            "O(N) with N being the number of entries shown.",
+        ),
+    CommandSpec::new("acl|save", 2, "server", "6.0.0")
+        .flags(ADMIN_FLAGS, ADMIN_ACL)
+        .docs(
+            "Saves the effective ACL rules in the configured ACL file.",
+            "O(N). Where N is the number of configured users.",
+        ),
+    CommandSpec::new("acl|setuser", -3, "server", "6.0.0")
+        .flags(ADMIN_FLAGS, ADMIN_ACL)
+        .docs(
+            "Creates and modifies an ACL user and its rules.",
+            "O(N). Where N is the number of rules provided.",
+        ),
+    CommandSpec::new("acl|users", 2, "server", "6.0.0")
+        .flags(ADMIN_FLAGS, ADMIN_ACL)
+        .docs(
+            "Lists all ACL users.",
+            "O(N). Where N is the number of configured users.",
+        ),
+    CommandSpec::new("acl|whoami", 2, "server", "6.0.0")
+        .flags(&["noscript", "loading", "stale"], &["@slow"])
+        .docs(
+            "Returns the authenticated username of the current connection.",
+            "O(1)",
+        ),
+];
+
+const CLIENT_SUBCOMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("client|getname", 2, "connection", "2.6.9")
+        .flags(CONNECTION_FLAGS, &["@slow", "@connection"])
+        .docs("Returns the name of the connection.", "O(1)"),
+    CommandSpec::new("client|id", 2, "connection", "5.0.0")
+        .flags(CONNECTION_FLAGS, &["@slow", "@connection"])
+        .docs("Returns the unique client ID of the connection.", "O(1)"),
+    CommandSpec::new("client|info", 2, "connection", "6.2.0")
+        .flags(CONNECTION_FLAGS, &["@slow", "@connection"])
+        .docs("Returns information about the connection.", "O(1)"),
+    CommandSpec::new("client|kill", -3, "connection", "2.4.0")
+        .flags(ADMIN_FLAGS, CONNECTION_ADMIN_ACL)
+        .docs(
+            "Terminates open connections.",
+            "O(N) where N is the number of client connections",
+        ),
+    CommandSpec::new("client|list", -2, "connection", "2.4.0")
+        .flags(ADMIN_FLAGS, CONNECTION_ADMIN_ACL)
+        .docs(
+            "Lists open connections.",
+            "O(N) where N is the number of client connections",
+        ),
+    CommandSpec::new("client|no-evict", 3, "connection", "7.0.0")
+        .flags(ADMIN_FLAGS, CONNECTION_ADMIN_ACL)
+        .docs("Sets the client eviction mode of the connection.", "O(1)"),
+    CommandSpec::new("client|pause", -3, "connection", "3.0.0")
+        .flags(ADMIN_FLAGS, CONNECTION_ADMIN_ACL)
+        .docs("Suspends commands processing.", "O(1)"),
+    CommandSpec::new("client|reply", 3, "connection", "3.2.0")
+        .flags(CONNECTION_FLAGS, &["@slow", "@connection"])
+        .docs("Instructs the server whether to reply to commands.", "O(1)"),
+    CommandSpec::new("client|setname", 3, "connection", "2.6.9")
+        .flags(CONNECTION_FLAGS, &["@slow", "@connection"])
+        .docs("Sets the connection name.", "O(1)"),
+    CommandSpec::new("client|unpause", 2, "connection", "6.2.0")
+        .flags(ADMIN_FLAGS, CONNECTION_ADMIN_ACL)
+        .docs(
+            "Resumes processing commands from paused clients.",
+            "O(N) Where N is the number of paused clients",
+        ),
+];
+
+const CONFIG_SUBCOMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("config|get", -3, "server", "2.0.0")
+        .flags(ADMIN_FLAGS, ADMIN_ACL)
+        .docs(
+            "Returns the effective values of configuration parameters.",
+            "O(N) when N is the number of configuration parameters provided",
+        ),
+    CommandSpec::new("config|resetstat", 2, "server", "2.0.0")
+        .flags(ADMIN_FLAGS, ADMIN_ACL)
+        .docs("Resets the server's statistics.", "O(1)"),
+    CommandSpec::new("config|rewrite", 2, "server", "2.8.0")
+        .flags(ADMIN_FLAGS, ADMIN_ACL)
+        .docs("Persists the effective configuration to file.", "O(1)"),
+    CommandSpec::new("config|set", -4, "server", "2.0.0")
+        .flags(ADMIN_FLAGS, ADMIN_ACL)
+        .docs(
+            "Sets configuration parameters in-flight.",
+            "O(N) when N is the number of configuration parameters provided",
+        ),
+];
+
+const FUNCTION_SUBCOMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("function|delete", 3, "scripting", "7.0.0")
+        .flags(&["noscript", "write"], &["@write", "@slow", "@scripting"])
+        .docs("Deletes a library and its functions.", "O(1)"),
+    CommandSpec::new("function|dump", 2, "scripting", "7.0.0")
+        .flags(&["noscript"], &["@slow", "@scripting"])
+        .docs(
+            "Dumps all libraries into a serialized binary payload.",
+            "O(N) where N is the number of functions",
+        ),
+    CommandSpec::new("function|flush", -2, "scripting", "7.0.0")
+        .flags(&["noscript", "write"], &["@write", "@slow", "@scripting"])
+        .docs(
+            "Deletes all libraries and functions.",
+            "O(N) where N is the number of functions deleted",
+        ),
+    CommandSpec::new("function|kill", 2, "scripting", "7.0.0")
+        .flags(&["noscript", "allow_busy"], &["@slow", "@scripting"])
+        .docs("Terminates a function during execution.", "O(1)"),
+    CommandSpec::new("function|list", -2, "scripting", "7.0.0")
+        .flags(&["noscript"], &["@slow", "@scripting"])
+        .docs(
+            "Returns information about all libraries.",
+            "O(N) where N is the number of functions",
+        ),
+    CommandSpec::new("function|load", -3, "scripting", "7.0.0")
+        .flags(
+            &["noscript", "write", "denyoom"],
+            &["@write", "@slow", "@scripting"],
+        )
+        .docs(
+            "Creates a library.",
+            "O(1) (considering compilation time is redundant)",
+        ),
+    CommandSpec::new("function|restore", -3, "scripting", "7.0.0")
+        .flags(
+            &["noscript", "write", "denyoom"],
+            &["@write", "@slow", "@scripting"],
+        )
+        .docs(
+            "Restores all libraries from a payload.",
+            "O(N) where N is the number of functions on the payload",
+        ),
+];
+
+const PUBSUB_SUBCOMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("pubsub|channels", -2, "pubsub", "2.8.0")
+        .flags(PUBSUB_FLAGS, &["@pubsub", "@slow"])
+        .docs(
+            "Returns the active channels.",
+            "O(N) where N is the number of active channels, and assuming constant time pattern matching (relatively short channels and patterns)",
+        ),
+    CommandSpec::new("pubsub|numpat", 2, "pubsub", "2.8.0")
+        .flags(PUBSUB_FLAGS, &["@pubsub", "@slow"])
+        .docs("Returns a count of unique pattern subscriptions.", "O(1)"),
+    CommandSpec::new("pubsub|numsub", -2, "pubsub", "2.8.0")
+        .flags(PUBSUB_FLAGS, &["@pubsub", "@slow"])
+        .docs(
+            "Returns a count of subscribers to channels.",
+            "O(N) for the NUMSUB subcommand, where N is the number of requested channels",
+        ),
+    CommandSpec::new("pubsub|shardchannels", -2, "pubsub", "7.0.0")
+        .flags(PUBSUB_FLAGS, &["@pubsub", "@slow"])
+        .docs(
+            "Returns the active shard channels.",
+            "O(N) where N is the number of active shard channels, and assuming constant time pattern matching (relatively short shard channels).",
+        ),
+    CommandSpec::new("pubsub|shardnumsub", -2, "pubsub", "7.0.0")
+        .flags(PUBSUB_FLAGS, &["@pubsub", "@slow"])
+        .docs(
+            "Returns the count of subscribers of shard channels.",
+            "O(N) for the SHARDNUMSUB subcommand, where N is the number of requested shard channels",
+        ),
+];
+
+const COMMAND_SUBCOMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("command|count", 2, "server", "2.8.13")
+        .flags(&["loading", "stale"], &["@slow", "@connection"])
+        .docs("Returns a count of commands.", "O(1)"),
+    CommandSpec::new("command|docs", -2, "server", "7.0.0")
+        .flags(&["loading", "stale"], &["@slow", "@connection"])
+        .docs(
+            "Returns documentary information about one, multiple or all commands.",
+            "O(N) where N is the number of commands to look up",
+        ),
+    CommandSpec::new("command|getkeys", -3, "server", "2.8.13")
+        .flags(&["loading", "stale"], &["@slow", "@connection"])
+        .docs(
+            "Extracts the key names from an arbitrary command.",
+            "O(N) where N is the number of arguments to the command",
+        ),
+    CommandSpec::new("command|info", -2, "server", "2.8.13")
+        .flags(&["loading", "stale"], &["@slow", "@connection"])
+        .docs(
+            "Returns information about one, multiple or all commands.",
+            "O(N) where N is the number of commands to look up",
+        ),
+    CommandSpec::new("command|list", -2, "server", "7.0.0")
+        .flags(&["loading", "stale"], &["@slow", "@connection"])
+        .docs(
+            "Returns a list of command names.",
+            "O(N) where N is the total number of Redis commands",
+        ),
+];
+
+/// Every command the server knows, the single source of truth for dispatch.
+pub const COMMAND_TABLE: &[CommandSpec] = &[
+    CommandSpec::new("acl", -2, "server", "6.0.0")
+        .docs("A container for Access List Control commands.", "Depends on subcommand.")
+        .subcommands(ACL_SUBCOMMANDS),
+    CommandSpec::new("auth", -2, "connection", "1.0.0")
+        .flags(
+            &["noscript", "loading", "stale", "fast", "no_auth", "allow_busy"],
+            &["@fast", "@connection"],
+        )
+        .docs(
+            "Authenticates the connection.",
+            "O(N) where N is the number of passwords defined for the user",
+        ),
+    CommandSpec::new("client", -2, "connection", "2.4.0")
+        .docs("A container for client connection commands.", "Depends on subcommand.")
+        .subcommands(CLIENT_SUBCOMMANDS),
+    CommandSpec::new("command", -1, "server", "2.8.13")
+        .flags(&["loading", "stale"], &["@slow", "@connection"])
+        .docs(
+            "Returns detailed information about all commands.",
+            "O(N) where N is the total number of Redis commands",
+        )
+        .subcommands(COMMAND_SUBCOMMANDS),
+    CommandSpec::new("config", -2, "server", "2.0.0")
+        .docs("A container for server configuration commands.", "Depends on subcommand.")
+        .subcommands(CONFIG_SUBCOMMANDS),
+    CommandSpec::new("echo", 2, "connection", "1.0.0")
+        .flags(&["fast"], &["@fast", "@connection"])
+        .docs("Returns the given string.", "O(1)"),
+    CommandSpec::new("fcall", -3, "scripting", "7.0.0")
+        .flags(
+            &[
+                "noscript",
+                "skip_monitor",
+                "may_replicate",
+                "no_mandatory_keys",
+                "stale",
+                "movablekeys",
+            ],
+            &["@slow", "@scripting"],
+        )
+        .docs(
+            "Invokes a function.",
+            "Depends on the function that is executed.",
+        ),
+    CommandSpec::new("fcall_ro", -3, "scripting", "7.0.0")
+        .flags(
+            &[
+                "readonly",
+                "noscript",
+                "skip_monitor",
+                "no_mandatory_keys",
+                "stale",
+                "movablekeys",
+            ],
+            &["@slow", "@scripting"],
+        )
+        .docs(
+            "Invokes a read-only function.",
+            "Depends on the function that is executed.",
+        ),
+    CommandSpec::new("function", -2, "scripting", "7.0.0")
+        .docs("A container for function commands.", "Depends on subcommand.")
+        .subcommands(FUNCTION_SUBCOMMANDS),
+    CommandSpec::new("get", 2, "string", "1.0.0")
+        .flags(&["readonly", "fast"], &["@read", "@string", "@fast"])
+        .keys(1, 1, 1, &["RO", "ACCESS"])
+        .docs("Returns the string value of a key.", "O(1)"),
+    CommandSpec::new("hello", -1, "connection", "6.0.0")
+        .flags(
+            &["noscript", "loading", "stale", "fast", "no_auth", "allow_busy"],
+            &["@fast", "@connection"],
+        )
+        .docs("Handshakes with the Redis server.", "O(1)"),
+    CommandSpec::new("hget", 3, "hash", "2.0.0")
+        .flags(&["readonly", "fast"], &["@read", "@hash", "@fast"])
+        .keys(1, 1, 1, &["RO", "ACCESS"])
+        .docs("Returns the value of a field in a hash.", "O(1)"),
+    CommandSpec::new("hgetall", 2, "hash", "2.0.0")
+        .flags(&["readonly"], &["@read", "@hash", "@slow"])
+        .keys(1, 1, 1, &["RO", "ACCESS"])
+        .docs(
+            "Returns all fields and values in a hash.",
+            "O(N) where N is the size of the hash.",
+        ),
+    CommandSpec::new("hmget", -3, "hash", "2.0.0")
+        .flags(&["readonly", "fast"], &["@read", "@hash", "@fast"])
+        .keys(1, 1, 1, &["RO", "ACCESS"])
+        .docs(
+            "Returns the values of all fields in a hash.",
+            "O(N) where N is the number of fields being requested.",
+        ),
+    CommandSpec::new("hset", -4, "hash", "2.0.0")
+        .flags(&["write", "denyoom", "fast"], &["@write", "@hash", "@fast"])
+        .keys(1, 1, 1, &["RW", "UPDATE"])
+        .docs(
+            "Creates or modifies the value of a field in a hash.",
+            "O(1) for each field/value pair added, so O(N) to add N field/value pairs when the command is called with multiple field/value pairs.",
+        ),
+    CommandSpec::new("info", -1, "server", "1.0.0")
+        .flags(&["loading", "stale"], &["@slow", "@dangerous"])
+        .docs("Returns information and statistics about the server.", "O(1)"),
+    CommandSpec::new("ping", -1, "connection", "1.0.0")
+        .flags(&["fast"], &["@fast", "@connection"])
+        .docs("Returns the server's liveliness response.", "O(1)"),
+    CommandSpec::new("psubscribe", -2, "pubsub", "2.0.0")
+        .flags(SUBSCRIBE_FLAGS, &["@pubsub", "@slow"])
+        .docs(
+            "Listens for messages published to channels that match one or more patterns.",
+            "O(N) where N is the number of patterns to subscribe to.",
+        ),
+    CommandSpec::new("publish", 3, "pubsub", "2.0.0")
+        .flags(
+            &["pubsub", "loading", "stale", "fast"],
+            &["@pubsub", "@fast"],
+        )
+        .docs(
+            "Posts a message to a channel.",
+            "O(N+M) where N is the number of clients subscribed to the receiving channel and M is the total number of subscribed patterns (by any client).",
+        ),
+    CommandSpec::new("pubsub", -2, "pubsub", "2.8.0")
+        .docs("A container for Pub/Sub commands.", "Depends on subcommand.")
+        .subcommands(PUBSUB_SUBCOMMANDS),
+    CommandSpec::new("punsubscribe", -1, "pubsub", "2.0.0")
+        .flags(SUBSCRIBE_FLAGS, &["@pubsub", "@slow"])
+        .docs(
+            "Stops listening to messages published to channels that match one or more patterns.",
+            "O(N) where N is the number of patterns to unsubscribe.",
+        ),
+    CommandSpec::new("quit", -1, "connection", "1.0.0")
+        .flags(
+            &["noscript", "loading", "stale", "fast", "no_auth", "allow_busy"],
+            &["@fast", "@connection"],
+        )
+        .docs("Closes the connection.", "O(1)"),
+    CommandSpec::new("reset", 1, "connection", "6.2.0")
+        .flags(
+            &["noscript", "loading", "stale", "fast", "no_auth", "allow_busy"],
+            &["@fast", "@connection"],
+        )
+        .docs("Resets the connection.", "O(1)"),
+    CommandSpec::new("set", -3, "string", "1.0.0")
+        .flags(&["write", "denyoom"], &["@write", "@string", "@slow"])
+        .keys(1, 1, 1, &["RW", "ACCESS", "UPDATE", "VARIABLE_FLAGS"])
+        .docs(
+            "Sets the string value of a key, ignoring its type. The key is created if it doesn't exist.",
+            "O(1)",
+        ),
+    CommandSpec::new("shutdown", -1, "server", "1.0.0")
+        .flags(
+            &["admin", "noscript", "loading", "stale", "no_multi", "allow_busy"],
+            ADMIN_ACL,
+        )
+        .docs(
+            "Synchronously saves the database(s) to disk and shuts down the Redis server.",
+            "O(N) when saving, where N is the total number of keys in all databases when saving data, otherwise O(1)",
+        ),
+    CommandSpec::new("spublish", 3, "pubsub", "7.0.0")
+        .flags(
+            &["pubsub", "loading", "stale", "fast"],
+            &["@pubsub", "@fast"],
+        )
+        .keys(1, 1, 1, &["NOT_KEY"])
+        .docs(
+            "Post a message to a shard channel",
+            "O(N) where N is the number of clients subscribed to the receiving shard channel.",
+        ),
+    CommandSpec::new("ssubscribe", -2, "pubsub", "7.0.0")
+        .flags(SUBSCRIBE_FLAGS, &["@pubsub", "@slow"])
+        .keys(1, -1, 1, &["NOT_KEY"])
+        .docs(
+            "Listens for messages published to shard channels.",
+            "O(N) where N is the number of shard channels to subscribe to.",
+        ),
+    CommandSpec::new("subscribe", -2, "pubsub", "2.0.0")
+        .flags(SUBSCRIBE_FLAGS, &["@pubsub", "@slow"])
+        .docs(
+            "Listens for messages published to channels.",
+            "O(N) where N is the number of channels to subscribe to.",
+        ),
+    CommandSpec::new("sunsubscribe", -1, "pubsub", "7.0.0")
+        .flags(SUBSCRIBE_FLAGS, &["@pubsub", "@slow"])
+        .keys(1, -1, 1, &["NOT_KEY"])
+        .docs(
+            "Stops listening to messages posted to shard channels.",
+            "O(N) where N is the number of shard channels to unsubscribe.",
+        ),
+    CommandSpec::new("time", 1, "server", "2.6.0")
+        .flags(&["loading", "stale", "fast"], &["@fast"])
+        .docs("Returns the server time.", "O(1)"),
+    CommandSpec::new("unsubscribe", -1, "pubsub", "2.0.0")
+        .flags(SUBSCRIBE_FLAGS, &["@pubsub", "@slow"])
+        .docs(
+            "Stops listening to messages posted to channels.",
+            "O(N) where N is the number of channels to unsubscribe.",
+        ),
+];
+
+/// Finds a command or subcommand by its full name, ignoring case.
+pub fn lookup(name: &str) -> Option<&'static CommandSpec> {
+    let (container, sub) = match name.split_once('|') {
+        Some((container, sub)) => (container, Some(sub)),
+        None => (name, None),
+    };
+    let spec = find(COMMAND_TABLE, container.as_bytes())?;
+    match sub {
+        Some(sub) => spec
+            .subcommands
+            .iter()
+            .find(|s| s.short_name().eq_ignore_ascii_case(sub)),
+        None => Some(spec),
+    }
+}
+
+/// Resolves the command a request invokes, descending into subcommands, and checks its arity.
+pub fn resolve(frame: &RespArray) -> Result<&'static CommandSpec, CommandError> {
+    let spec = match frame.first() {
+        Some(RespFrame::BulkString(name)) => find(COMMAND_TABLE, name),
+        _ => None,
+    }
+    .ok_or_else(|| CommandError::unknown_command(frame))?;
+    if !spec.check_arity(frame.len()) {
+        return Err(CommandError::WrongArity(spec.name.to_string()));
+    }
+    // COMMAND alone is a command of its own, CONFIG and PUBSUB always need a subcommand
+    if spec.subcommands.is_empty() || frame.len() < 2 {
+        return Ok(spec);
+    }
+
+    let sub = match &frame[1] {
+        RespFrame::BulkString(name) => spec
+            .subcommands
+            .iter()
+            .find(|s| s.short_name().as_bytes().eq_ignore_ascii_case(name)),
+        _ => None,
+    }
+    .ok_or_else(|| CommandError::unknown_subcommand(frame))?;
+    if !sub.check_arity(frame.len()) {
+        return Err(CommandError::WrongArity(sub.name.to_string()));
+    }
+    Ok(sub)
+}
+
+fn find(table: &'static [CommandSpec], name: &[u8]) -> Option<&'static CommandSpec> {
+    table
+        .iter()
+        .find(|spec| spec.name.as_bytes().eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespDecode;
+    use anyhow::Result;
+
+    #[test]
+    fn test_resolve() -> Result<()> {
+        // GET key
+        let mut buf = bytes::BytesMut::from(&b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n"[..]);
+        assert_eq!(resolve(&RespArray::decode(&mut buf)?)?.name, "get");
+
+        // Config Get *
+        let mut buf = bytes::BytesMut::from(&b"*3\r\n$6\r\nConfig\r\n$3\r\nGet\r\n$1\r\n*\r\n"[..]);
+        assert_eq!(resolve(&RespArray::decode(&mut buf)?)?.name, "config|get");
+
+        // command
+        let mut buf = bytes::BytesMut::from(&b"*1\r\n$7\r\ncommand\r\n"[..]);
+        assert_eq!(resolve(&RespArray::decode(&mut buf)?)?.name, "command");
+
+        // get key extra
+        let mut buf =
+            bytes::BytesMut::from(&b"*3\r\n$3\r\nget\r\n$3\r\nkey\r\n$5\r\nextra\r\n"[..]);
+        assert_eq!(
+            resolve(&RespArray::decode(&mut buf)?).unwrap_err(),
+            CommandError::WrongArity("get".into())
+        );
+
+        // config get
+        let mut buf = bytes::BytesMut::from(&b"*2\r\n$6\r\nconfig\r\n$3\r\nget\r\n"[..]);
+        assert_eq!(
+            resolve(&RespArray::decode(&mut buf)?).unwrap_err(),
+            CommandError::WrongArity("config|get".into())
+        );
+
+        // config
+        let mut buf = bytes::BytesMut::from(&b"*1\r\n$6\r\nconfig\r\n"[..]);
+        assert_eq!(
+            resolve(&RespArray::decode(&mut buf)?).unwrap_err(),
+            CommandError::WrongArity("config".into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(lookup("HSET").map(|s| s.arity), Some(-4));
+        assert_eq!(
+            lookup("pubsub|NUMPAT").map(|s| s.name),
+            Some("pubsub|numpat")
+        );
+        assert!(lookup("pubsub|nope").is_none());
+        assert!(lookup("nope").is_none());
+    }
+
+    #[test]
+    fn test_key_positions() {
+        let get = lookup("get").unwrap();
+        assert_eq!(get.key_positions(2), [1]);
+        let ssubscribe = lookup("ssubscribe").unwrap();
+        assert_eq!(ssubscribe.key_positions(4), [1, 2, 3]);
+        assert!(lookup("publish").unwrap().key_positions(3).is_empty());
+    }
+
+    #[test]
+    fn test_table_is_sorted_and_consistent() {
+        for spec in COMMAND_TABLE {
+            assert_eq!(spec.name, spec.name.to_ascii_lowercase());
+            for sub in spec.subcommands {
+                assert!(sub.name.starts_with(&format!("{}|", spec.name)));
+            }
+        }
+        assert!(COMMAND_TABLE.windows(2).all(|w| w[0].name < w[1].name));
+    }
+}
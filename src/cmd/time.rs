@@ -0,0 +1,57 @@
+use crate::cmd::{validate_command, CommandError, CommandExecutor};
+use crate::{Backend, BulkString, RespArray, RespFrame};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug)]
+pub struct Time;
+
+impl CommandExecutor for Time {
+    fn execute(self, _: &Backend) -> Result<RespFrame, CommandError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Ok(RespArray::new(vec![
+            BulkString::from(now.as_secs().to_string()).into(),
+            BulkString::from(now.subsec_micros().to_string()).into(),
+        ])
+        .into())
+    }
+}
+
+// time
+// *1\r\n$4\r\ntime\r\n
+impl TryFrom<RespArray> for Time {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["time"])?;
+
+        Ok(Time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespDecode;
+    use anyhow::Result;
+
+    #[test]
+    fn test_time_command() -> Result<()> {
+        let mut cmd = bytes::BytesMut::from(&b"*1\r\n$4\r\ntime\r\n"[..]);
+        let time = Time::try_from(RespArray::decode(&mut cmd)?)?;
+        let RespFrame::Array(reply) = time.execute(&Backend::new())? else {
+            panic!("Expected Array");
+        };
+        let parts: Vec<u64> = reply
+            .iter()
+            .map(|part| match part {
+                RespFrame::BulkString(part) => String::from_utf8_lossy(part).parse().unwrap(),
+                _ => panic!("Expected BulkString"),
+            })
+            .collect();
+        assert!(parts[0] > 1_700_000_000);
+        assert!(parts[1] < 1_000_000);
+        Ok(())
+    }
+}
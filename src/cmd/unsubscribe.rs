@@ -0,0 +1,109 @@
+use crate::cmd::{
+    extract_args, extract_strings, subscription_reply, validate_command, CommandError,
+    ContextExecutor, ExecFuture,
+};
+use crate::network::Session;
+use crate::RespArray;
+
+#[derive(Debug)]
+pub struct Unsubscribe {
+    channels: Vec<String>,
+}
+
+impl ContextExecutor for Unsubscribe {
+    fn execute(self, session: &mut Session) -> ExecFuture<'_> {
+        Box::pin(async move {
+            // without arguments, unsubscribe from every channel the connection is subscribed to
+            let channels = if self.channels.is_empty() {
+                session.channels()
+            } else {
+                self.channels
+            };
+            if channels.is_empty() {
+                return Ok(vec![subscription_reply(
+                    "unsubscribe",
+                    None,
+                    session.subscriptions(),
+                )]);
+            }
+
+            Ok(channels
+                .into_iter()
+                .map(|channel| {
+                    let count = session.unsubscribe(&channel);
+                    subscription_reply("unsubscribe", Some(channel), count)
+                })
+                .collect())
+        })
+    }
+}
+
+// unsubscribe news
+// *2\r\n$11\r\nunsubscribe\r\n$4\r\nnews\r\n
+impl TryFrom<RespArray> for Unsubscribe {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["unsubscribe"])?;
+
+        let channels = extract_strings(extract_args(value, 1)?)?;
+        Ok(Unsubscribe { channels })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Backend, RespDecode};
+    use anyhow::Result;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_unsubscribe_command() -> Result<()> {
+        let mut cmd = bytes::BytesMut::from(&b"*2\r\n$11\r\nunsubscribe\r\n$4\r\nnews\r\n"[..]);
+        let cmd = RespArray::decode(&mut cmd)?;
+        let unsubscribe = Unsubscribe::try_from(cmd)?;
+        assert_eq!(unsubscribe.channels, ["news"]);
+
+        let backend = Backend::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(backend.clone(), tx);
+        session.subscribe("news".into());
+        session.subscribe("sports".into());
+
+        let ret = unsubscribe.execute(&mut session).await?;
+        assert_eq!(
+            ret,
+            vec![subscription_reply("unsubscribe", Some("news".into()), 1)]
+        );
+        assert_eq!(backend.pubsub().numsub("news"), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribe_all() -> Result<()> {
+        let backend = Backend::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(backend, tx);
+        session.subscribe("news".into());
+        session.subscribe("sports".into());
+
+        let ret = Unsubscribe { channels: vec![] }
+            .execute(&mut session)
+            .await?;
+        assert_eq!(
+            ret,
+            vec![
+                subscription_reply("unsubscribe", Some("news".into()), 1),
+                subscription_reply("unsubscribe", Some("sports".into()), 0),
+            ]
+        );
+        assert!(!session.is_subscribed());
+
+        let ret = Unsubscribe { channels: vec![] }
+            .execute(&mut session)
+            .await?;
+        assert_eq!(ret, vec![subscription_reply("unsubscribe", None, 0)]);
+        Ok(())
+    }
+}
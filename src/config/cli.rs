@@ -0,0 +1,95 @@
+use super::file::quote;
+use std::path::PathBuf;
+
+/// What the command line asks the server to do.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Cli {
+    /// Start with the configuration in `file`, if any, followed by the directives given as
+    /// options.
+    Run {
+        file: Option<PathBuf>,
+        overrides: String,
+    },
+    Version,
+    Help,
+}
+
+/// Parses the arguments the way redis-server does: `[/path/to/redis.conf] [--name value...]`,
+/// where every `--name` starts a config directive and the arguments that follow are its values.
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Cli {
+    let mut args = args.into_iter().peekable();
+    match args.peek().map(String::as_str) {
+        Some("-v" | "--version") => return Cli::Version,
+        Some("-h" | "--help") => return Cli::Help,
+        _ => {}
+    }
+
+    let file = args
+        .next_if(|arg| !arg.starts_with("--"))
+        .map(PathBuf::from);
+    let mut overrides = String::new();
+    for arg in args {
+        match arg.strip_prefix("--") {
+            Some(name) => {
+                if !overrides.is_empty() {
+                    overrides.push('\n');
+                }
+                overrides.push_str(name);
+            }
+            None => {
+                overrides.push(' ');
+                overrides.push_str(&quote(&arg));
+            }
+        }
+    }
+    Cli::Run { file, overrides }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+
+    fn parse(args: &[&str]) -> Cli {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse(&["--version"]), Cli::Version);
+        assert_eq!(parse(&["-h"]), Cli::Help);
+        assert_eq!(
+            parse(&[]),
+            Cli::Run {
+                file: None,
+                overrides: String::new()
+            }
+        );
+
+        let cli = parse(&[
+            "/etc/redis.conf",
+            "--port",
+            "6380",
+            "--bind",
+            "127.0.0.1",
+            "::1",
+            "--notify-keyspace-events",
+            "",
+        ]);
+        assert_eq!(
+            cli,
+            Cli::Run {
+                file: Some("/etc/redis.conf".into()),
+                overrides: "port 6380\nbind 127.0.0.1 ::1\nnotify-keyspace-events \"\"".into()
+            }
+        );
+
+        let Cli::Run { overrides, .. } = cli else {
+            unreachable!()
+        };
+        let (config, warnings) = ServerConfig::load(None, &overrides).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(config.port, 6380);
+        assert_eq!(config.bind, ["127.0.0.1", "::1"]);
+    }
+}
@@ -0,0 +1,247 @@
+use super::{find_param, ConfigError, Param, ServerConfig, PARAMS};
+use crate::network::split_args;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+// separates the directives CONFIG REWRITE appends from the rest of the file
+const REWRITE_MARKER: &str = "# Generated by CONFIG REWRITE";
+
+impl ServerConfig {
+    /// Loads the configuration from `file`, if any, then applies `overrides`, directives in
+    /// the same format that come from the command line. The warnings about the directives
+    /// that were ignored are returned as well, since the configuration is loaded before
+    /// logging is set up.
+    pub fn load(
+        file: Option<PathBuf>,
+        overrides: &str,
+    ) -> Result<(Self, Vec<String>), ConfigError> {
+        let mut config = ServerConfig::default();
+        let mut warnings = vec![];
+        if let Some(path) = &file {
+            let text = fs::read_to_string(path)
+                .map_err(|e| ConfigError::Open(path.display().to_string(), e.to_string()))?;
+            warnings = config.apply(&text)?;
+        }
+        warnings.extend(config.apply(overrides)?);
+        config.file = file;
+        Ok((config, warnings))
+    }
+
+    /// Applies the directives of a redis.conf style text, one per line, returning a warning
+    /// for every directive that was ignored.
+    pub fn apply(&mut self, text: &str) -> Result<Vec<String>, ConfigError> {
+        let mut warnings = vec![];
+        for (i, line) in text.lines().enumerate() {
+            let error = |reason: &str| ConfigError::File(i + 1, line.to_string(), reason.into());
+            let args = match parse_line(line) {
+                Some(Ok(args)) => args,
+                Some(Err(())) => return Err(error("Unbalanced quotes in configuration line")),
+                None => continue,
+            };
+            match find_param(&args[0]) {
+                Some(param) => {
+                    if args.len() < 2 || (!param.multi_arg && args.len() > 2) {
+                        return Err(error("wrong number of arguments"));
+                    }
+                    (param.set)(self, &args[1..].join(" ")).map_err(|reason| error(&reason))?;
+                }
+                // unlike redis, a stock redis.conf with directives this server has no use for
+                // still loads
+                None => warnings.push(format!(
+                    "Ignoring unsupported config directive '{}' at line {}",
+                    args[0],
+                    i + 1
+                )),
+            }
+        }
+        Ok(warnings)
+    }
+
+    /// Writes the configuration back to the file it was loaded from. Directives are replaced in
+    /// place, comments and unsupported directives are kept, and parameters that differ from
+    /// their defaults but were not in the file are appended.
+    pub fn rewrite(&self) -> Result<(), ConfigError> {
+        let path = self.file.as_ref().ok_or(ConfigError::NoFile)?;
+        let old = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(ConfigError::Rewrite(e.to_string())),
+        };
+
+        // like redis, replace the file at once so a crash never leaves half of it behind
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(format!(".tmp-{}", std::process::id()));
+        fs::write(&tmp, self.rewrite_text(&old))
+            .and_then(|_| fs::rename(&tmp, path))
+            .map_err(|e| {
+                let _ = fs::remove_file(&tmp);
+                ConfigError::Rewrite(e.to_string())
+            })
+    }
+
+    fn rewrite_text(&self, old: &str) -> String {
+        let mut written: Vec<&str> = vec![];
+        let mut lines: Vec<String> = vec![];
+        for line in old.lines() {
+            let param = match parse_line(line) {
+                Some(Ok(args)) => find_param(&args[0]),
+                _ => None,
+            };
+            match param {
+                // only the first of repeated directives survives, holding the current value
+                Some(param) if written.contains(&param.name) => {}
+                Some(param) => {
+                    written.push(param.name);
+                    lines.push(self.directive(param));
+                }
+                None => lines.push(line.to_string()),
+            }
+        }
+
+        let defaults = ServerConfig::default();
+        let mut marked = lines.iter().any(|line| line == REWRITE_MARKER);
+        for param in PARAMS {
+            if written.contains(&param.name) || (param.get)(self) == (param.get)(&defaults) {
+                continue;
+            }
+            if !marked {
+                lines.push(REWRITE_MARKER.to_string());
+                marked = true;
+            }
+            lines.push(self.directive(param));
+        }
+
+        let mut text = lines.join("\n");
+        text.push('\n');
+        text
+    }
+
+    fn directive(&self, param: &Param) -> String {
+        let value = (param.get)(self);
+        let args: Vec<String> = if param.multi_arg {
+            value.split_whitespace().map(quote).collect()
+        } else {
+            vec![quote(&value)]
+        };
+        format!("{} {}", param.name, args.join(" "))
+    }
+}
+
+// the arguments of a directive, None for blank and comment lines
+fn parse_line(line: &str) -> Option<Result<Vec<String>, ()>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let args = match split_args(line.as_bytes()) {
+        Some(args) => args,
+        None => return Some(Err(())),
+    };
+    Some(Ok(args
+        .into_iter()
+        .map(|arg| String::from_utf8_lossy(&arg).into_owned())
+        .collect()))
+}
+
+/// Quotes an argument the way `sdscatrepr` in redis/src/sds.c does, when it would not read back
+/// as a single argument otherwise.
+pub(super) fn quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .bytes()
+            .all(|c| c.is_ascii_graphic() && !matches!(c, b'"' | b'\'' | b'\\'));
+    if plain {
+        return arg.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+    for c in arg.bytes() {
+        match c {
+            b'\\' => quoted.push_str("\\\\"),
+            b'"' => quoted.push_str("\\\""),
+            b'\n' => quoted.push_str("\\n"),
+            b'\r' => quoted.push_str("\\r"),
+            b'\t' => quoted.push_str("\\t"),
+            0x07 => quoted.push_str("\\a"),
+            0x08 => quoted.push_str("\\b"),
+            c if c.is_ascii_graphic() || c == b' ' => quoted.push(c as char),
+            c => quoted.push_str(&format!("\\x{:02x}", c)),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LogLevel;
+
+    #[test]
+    fn test_apply() {
+        let mut config = ServerConfig::default();
+        let warnings = config
+            .apply(
+                "# a comment\n\n  port 6380\nbind 127.0.0.1 \"::1\"\nloglevel warning\nsave \"\"\nmaxmemory 1gb\n",
+            )
+            .unwrap();
+        assert_eq!(
+            warnings,
+            ["Ignoring unsupported config directive 'save' at line 6"]
+        );
+        assert_eq!(config.port, 6380);
+        assert_eq!(config.bind, ["127.0.0.1", "::1"]);
+        assert_eq!(config.loglevel, LogLevel::Warning);
+        assert_eq!(config.maxmemory, 1024 * 1024 * 1024);
+
+        let err = config.apply("timeout 0\nport abc\n").unwrap_err();
+        assert_eq!(
+            err,
+            ConfigError::File(
+                2,
+                "port abc".into(),
+                "argument couldn't be parsed into an integer".into()
+            )
+        );
+        assert!(config.apply("port 1 2\n").is_err());
+        assert!(config.apply("port \"6379\n").is_err());
+    }
+
+    #[test]
+    fn test_rewrite_text() {
+        let mut config = ServerConfig {
+            port: 7000,
+            timeout: 30,
+            bind: vec!["127.0.0.1".into(), "-::1".into()],
+            ..Default::default()
+        };
+        let old =
+            "# Redis configuration\nport 6379\n\n# keep me\nsave 3600 1\nport 6380\nbind 0.0.0.0\n";
+        let rewritten = config.rewrite_text(old);
+        assert_eq!(
+            rewritten,
+            "# Redis configuration\nport 7000\n\n# keep me\nsave 3600 1\nbind 127.0.0.1 -::1\n# Generated by CONFIG REWRITE\ntimeout 30\n"
+        );
+
+        // a second rewrite appends below the existing marker
+        config.maxmemory = 100;
+        let rewritten = config.rewrite_text(&rewritten);
+        assert!(rewritten.ends_with("# Generated by CONFIG REWRITE\ntimeout 30\nmaxmemory 100\n"));
+        assert_eq!(rewritten.matches(REWRITE_MARKER).count(), 1);
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("notice"), "notice");
+        assert_eq!(quote(""), "\"\"");
+        assert_eq!(quote("a b"), "\"a b\"");
+        assert_eq!(quote("say \"hi\"\n"), "\"say \\\"hi\\\"\\n\"");
+
+        let mut config = ServerConfig::default();
+        config
+            .apply(&format!("notify-keyspace-events {}\n", quote("")))
+            .unwrap();
+        assert_eq!(config.notify_keyspace_events, 0);
+    }
+}
@@ -0,0 +1,795 @@
+mod cli;
+mod file;
+
+use crate::backend::notify::{format_notify_flags, parse_notify_flags};
+use crate::glob::glob_match;
+use crate::RespLimits;
+use std::path::PathBuf;
+use thiserror::Error;
+
+pub use cli::{parse_args, Cli};
+
+// the smallest value redis accepts for either protocol memory limit
+const MIN_MEMORY_LIMIT: usize = 1024 * 1024;
+
+/// Server settings, read from the command line and a redis.conf style file at startup and
+/// changed at runtime with CONFIG SET.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerConfig {
+    /// The file the configuration was loaded from, which CONFIG REWRITE writes back to.
+    pub file: Option<PathBuf>,
+    /// Addresses to listen on. `*` stands for every IPv4 address, `::*` for every IPv6 one,
+    /// and a leading `-` makes the address optional.
+    pub bind: Vec<String>,
+    pub port: u16,
+    pub databases: usize,
+    /// Path of a unix socket to listen on besides TCP, none when empty.
+    pub unixsocket: String,
+    /// Mode of the unix socket file, left to the umask when 0.
+    pub unixsocketperm: u32,
+    /// Port to accept TLS connections on, on the `bind` addresses. 0 disables TLS.
+    pub tls_port: u16,
+    pub tls_cert_file: String,
+    pub tls_key_file: String,
+    pub tls_ca_cert_file: String,
+    pub tls_auth_clients: TlsAuthClients,
+    /// Password of the default user, which needs none when empty.
+    pub requirepass: String,
+    /// File the ACL users are loaded from and saved to, none when empty.
+    pub aclfile: String,
+    pub acllog_max_len: usize,
+    // accepted for compatibility, nothing is evicted
+    pub maxmemory: usize,
+    pub maxclients: usize,
+    /// Seconds a client may stay idle before it is disconnected, 0 for ever.
+    pub timeout: u64,
+    /// Seconds between TCP keepalive probes to clients, 0 disables them.
+    pub tcp_keepalive: u64,
+    /// Milliseconds a function runs before other clients are told the server is busy, 0 for never.
+    pub busy_reply_threshold: u64,
+    pub client_output_buffer_limit: ClientOutputBufferLimits,
+    pub loglevel: LogLevel,
+    pub notify_keyspace_events: u32,
+    pub proto_max_bulk_len: usize,
+    pub client_query_buffer_limit: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum LogLevel {
+    Debug,
+    Verbose,
+    Notice,
+    Warning,
+    Nothing,
+}
+
+/// Whether TLS clients must present a certificate signed by `tls-ca-cert-file`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsAuthClients {
+    No,
+    Yes,
+    Optional,
+}
+
+/// How much output may pile up for a client before it is disconnected, for each class of
+/// clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientOutputBufferLimits {
+    pub normal: OutputBufferLimit,
+    pub replica: OutputBufferLimit,
+    pub pubsub: OutputBufferLimit,
+}
+
+/// A client past the hard limit, or past the soft limit for `soft_seconds` in a row, is
+/// disconnected. A limit of 0 is no limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OutputBufferLimit {
+    pub hard: usize,
+    pub soft: usize,
+    pub soft_seconds: u64,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    #[error("Unknown option or number of arguments for CONFIG SET - '{0}'")]
+    Unknown(String),
+    #[error("CONFIG SET failed (possibly related to argument '{0}') - can't set immutable config")]
+    Immutable(String),
+    #[error("CONFIG SET failed (possibly related to argument '{0}') - {1}")]
+    Invalid(String, String),
+    #[error("CONFIG SET failed (possibly related to argument '{0}') - duplicate parameter")]
+    Duplicate(String),
+    #[error("Fatal error, can't open config file '{0}': {1}")]
+    Open(String, String),
+    #[error("\n*** FATAL CONFIG FILE ERROR ***\nReading the configuration file, at line {0}\n>>> '{1}'\n{2}")]
+    File(usize, String, String),
+    #[error("The server is running without a config file")]
+    NoFile,
+    #[error("CONFIG SET failed (possibly related to argument '{0}') - Unable to update TLS configuration. Check server logs.")]
+    Tls(String),
+    #[error("Rewriting config file: {0}")]
+    Rewrite(String),
+}
+
+struct Param {
+    name: &'static str,
+    mutable: bool,
+    // whether a file directive may spread the value over several arguments
+    multi_arg: bool,
+    get: fn(&ServerConfig) -> String,
+    set: fn(&mut ServerConfig, &str) -> Result<(), String>,
+}
+
+const PARAMS: &[Param] = &[
+    Param {
+        name: "bind",
+        mutable: false,
+        multi_arg: true,
+        get: |c| c.bind.join(" "),
+        set: |c, v| {
+            let addrs: Vec<String> = v.split_whitespace().map(String::from).collect();
+            if addrs.is_empty() {
+                return Err("argument must not be empty".to_string());
+            }
+            c.bind = addrs;
+            Ok(())
+        },
+    },
+    Param {
+        name: "port",
+        mutable: false,
+        multi_arg: false,
+        get: |c| c.port.to_string(),
+        set: |c, v| {
+            c.port = parse_number(v, 0, u16::MAX as u64)? as u16;
+            Ok(())
+        },
+    },
+    Param {
+        name: "databases",
+        mutable: false,
+        multi_arg: false,
+        get: |c| c.databases.to_string(),
+        set: |c, v| {
+            c.databases = parse_number(v, 1, i32::MAX as u64)? as usize;
+            Ok(())
+        },
+    },
+    Param {
+        name: "unixsocket",
+        mutable: false,
+        multi_arg: false,
+        get: |c| c.unixsocket.clone(),
+        set: |c, v| {
+            c.unixsocket = v.to_string();
+            Ok(())
+        },
+    },
+    Param {
+        name: "unixsocketperm",
+        mutable: false,
+        multi_arg: false,
+        get: |c| format!("{:o}", c.unixsocketperm),
+        set: |c, v| {
+            c.unixsocketperm = u32::from_str_radix(v, 8)
+                .ok()
+                .filter(|perm| *perm <= 0o777)
+                .ok_or("argument must be an octal number between 0 and 777")?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "tls-port",
+        mutable: false,
+        multi_arg: false,
+        get: |c| c.tls_port.to_string(),
+        set: |c, v| {
+            c.tls_port = parse_number(v, 0, u16::MAX as u64)? as u16;
+            Ok(())
+        },
+    },
+    Param {
+        name: "tls-cert-file",
+        mutable: true,
+        multi_arg: false,
+        get: |c| c.tls_cert_file.clone(),
+        set: |c, v| {
+            c.tls_cert_file = v.to_string();
+            Ok(())
+        },
+    },
+    Param {
+        name: "tls-key-file",
+        mutable: true,
+        multi_arg: false,
+        get: |c| c.tls_key_file.clone(),
+        set: |c, v| {
+            c.tls_key_file = v.to_string();
+            Ok(())
+        },
+    },
+    Param {
+        name: "tls-ca-cert-file",
+        mutable: true,
+        multi_arg: false,
+        get: |c| c.tls_ca_cert_file.clone(),
+        set: |c, v| {
+            c.tls_ca_cert_file = v.to_string();
+            Ok(())
+        },
+    },
+    Param {
+        name: "tls-auth-clients",
+        mutable: true,
+        multi_arg: false,
+        get: |c| c.tls_auth_clients.as_str().to_string(),
+        set: |c, v| {
+            c.tls_auth_clients = TlsAuthClients::parse(v)
+                .ok_or("argument(s) must be one of the following: no, yes, optional")?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "requirepass",
+        mutable: true,
+        multi_arg: false,
+        get: |c| c.requirepass.clone(),
+        set: |c, v| {
+            c.requirepass = v.to_string();
+            Ok(())
+        },
+    },
+    Param {
+        name: "aclfile",
+        mutable: false,
+        multi_arg: false,
+        get: |c| c.aclfile.clone(),
+        set: |c, v| {
+            c.aclfile = v.to_string();
+            Ok(())
+        },
+    },
+    Param {
+        name: "acllog-max-len",
+        mutable: true,
+        multi_arg: false,
+        get: |c| c.acllog_max_len.to_string(),
+        set: |c, v| {
+            c.acllog_max_len = parse_number(v, 0, i64::MAX as u64)? as usize;
+            Ok(())
+        },
+    },
+    Param {
+        name: "maxmemory",
+        mutable: true,
+        multi_arg: false,
+        get: |c| c.maxmemory.to_string(),
+        set: |c, v| {
+            c.maxmemory = parse_memory(v).ok_or("argument must be a memory value")?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "maxclients",
+        mutable: true,
+        multi_arg: false,
+        get: |c| c.maxclients.to_string(),
+        set: |c, v| {
+            c.maxclients = parse_number(v, 1, u32::MAX as u64)? as usize;
+            Ok(())
+        },
+    },
+    Param {
+        name: "timeout",
+        mutable: true,
+        multi_arg: false,
+        get: |c| c.timeout.to_string(),
+        set: |c, v| {
+            c.timeout = parse_number(v, 0, i32::MAX as u64)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "tcp-keepalive",
+        mutable: true,
+        multi_arg: false,
+        get: |c| c.tcp_keepalive.to_string(),
+        set: |c, v| {
+            c.tcp_keepalive = parse_number(v, 0, i32::MAX as u64)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "busy-reply-threshold",
+        mutable: true,
+        multi_arg: false,
+        get: |c| c.busy_reply_threshold.to_string(),
+        set: |c, v| {
+            c.busy_reply_threshold = parse_number(v, 0, i64::MAX as u64)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "client-output-buffer-limit",
+        mutable: true,
+        multi_arg: true,
+        get: |c| c.client_output_buffer_limit.to_string(),
+        set: |c, v| c.client_output_buffer_limit.set(v),
+    },
+    Param {
+        name: "loglevel",
+        mutable: true,
+        multi_arg: false,
+        get: |c| c.loglevel.as_str().to_string(),
+        set: |c, v| {
+            c.loglevel = LogLevel::parse(v).ok_or(
+                "argument(s) must be one of the following: debug, verbose, notice, warning, nothing",
+            )?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "notify-keyspace-events",
+        mutable: true,
+        multi_arg: false,
+        get: |c| format_notify_flags(c.notify_keyspace_events),
+        set: |c, v| {
+            c.notify_keyspace_events = parse_notify_flags(v)
+                .map_err(|_| "Invalid event class character. Use 'Ag$lshzxeKEtmdn'.".to_string())?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "proto-max-bulk-len",
+        mutable: true,
+        multi_arg: false,
+        get: |c| c.proto_max_bulk_len.to_string(),
+        set: |c, v| {
+            c.proto_max_bulk_len = parse_memory_limit(v)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "client-query-buffer-limit",
+        mutable: true,
+        multi_arg: false,
+        get: |c| c.client_query_buffer_limit.to_string(),
+        set: |c, v| {
+            c.client_query_buffer_limit = parse_memory_limit(v)?;
+            Ok(())
+        },
+    },
+];
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        let limits = RespLimits::default();
+        Self {
+            file: None,
+            bind: vec!["*".to_string(), "-::*".to_string()],
+            port: 6379,
+            databases: 16,
+            unixsocket: String::new(),
+            unixsocketperm: 0,
+            tls_port: 0,
+            tls_cert_file: String::new(),
+            tls_key_file: String::new(),
+            tls_ca_cert_file: String::new(),
+            tls_auth_clients: TlsAuthClients::Yes,
+            requirepass: String::new(),
+            aclfile: String::new(),
+            acllog_max_len: 128,
+            maxmemory: 0,
+            maxclients: 10000,
+            timeout: 0,
+            tcp_keepalive: 300,
+            busy_reply_threshold: 5000,
+            client_output_buffer_limit: ClientOutputBufferLimits::default(),
+            loglevel: LogLevel::Notice,
+            notify_keyspace_events: 0,
+            proto_max_bulk_len: limits.max_bulk_len,
+            client_query_buffer_limit: limits.max_query_buffer,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// The parameters whose names match any of the glob `patterns`, with their values.
+    pub fn get(&self, patterns: &[String]) -> Vec<(&'static str, String)> {
+        PARAMS
+            .iter()
+            .filter(|param| {
+                patterns
+                    .iter()
+                    .any(|p| glob_match(p.to_ascii_lowercase().as_bytes(), param.name.as_bytes()))
+            })
+            .map(|param| (param.name, (param.get)(self)))
+            .collect()
+    }
+
+    /// Changes mutable parameters at runtime, either all of them or none.
+    pub fn set(&mut self, params: &[(String, String)]) -> Result<(), ConfigError> {
+        let mut config = self.clone();
+        let mut seen = Vec::with_capacity(params.len());
+        for (name, value) in params {
+            let param = find_param(name).ok_or_else(|| ConfigError::Unknown(name.clone()))?;
+            if !param.mutable {
+                return Err(ConfigError::Immutable(name.clone()));
+            }
+            if seen.contains(&param.name) {
+                return Err(ConfigError::Duplicate(name.clone()));
+            }
+            seen.push(param.name);
+            (param.set)(&mut config, value)
+                .map_err(|reason| ConfigError::Invalid(name.clone(), reason))?;
+        }
+        *self = config;
+        Ok(())
+    }
+
+    /// The limits applied when decoding client input.
+    pub fn resp_limits(&self) -> RespLimits {
+        RespLimits {
+            max_bulk_len: self.proto_max_bulk_len,
+            max_query_buffer: self.client_query_buffer_limit,
+            ..RespLimits::default()
+        }
+    }
+}
+
+impl LogLevel {
+    fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "debug" => Some(LogLevel::Debug),
+            "verbose" => Some(LogLevel::Verbose),
+            "notice" => Some(LogLevel::Notice),
+            "warning" => Some(LogLevel::Warning),
+            "nothing" => Some(LogLevel::Nothing),
+            _ => None,
+        }
+    }
+
+    /// The level stored as `level as u8`, anything out of range logging nothing.
+    pub fn from_u8(level: u8) -> Self {
+        match level {
+            0 => LogLevel::Debug,
+            1 => LogLevel::Verbose,
+            2 => LogLevel::Notice,
+            3 => LogLevel::Warning,
+            _ => LogLevel::Nothing,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Verbose => "verbose",
+            LogLevel::Notice => "notice",
+            LogLevel::Warning => "warning",
+            LogLevel::Nothing => "nothing",
+        }
+    }
+
+    /// Whether events of a tracing `level` are logged, debug showing everything down to
+    /// payloads at trace level.
+    pub fn enabled(&self, level: &tracing::Level) -> bool {
+        let max = match self {
+            LogLevel::Debug => tracing::Level::TRACE,
+            LogLevel::Verbose => tracing::Level::DEBUG,
+            LogLevel::Notice => tracing::Level::INFO,
+            LogLevel::Warning => tracing::Level::WARN,
+            LogLevel::Nothing => return false,
+        };
+        *level <= max
+    }
+}
+
+impl Default for ClientOutputBufferLimits {
+    fn default() -> Self {
+        const MB: usize = 1024 * 1024;
+        Self {
+            normal: OutputBufferLimit::default(),
+            replica: OutputBufferLimit {
+                hard: 256 * MB,
+                soft: 64 * MB,
+                soft_seconds: 60,
+            },
+            pubsub: OutputBufferLimit {
+                hard: 32 * MB,
+                soft: 8 * MB,
+                soft_seconds: 60,
+            },
+        }
+    }
+}
+
+impl ClientOutputBufferLimits {
+    /// The limit of a client type as CLIENT LIST names it.
+    pub fn for_type(&self, client_type: &str) -> OutputBufferLimit {
+        match client_type {
+            "pubsub" => self.pubsub,
+            "replica" => self.replica,
+            _ => self.normal,
+        }
+    }
+
+    // `<class> <hard> <soft> <soft seconds>` groups, the classes left out keep their limits
+    fn set(&mut self, value: &str) -> Result<(), String> {
+        let args: Vec<&str> = value.split_whitespace().collect();
+        if args.is_empty() || !args.len().is_multiple_of(4) {
+            return Err("Wrong number of arguments in buffer limit configuration.".to_string());
+        }
+        let mut limits = *self;
+        for group in args.chunks(4) {
+            let limit = match group[0].to_ascii_lowercase().as_str() {
+                "normal" => &mut limits.normal,
+                "replica" | "slave" => &mut limits.replica,
+                "pubsub" => &mut limits.pubsub,
+                _ => {
+                    return Err(
+                        "Invalid client class specified in buffer limit configuration.".to_string(),
+                    )
+                }
+            };
+            let (Some(hard), Some(soft), Ok(soft_seconds)) = (
+                parse_memory(group[1]),
+                parse_memory(group[2]),
+                group[3].parse::<u64>(),
+            ) else {
+                return Err(
+                    "Error in hard, soft or soft_seconds setting in buffer limit configuration."
+                        .to_string(),
+                );
+            };
+            *limit = OutputBufferLimit {
+                hard,
+                soft,
+                soft_seconds,
+            };
+        }
+        *self = limits;
+        Ok(())
+    }
+}
+
+// the format CONFIG GET reports, with the class names of redis
+impl std::fmt::Display for ClientOutputBufferLimits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let classes = [
+            ("normal", self.normal),
+            ("slave", self.replica),
+            ("pubsub", self.pubsub),
+        ];
+        for (i, (class, limit)) in classes.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(
+                f,
+                "{} {} {} {}",
+                class, limit.hard, limit.soft, limit.soft_seconds
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl TlsAuthClients {
+    fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "no" => Some(TlsAuthClients::No),
+            "yes" => Some(TlsAuthClients::Yes),
+            "optional" => Some(TlsAuthClients::Optional),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TlsAuthClients::No => "no",
+            TlsAuthClients::Yes => "yes",
+            TlsAuthClients::Optional => "optional",
+        }
+    }
+}
+
+fn find_param(name: &str) -> Option<&'static Param> {
+    PARAMS.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+}
+
+fn parse_number(value: &str, min: u64, max: u64) -> Result<u64, String> {
+    let n: i64 = value
+        .parse()
+        .map_err(|_| "argument couldn't be parsed into an integer".to_string())?;
+    if n < min as i64 || n as u64 > max {
+        return Err(format!(
+            "argument must be between {} and {} inclusive",
+            min, max
+        ));
+    }
+    Ok(n as u64)
+}
+
+fn parse_memory_limit(value: &str) -> Result<usize, String> {
+    match parse_memory(value) {
+        Some(bytes) if bytes >= MIN_MEMORY_LIMIT => Ok(bytes),
+        Some(_) => Err(format!(
+            "argument must be between {} and {} inclusive",
+            MIN_MEMORY_LIMIT,
+            usize::MAX
+        )),
+        None => Err("argument must be a memory value".to_string()),
+    }
+}
+
+/// Parses a memory value such as `512mb`, with the unit rules of `memtoull` in redis/src/util.c.
+fn parse_memory(value: &str) -> Option<usize> {
+    let value = value.to_ascii_lowercase();
+    let digits = value.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let unit = match &value[digits.len()..] {
+        "" | "b" => 1,
+        "k" => 1000,
+        "kb" => 1024,
+        "m" => 1000 * 1000,
+        "mb" => 1024 * 1024,
+        "g" => 1000 * 1000 * 1000,
+        "gb" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    digits.parse::<usize>().ok()?.checked_mul(unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(params: &[(&str, &str)]) -> Vec<(String, String)> {
+        params
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_config_get() {
+        let config = ServerConfig::default();
+        assert_eq!(
+            config.get(&["PORT".into(), "*-keepalive".into()]),
+            [
+                ("port", "6379".to_string()),
+                ("tcp-keepalive", "300".to_string())
+            ]
+        );
+        assert_eq!(
+            config.get(&["bind".into()]),
+            [("bind", "* -::*".to_string())]
+        );
+        assert!(config.get(&["nope".into()]).is_empty());
+    }
+
+    #[test]
+    fn test_config_set_is_atomic() {
+        let mut config = ServerConfig::default();
+        config
+            .set(&pairs(&[("maxmemory", "100mb"), ("LogLevel", "warning")]))
+            .unwrap();
+        assert_eq!(config.maxmemory, 100 * 1024 * 1024);
+        assert_eq!(config.loglevel, LogLevel::Warning);
+
+        let before = config.clone();
+        let err = config
+            .set(&pairs(&[("timeout", "10"), ("tcp-keepalive", "soon")]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CONFIG SET failed (possibly related to argument 'tcp-keepalive') - argument couldn't be parsed into an integer"
+        );
+        assert_eq!(config, before);
+
+        assert_eq!(
+            config.set(&pairs(&[("port", "6380")])),
+            Err(ConfigError::Immutable("port".into()))
+        );
+        assert_eq!(
+            config.set(&pairs(&[("nope", "1")])),
+            Err(ConfigError::Unknown("nope".into()))
+        );
+        assert_eq!(
+            config.set(&pairs(&[("timeout", "1"), ("TIMEOUT", "2")])),
+            Err(ConfigError::Duplicate("TIMEOUT".into()))
+        );
+    }
+
+    #[test]
+    fn test_memory_limits() {
+        let mut config = ServerConfig::default();
+        config
+            .set(&pairs(&[
+                ("proto-max-bulk-len", "2mb"),
+                ("client-query-buffer-limit", "1048576"),
+            ]))
+            .unwrap();
+        assert_eq!(config.resp_limits().max_bulk_len, 2 * 1024 * 1024);
+        assert_eq!(config.resp_limits().max_query_buffer, 1024 * 1024);
+
+        for value in ["1kb", "lots", "-1", "1tb"] {
+            assert!(config
+                .set(&pairs(&[("proto-max-bulk-len", value)]))
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_unix_socket() {
+        let mut config = ServerConfig::default();
+        config
+            .apply("unixsocket /tmp/redis.sock\nunixsocketperm 700\n")
+            .unwrap();
+        assert_eq!(config.unixsocket, "/tmp/redis.sock");
+        assert_eq!(config.unixsocketperm, 0o700);
+        assert_eq!(
+            config.get(&["unixsocket*".into()]),
+            [
+                ("unixsocket", "/tmp/redis.sock".to_string()),
+                ("unixsocketperm", "700".to_string())
+            ]
+        );
+
+        assert!(config.apply("unixsocketperm 1000\n").is_err());
+        assert!(config.apply("unixsocketperm 8\n").is_err());
+        assert_eq!(
+            config.set(&pairs(&[("unixsocket", "/tmp/other.sock")])),
+            Err(ConfigError::Immutable("unixsocket".into()))
+        );
+    }
+
+    #[test]
+    fn test_client_output_buffer_limit() {
+        let mut config = ServerConfig::default();
+        assert_eq!(
+            config.get(&["client-output-buffer-limit".into()]),
+            [(
+                "client-output-buffer-limit",
+                "normal 0 0 0 slave 268435456 67108864 60 pubsub 33554432 8388608 60".to_string()
+            )]
+        );
+
+        config
+            .set(&pairs(&[(
+                "client-output-buffer-limit",
+                "pubsub 1mb 512kb 10 normal 100 0 0",
+            )]))
+            .unwrap();
+        let limits = config.client_output_buffer_limit;
+        assert_eq!(
+            limits.for_type("pubsub"),
+            OutputBufferLimit {
+                hard: 1024 * 1024,
+                soft: 512 * 1024,
+                soft_seconds: 10
+            }
+        );
+        assert_eq!(limits.for_type("normal").hard, 100);
+        assert_eq!(limits.replica, ClientOutputBufferLimits::default().replica);
+
+        config
+            .apply("client-output-buffer-limit replica 0 0 0\n")
+            .unwrap();
+        assert_eq!(config.client_output_buffer_limit.replica.hard, 0);
+
+        for value in ["pubsub 1mb 1mb", "master 0 0 0", "pubsub lots 0 0"] {
+            assert!(config
+                .set(&pairs(&[("client-output-buffer-limit", value)]))
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_log_level() {
+        assert!(LogLevel::Notice.enabled(&tracing::Level::INFO));
+        assert!(!LogLevel::Notice.enabled(&tracing::Level::DEBUG));
+        assert!(LogLevel::Debug.enabled(&tracing::Level::TRACE));
+        assert!(!LogLevel::Nothing.enabled(&tracing::Level::ERROR));
+    }
+}
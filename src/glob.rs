@@ -0,0 +1,100 @@
+/// Redis style glob matching, as implemented by `stringmatchlen` in redis/src/util.c.
+///
+/// Supported syntax:
+/// - `*` matches any sequence of characters, including the empty one
+/// - `?` matches exactly one character
+/// - `[abc]`, `[a-z]` and `[^abc]` match one character from (or not from) a set
+/// - `\x` matches `x` literally
+pub fn glob_match(pattern: &[u8], string: &[u8]) -> bool {
+    let (mut p, mut s) = (0, 0);
+    while p < pattern.len() {
+        match pattern[p] {
+            b'*' => {
+                while p + 1 < pattern.len() && pattern[p + 1] == b'*' {
+                    p += 1;
+                }
+                if p + 1 == pattern.len() {
+                    return true;
+                }
+                return (s..=string.len()).any(|i| glob_match(&pattern[p + 1..], &string[i..]));
+            }
+            b'?' => {
+                if s >= string.len() {
+                    return false;
+                }
+                s += 1;
+            }
+            b'[' => {
+                if s >= string.len() {
+                    return false;
+                }
+                p += 1;
+                let not = pattern.get(p) == Some(&b'^');
+                if not {
+                    p += 1;
+                }
+                let mut matched = false;
+                while p < pattern.len() && pattern[p] != b']' {
+                    if pattern[p] == b'\\' && p + 1 < pattern.len() {
+                        p += 1;
+                        matched |= pattern[p] == string[s];
+                    } else if p + 2 < pattern.len() && pattern[p + 1] == b'-' {
+                        let (mut start, mut end) = (pattern[p], pattern[p + 2]);
+                        if start > end {
+                            std::mem::swap(&mut start, &mut end);
+                        }
+                        matched |= (start..=end).contains(&string[s]);
+                        p += 2;
+                    } else {
+                        matched |= pattern[p] == string[s];
+                    }
+                    p += 1;
+                }
+                if matched == not {
+                    return false;
+                }
+                s += 1;
+            }
+            c => {
+                let c = if c == b'\\' && p + 1 < pattern.len() {
+                    p += 1;
+                    pattern[p]
+                } else {
+                    c
+                };
+                if string.get(s) != Some(&c) {
+                    return false;
+                }
+                s += 1;
+            }
+        }
+        p += 1;
+    }
+    s == string.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(b"*", b""));
+        assert!(glob_match(b"*", b"anything"));
+        assert!(glob_match(b"events.*", b"events.login"));
+        assert!(!glob_match(b"events.*", b"event.login"));
+        assert!(glob_match(b"h?llo", b"hello"));
+        assert!(!glob_match(b"h?llo", b"hllo"));
+        assert!(glob_match(b"h*llo", b"heeeello"));
+        assert!(glob_match(b"h[ae]llo", b"hallo"));
+        assert!(!glob_match(b"h[ae]llo", b"hillo"));
+        assert!(glob_match(b"h[^e]llo", b"hallo"));
+        assert!(!glob_match(b"h[^e]llo", b"hello"));
+        assert!(glob_match(b"h[a-b]llo", b"hbllo"));
+        assert!(glob_match(b"h[b-a]llo", b"hbllo"));
+        assert!(glob_match(b"h\\*llo", b"h*llo"));
+        assert!(!glob_match(b"h\\*llo", b"hello"));
+        assert!(glob_match(b"a*b*c", b"axxbyyc"));
+        assert!(!glob_match(b"a*b*c", b"axxbyy"));
+    }
+}
@@ -1,5 +1,8 @@
+pub mod acl;
 mod backend;
 pub mod cmd;
+pub mod config;
+mod glob;
 pub mod network;
 mod resp;
 
@@ -1,25 +1,104 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use r_redis::cmd::REDIS_VERSION;
+use r_redis::config::{parse_args, Cli, ServerConfig};
 use r_redis::{network, Backend};
-use tokio::net::TcpListener;
+use std::path::Path;
+use std::time::Duration;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::task::JoinSet;
 use tracing::{info, warn};
+use tracing_subscriber::filter::{filter_fn, EnvFilter};
+use tracing_subscriber::prelude::*;
+
+const USAGE: &str = "Usage: r-redis [/path/to/redis.conf] [options]
+       r-redis -v or --version
+       r-redis -h or --help
+
+Examples:
+       r-redis (run the server with default config)
+       r-redis /etc/redis/6379.conf
+       r-redis --port 7777
+       r-redis /etc/myredis.conf --loglevel verbose";
+
+// how long clients get to finish the commands they run once the server shuts down
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    tracing_subscriber::fmt::init();
-
-    let addr = "0.0.0.0:6379";
-    let listener = TcpListener::bind(addr).await?;
-    info!("Listening on: {}", addr);
-
-    let backend = Backend::new();
-    loop {
-        let (stream, raddr) = listener.accept().await?;
-        let cloned_backend = backend.clone();
-        tokio::spawn(async move {
-            match network::stream_handler(stream, cloned_backend).await {
-                Ok(_) => info!("Connection from {} closed", raddr),
-                Err(e) => warn!("Connection from {} error: {:?}", raddr, e),
-            }
-        });
+    let (file, overrides) = match parse_args(std::env::args().skip(1)) {
+        Cli::Run { file, overrides } => (file, overrides),
+        Cli::Version => {
+            println!(
+                "r-redis v={} (redis v={})",
+                env!("CARGO_PKG_VERSION"),
+                REDIS_VERSION
+            );
+            return Ok(());
+        }
+        Cli::Help => {
+            eprintln!("{}", USAGE);
+            return Ok(());
+        }
+    };
+    let (config, warnings) = match ServerConfig::load(file, &overrides) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let backend = Backend::with_config(config);
+    init_tracing(&backend);
+    for warning in warnings {
+        warn!("{}", warning);
+    }
+    let aclfile = backend.config().aclfile.clone();
+    if !aclfile.is_empty() {
+        if let Err(e) = backend.acl().load_file(Path::new(&aclfile)) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if backend.config().tls_port != 0 {
+        let tls = network::tls::load(&backend.config()).context("Failed to configure TLS")?;
+        backend.set_tls(tls);
+    }
+    let mut sigterm = signal(SignalKind::terminate())?;
+    let mut sigint = signal(SignalKind::interrupt())?;
+    let listeners = network::listen(&backend.config())?;
+    let mut servers = JoinSet::new();
+    for listener in listeners {
+        servers.spawn(network::serve(listener, backend.clone()));
+    }
+    tokio::select! {
+        // the listeners only stop when they panic
+        Some(ret) = servers.join_next() => ret?,
+        _ = backend.shutdown().requested() => info!("User requested shutdown..."),
+        _ = sigterm.recv() => info!("Received SIGTERM scheduling shutdown..."),
+        _ = sigint.recv() => info!("Received SIGINT scheduling shutdown..."),
+    }
+
+    // stop accepting before the clients connected so far are closed
+    servers.shutdown().await;
+    network::shutdown(&backend, SHUTDOWN_TIMEOUT).await;
+    Ok(())
+}
+
+// RUST_LOG takes precedence, otherwise the loglevel setting decides as it changes
+fn init_tracing(backend: &Backend) {
+    let fmt = tracing_subscriber::fmt::layer();
+    match EnvFilter::try_from_default_env() {
+        Ok(filter) => tracing_subscriber::registry()
+            .with(fmt.with_filter(filter))
+            .init(),
+        Err(_) => {
+            let backend = backend.clone();
+            let filter = filter_fn(move |meta| backend.loglevel().enabled(meta.level()));
+            tracing_subscriber::registry()
+                .with(fmt.with_filter(filter))
+                .init()
+        }
     }
 }
@@ -0,0 +1,129 @@
+use crate::Backend;
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpStream, UnixStream};
+
+/// A client connection the server can speak RESP over.
+pub trait Connection: AsyncRead + AsyncWrite + Unpin + Send + 'static {
+    fn client_addr(&self) -> io::Result<ClientAddr>;
+}
+
+/// A connection that counts the bytes read from and written to it in the stats of the server.
+#[derive(Debug)]
+pub struct Metered<S> {
+    inner: S,
+    backend: Backend,
+}
+
+/// Both ends of a connection, as CLIENT LIST reports them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientAddr {
+    Tcp { peer: SocketAddr, local: SocketAddr },
+    Unix { path: PathBuf },
+}
+
+impl Connection for TcpStream {
+    fn client_addr(&self) -> io::Result<ClientAddr> {
+        Ok(ClientAddr::Tcp {
+            peer: self.peer_addr()?,
+            local: self.local_addr()?,
+        })
+    }
+}
+
+impl Connection for UnixStream {
+    fn client_addr(&self) -> io::Result<ClientAddr> {
+        // the client end of a unix socket is unnamed, both ends go by the listening path
+        let path = self.local_addr()?.as_pathname().map(PathBuf::from);
+        Ok(ClientAddr::Unix {
+            path: path.unwrap_or_default(),
+        })
+    }
+}
+
+impl ClientAddr {
+    /// The client's address, `ip:port` or `path:0` for unix sockets.
+    pub fn addr(&self) -> String {
+        match self {
+            ClientAddr::Tcp { peer, .. } => peer.to_string(),
+            ClientAddr::Unix { path } => format!("{}:0", path.display()),
+        }
+    }
+
+    /// The server's address the client connected to.
+    pub fn laddr(&self) -> String {
+        match self {
+            ClientAddr::Tcp { local, .. } => local.to_string(),
+            ClientAddr::Unix { path } => format!("{}:0", path.display()),
+        }
+    }
+
+    pub fn is_unix(&self) -> bool {
+        matches!(self, ClientAddr::Unix { .. })
+    }
+}
+
+impl fmt::Display for ClientAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.addr())
+    }
+}
+
+impl<S> Metered<S> {
+    pub fn new(inner: S, backend: Backend) -> Self {
+        Self { inner, backend }
+    }
+}
+
+impl<S: Connection> Connection for Metered<S> {
+    fn client_addr(&self) -> io::Result<ClientAddr> {
+        self.inner.client_addr()
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for Metered<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let filled = buf.filled().len();
+        let ret = Pin::new(&mut self.inner).poll_read(cx, buf);
+        let read = buf.filled().len() - filled;
+        if read > 0 {
+            let stats = self.backend.stats();
+            stats.reads_processed.incr();
+            stats.net_input_bytes.add(read as u64);
+        }
+        ret
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for Metered<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let ret = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = ret {
+            let stats = self.backend.stats();
+            stats.writes_processed.incr();
+            stats.net_output_bytes.add(written as u64);
+        }
+        ret
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
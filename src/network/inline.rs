@@ -0,0 +1,186 @@
+use crate::{BulkString, RespArray, RespError};
+use bytes::BytesMut;
+
+// PROTO_INLINE_MAX_SIZE in redis/src/server.h
+const INLINE_MAX_SIZE: usize = 64 * 1024;
+
+/// Decodes an inline command, a single line of space separated arguments as typed into
+/// telnet or netcat, into the array of bulk strings a RESP client would have sent.
+pub fn decode_inline(buf: &mut BytesMut) -> Result<RespArray, RespError> {
+    let Some(end) = buf.iter().position(|&b| b == b'\n') else {
+        if buf.len() > INLINE_MAX_SIZE {
+            return Err(RespError::LimitExceeded("too big inline request".into()));
+        }
+        return Err(RespError::NotComplete);
+    };
+    if end > INLINE_MAX_SIZE {
+        return Err(RespError::LimitExceeded("too big inline request".into()));
+    }
+
+    let line = buf.split_to(end + 1);
+    let line = &line[..end];
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    let args = split_args(line)
+        .ok_or_else(|| RespError::InvalidInline("unbalanced quotes in request".into()))?;
+    Ok(RespArray::new(
+        args.into_iter()
+            .map(|arg| BulkString::new(arg).into())
+            .collect(),
+    ))
+}
+
+/// Splits a line into arguments with the quoting rules of `sdssplitargs` in redis/src/sds.c:
+/// double quoted arguments understand `\xHH` and the usual C escapes, single quoted ones only
+/// `\'`, and a closing quote must be followed by whitespace. Returns `None` on unbalanced quotes.
+pub(crate) fn split_args(line: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let mut args = vec![];
+    let mut p = 0;
+    loop {
+        while p < line.len() && line[p].is_ascii_whitespace() {
+            p += 1;
+        }
+        if p == line.len() {
+            return Some(args);
+        }
+
+        let mut arg = vec![];
+        let (mut in_double, mut in_single) = (false, false);
+        loop {
+            let c = line.get(p).copied();
+            if in_double {
+                match c? {
+                    b'\\'
+                        if line.get(p + 1) == Some(&b'x')
+                            && line.get(p + 2).is_some_and(u8::is_ascii_hexdigit)
+                            && line.get(p + 3).is_some_and(u8::is_ascii_hexdigit) =>
+                    {
+                        let hex = std::str::from_utf8(&line[p + 2..p + 4]).ok()?;
+                        arg.push(u8::from_str_radix(hex, 16).ok()?);
+                        p += 3;
+                    }
+                    b'\\' if p + 1 < line.len() => {
+                        p += 1;
+                        arg.push(match line[p] {
+                            b'n' => b'\n',
+                            b'r' => b'\r',
+                            b't' => b'\t',
+                            b'b' => 0x08,
+                            b'a' => 0x07,
+                            c => c,
+                        });
+                    }
+                    b'"' => {
+                        closing_quote(line, p)?;
+                        p += 1;
+                        break;
+                    }
+                    c => arg.push(c),
+                }
+            } else if in_single {
+                match c? {
+                    b'\\' if line.get(p + 1) == Some(&b'\'') => {
+                        p += 1;
+                        arg.push(b'\'');
+                    }
+                    b'\'' => {
+                        closing_quote(line, p)?;
+                        p += 1;
+                        break;
+                    }
+                    c => arg.push(c),
+                }
+            } else {
+                match c {
+                    None | Some(b' ' | b'\n' | b'\r' | b'\t' | b'\0') => break,
+                    Some(b'"') => in_double = true,
+                    Some(b'\'') => in_single = true,
+                    Some(c) => arg.push(c),
+                }
+            }
+            p += 1;
+        }
+        args.push(arg);
+    }
+}
+
+// a closing quote must be followed by whitespace or the end of the line
+fn closing_quote(line: &[u8], p: usize) -> Option<()> {
+    line.get(p + 1)
+        .is_none_or(u8::is_ascii_whitespace)
+        .then_some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    fn args(line: &str) -> Option<Vec<String>> {
+        split_args(line.as_bytes()).map(|args| {
+            args.into_iter()
+                .map(|arg| String::from_utf8_lossy(&arg).into())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(args(""), Some(vec![]));
+        assert_eq!(args("  \t "), Some(vec![]));
+        assert_eq!(
+            args("set  key\tvalue "),
+            Some(vec!["set".into(), "key".into(), "value".into()])
+        );
+        assert_eq!(
+            args(r#"set "hello world" 'it\'s'"#),
+            Some(vec!["set".into(), "hello world".into(), "it's".into()])
+        );
+        assert_eq!(args(r#""a\x41\n\"\\""#), Some(vec!["aA\n\"\\".into()]));
+        // only \' is an escape inside single quotes
+        assert_eq!(args(r#"'a\nb'"#), Some(vec![r"a\nb".into()]));
+        // a quote in the middle of a word opens a quoted section
+        assert_eq!(args(r#"foo"bar baz""#), Some(vec!["foobar baz".into()]));
+        assert_eq!(args(r#""""#), Some(vec!["".into()]));
+
+        assert_eq!(args(r#""unterminated"#), None);
+        assert_eq!(args("'unterminated"), None);
+        assert_eq!(args(r#""closed"right"#), None);
+    }
+
+    #[test]
+    fn test_decode_inline() -> Result<()> {
+        let mut buf = BytesMut::from(&b"SET key \"a b\"\r\nPING\n"[..]);
+        assert_eq!(
+            decode_inline(&mut buf)?,
+            RespArray::new(vec![
+                BulkString::from("SET").into(),
+                BulkString::from("key").into(),
+                BulkString::from("a b").into(),
+            ])
+        );
+        assert_eq!(
+            decode_inline(&mut buf)?,
+            RespArray::new(vec![BulkString::from("PING").into()])
+        );
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from(&b"PIN"[..]);
+        assert_eq!(decode_inline(&mut buf), Err(RespError::NotComplete));
+        assert_eq!(buf.len(), 3);
+
+        let mut buf = BytesMut::from(&b"echo \"oops\r\n"[..]);
+        assert_eq!(
+            decode_inline(&mut buf),
+            Err(RespError::InvalidInline(
+                "unbalanced quotes in request".into()
+            ))
+        );
+
+        let mut buf = BytesMut::from(vec![b'a'; INLINE_MAX_SIZE + 1].as_slice());
+        assert_eq!(
+            decode_inline(&mut buf),
+            Err(RespError::LimitExceeded("too big inline request".into()))
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,227 @@
+use crate::config::ServerConfig;
+use crate::network::{stream_handler, Connection};
+use crate::Backend;
+use anyhow::{bail, Context, Result};
+use socket2::{Domain, SockRef, Socket, TcpKeepalive, Type};
+use std::fs;
+use std::io::ErrorKind;
+use std::net::{IpAddr, SocketAddr};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream, UnixListener};
+use tokio_rustls::TlsAcceptor;
+use tracing::{info, warn};
+
+// the backlog redis uses unless tcp-backlog says otherwise
+const TCP_BACKLOG: i32 = 511;
+
+// how long accepting waits once the process runs out of file descriptors
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+// how long a client gets to complete its TLS handshake
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A socket the server accepts clients on.
+#[derive(Debug)]
+pub enum Listener {
+    Tcp(TcpListener),
+    /// Accepts TCP connections that start with a TLS handshake.
+    Tls(TcpListener),
+    Unix(UnixListener),
+}
+
+/// Binds every address in `bind` on `port` and `tls-port`, failing unless each one that is not
+/// optional (prefixed with `-`) could be bound, and `unixsocket` when it is set. A port of 0
+/// disables it, though one of them must be listening.
+pub fn listen(config: &ServerConfig) -> Result<Vec<Listener>> {
+    let mut listeners: Vec<Listener> = listen_tcp(config, config.port)?
+        .into_iter()
+        .map(Listener::Tcp)
+        .collect();
+    listeners.extend(
+        listen_tcp(config, config.tls_port)?
+            .into_iter()
+            .map(Listener::Tls),
+    );
+    if !config.unixsocket.is_empty() {
+        let listener = bind_unix(Path::new(&config.unixsocket), config.unixsocketperm)
+            .with_context(|| format!("Failed opening Unix socket {}", config.unixsocket))?;
+        info!("Listening on: {}", config.unixsocket);
+        listeners.push(Listener::Unix(listener));
+    }
+    if listeners.is_empty() {
+        bail!("Configured to not listen anywhere, exiting.");
+    }
+    Ok(listeners)
+}
+
+fn listen_tcp(config: &ServerConfig, port: u16) -> Result<Vec<TcpListener>> {
+    let mut listeners = vec![];
+    if port == 0 {
+        return Ok(listeners);
+    }
+
+    for addr in &config.bind {
+        let (optional, host) = match addr.strip_prefix('-') {
+            Some(host) => (true, host),
+            None => (false, addr.as_str()),
+        };
+        let ip: IpAddr = match host {
+            "*" => "0.0.0.0".parse()?,
+            "::*" => "::".parse()?,
+            host => host
+                .parse()
+                .with_context(|| format!("Invalid bind address '{}'", host))?,
+        };
+        match bind(SocketAddr::new(ip, port)) {
+            Ok(listener) => {
+                info!("Listening on: {}", listener.local_addr()?);
+                listeners.push(listener);
+            }
+            Err(e) if optional => warn!("Could not bind optional address {}: {}", addr, e),
+            Err(e) => return Err(e).with_context(|| format!("Could not bind {}:{}", host, port)),
+        }
+    }
+    if listeners.is_empty() {
+        bail!(
+            "Failed listening on port {}, no address could be bound",
+            port
+        );
+    }
+    Ok(listeners)
+}
+
+fn bind(addr: SocketAddr) -> std::io::Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, None)?;
+    // like redis, IPv6 sockets leave IPv4 to their own listeners so both can share a port
+    if addr.is_ipv6() {
+        socket.set_only_v6(true)?;
+    }
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(TCP_BACKLOG)?;
+    TcpListener::from_std(socket.into())
+}
+
+fn bind_unix(path: &Path, perm: u32) -> std::io::Result<UnixListener> {
+    // a socket file left behind by a previous run would fail the bind
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let listener = UnixListener::bind(path)?;
+    if perm != 0 {
+        fs::set_permissions(path, fs::Permissions::from_mode(perm))?;
+    }
+    Ok(listener)
+}
+
+/// Accepts connections on `listener`, serving each on its own task. A connection that fails
+/// before it is served is logged and dropped, the listener carries on.
+pub async fn serve(listener: Listener, backend: Backend) {
+    loop {
+        let accepted = match &listener {
+            Listener::Tcp(listener) => accept_tcp(listener, &backend)
+                .await
+                .map(|(stream, _)| spawn_handler(stream, backend.clone())),
+            Listener::Tls(listener) => accept_tcp(listener, &backend)
+                .await
+                .map(|(stream, addr)| spawn_tls_handler(stream, addr, backend.clone())),
+            Listener::Unix(listener) => listener
+                .accept()
+                .await
+                .map(|(stream, _)| spawn_handler(stream, backend.clone())),
+        };
+        if let Err(e) = accepted {
+            warn!("Accepting client connection: {}", e);
+            // out of file descriptors, accepting fails right away again until some are closed
+            if matches!(e.raw_os_error(), Some(libc::EMFILE | libc::ENFILE)) {
+                tokio::time::sleep(ACCEPT_BACKOFF).await;
+            }
+        }
+    }
+}
+
+async fn accept_tcp(
+    listener: &TcpListener,
+    backend: &Backend,
+) -> std::io::Result<(TcpStream, SocketAddr)> {
+    let (stream, addr) = listener.accept().await?;
+    configure_tcp(&stream, backend)?;
+    Ok((stream, addr))
+}
+
+fn spawn_tls_handler(stream: TcpStream, addr: SocketAddr, backend: Backend) {
+    let Some(tls) = backend.tls() else {
+        return warn!(
+            "Dropping TLS connection from {}, TLS is not configured",
+            addr
+        );
+    };
+    // a slow handshake must not hold up the clients queued behind it, nor hold its socket
+    // forever, unregistered clients being out of reach of maxclients and timeout
+    tokio::spawn(async move {
+        let handshake = TlsAcceptor::from(tls).accept(stream);
+        match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, handshake).await {
+            Ok(Ok(stream)) => spawn_handler(stream, backend),
+            Ok(Err(e)) => warn!(
+                "Error accepting a client TLS connection from {}: {}",
+                addr, e
+            ),
+            Err(_) => warn!("Timed out on the TLS handshake of {}", addr),
+        }
+    });
+}
+
+/// Closes every client once it is done with the command it runs, waiting up to `timeout` for
+/// them to go, then removes the unix socket. The listeners are to be stopped first, so that no
+/// client connects meanwhile.
+pub async fn shutdown(backend: &Backend, timeout: Duration) {
+    let clients = backend.clients();
+    clients.kill(|_| true);
+    if tokio::time::timeout(timeout, clients.wait_empty())
+        .await
+        .is_err()
+    {
+        warn!("Exiting with {} clients still connected", clients.len());
+    }
+
+    let unixsocket = backend.config().unixsocket.clone();
+    if !unixsocket.is_empty() {
+        info!("Removing the unix socket file.");
+        if let Err(e) = fs::remove_file(&unixsocket) {
+            warn!("Error removing the unix socket file: {}", e);
+        }
+    }
+    info!("r-redis is now ready to exit, bye bye...");
+}
+
+fn configure_tcp(stream: &TcpStream, backend: &Backend) -> std::io::Result<()> {
+    // replies are already batched per read, like redis don't hold them back any longer
+    stream.set_nodelay(true)?;
+    let keepalive = backend.config().tcp_keepalive;
+    if keepalive > 0 {
+        // like redis, probe a silent peer every third of the keepalive period once it is over
+        let time = Duration::from_secs(keepalive);
+        let keepalive = TcpKeepalive::new()
+            .with_time(time)
+            .with_interval((time / 3).max(Duration::from_secs(1)));
+        SockRef::from(stream).set_tcp_keepalive(&keepalive)?;
+    }
+    Ok(())
+}
+
+fn spawn_handler<S: Connection>(stream: S, backend: Backend) {
+    let addr = match stream.client_addr() {
+        Ok(addr) => addr,
+        Err(e) => return warn!("Dropping connection with no address: {}", e),
+    };
+    tokio::spawn(async move {
+        match stream_handler(stream, backend).await {
+            Ok(_) => info!("Connection from {} closed", addr),
+            Err(e) => warn!("Connection from {} error: {:?}", addr, e),
+        }
+    });
+}
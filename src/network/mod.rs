@@ -0,0 +1,820 @@
+mod connection;
+mod inline;
+mod listener;
+mod session;
+pub mod tls;
+
+use crate::backend::ClientHandle;
+use crate::cmd::{self, Command, CommandError, CommandSpec, ContextExecutor};
+use crate::{
+    extract_end_and_length, Backend, BulkString, RespArray, RespDecode, RespEncode, RespError,
+    RespFrame, RespLimits, RespProtocol, SimpleError,
+};
+use anyhow::Result;
+use connection::Metered;
+use futures::SinkExt;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_stream::StreamExt;
+use tokio_util::codec::{Decoder, Encoder, Framed};
+use tracing::{debug, info, trace};
+
+pub use connection::{ClientAddr, Connection};
+pub(crate) use inline::split_args;
+pub use listener::{listen, serve, shutdown, Listener};
+pub use session::{ReplyMode, Session};
+
+pub async fn stream_handler<S: Connection>(stream: S, backend: Backend) -> Result<()> {
+    let addr = stream.client_addr()?;
+    let stream = Metered::new(stream, backend.clone());
+    let mut framed = Framed::new(stream, RespFrameCodec::default());
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    // the slot is taken as the client registers, clients connecting together can't overshoot
+    let Some(session) = Session::accept(backend.clone(), sender) else {
+        info!("Rejecting client, max number of clients reached");
+        backend.stats().rejected_connections.incr();
+        let err = SimpleError::new("ERR max number of clients reached");
+        framed.send(err.into()).await?;
+        return Ok(());
+    };
+    backend.stats().connections_received.incr();
+    let mut session = session.with_addr(addr);
+    let client = session.client().clone();
+    // registered by now, a client that connected as the server shuts down is either closed
+    // along with the others or sees the shutdown here
+    if session.backend().shutdown().is_requested() {
+        return Ok(());
+    }
+    framed.codec_mut().limits = session.backend().resp_limits();
+    session.sync_client();
+    loop {
+        // like redis, subscribed clients are never closed for being idle, they only listen
+        let idle_timeout = match session.is_subscribed() {
+            true => 0,
+            false => session.backend().config().timeout,
+        };
+        tokio::select! {
+            // a killed client is closed before it gets to send anything else
+            biased;
+            _ = client.killed() => {
+                info!("Client {} killed", session.id());
+                return Ok(());
+            }
+            req = framed.next() => match req {
+                Some(Ok(req)) => {
+                    // answer every request a pipelining client already sent, then write the
+                    // replies out together
+                    let mut req = Some(req);
+                    while let Some(frame) = req {
+                        feed_replies(&mut framed, &mut session, frame).await?;
+                        if client.is_killed() {
+                            break;
+                        }
+                        req = match buffered_request(&mut framed) {
+                            Ok(req) => req,
+                            Err(e) => return close_with_error(&mut framed, session.backend(), e).await,
+                        };
+                    }
+                    record_buffers(&framed, &receiver, &session);
+                    if !client.check_output(framed.write_buffer().len()) {
+                        info!("Closing client {} over its output buffer limit", session.id());
+                        return Ok(());
+                    }
+                    if !flush_unless_killed(&mut framed, &client).await? {
+                        info!("Client {} killed", session.id());
+                        return Ok(());
+                    }
+                }
+                Some(Err(e)) => return close_with_error(&mut framed, session.backend(), e).await,
+                None => {
+                    info!("Connection closed");
+                    return Ok(());
+                }
+            },
+            Some(message) = receiver.recv() => {
+                // encoded straight into the write buffer to learn how much of the queue it was
+                let mut codec = framed.codec().clone();
+                let buffered = framed.write_buffer().len();
+                codec.encode(message, framed.write_buffer_mut())?;
+                client.dequeue_output(framed.write_buffer().len() - buffered);
+                if !flush_unless_killed(&mut framed, &client).await? {
+                    info!("Client {} killed", session.id());
+                    return Ok(());
+                }
+            }
+            _ = tokio::time::sleep(Duration::from_secs(idle_timeout)), if idle_timeout > 0 => {
+                info!("Closing idle client {}", session.id());
+                return Ok(());
+            }
+        }
+    }
+}
+
+// writes the buffered output out, giving up if the client is killed while the socket is full,
+// as a client that does not read its replies would otherwise never notice
+async fn flush_unless_killed<S: Connection>(
+    framed: &mut Framed<S, RespFrameCodec>,
+    client: &ClientHandle,
+) -> Result<bool> {
+    tokio::select! {
+        biased;
+        res = framed.flush() => {
+            res?;
+            // the replies written out no longer count against the limit
+            Ok(client.check_output(framed.write_buffer().len()))
+        }
+        _ = client.killed() => Ok(false),
+    }
+}
+
+async fn feed_replies<S: Connection>(
+    framed: &mut Framed<S, RespFrameCodec>,
+    session: &mut Session,
+    frame: RespFrame,
+) -> Result<()> {
+    trace!(
+        "Received request: {:?}",
+        String::from_utf8_lossy(frame.encode().as_slice())
+    );
+    let resp = request_handler(RedisRequest { frame }, session).await?;
+    // HELLO may have switched protocols, its reply already uses the new one
+    framed.codec_mut().protocol = session.protocol();
+    framed.codec_mut().limits = session.backend().resp_limits();
+    for frame in resp.frames {
+        trace!(
+            "Send response: {:?}",
+            String::from_utf8_lossy(frame.encode().as_slice())
+        );
+        framed.feed(frame).await?;
+    }
+    Ok(())
+}
+
+// the sizes CLIENT LIST reports, as they are before the replies are written out
+fn record_buffers<S: Connection>(
+    framed: &Framed<S, RespFrameCodec>,
+    receiver: &mpsc::UnboundedReceiver<RespFrame>,
+    session: &Session,
+) {
+    let read = framed.read_buffer();
+    let (qbuf, qbuf_free) = (read.len(), read.capacity() - read.len());
+    let (obl, oll) = (framed.write_buffer().len(), receiver.len());
+    let queued = session.client().queued_output();
+    session.client().update(|info| {
+        info.qbuf = qbuf;
+        info.qbuf_free = qbuf_free;
+        info.obl = obl;
+        info.oll = oll;
+        info.omem = obl + queued;
+    });
+}
+
+// the next request if it is already complete in the read buffer, without reading the socket
+fn buffered_request<S: Connection>(
+    framed: &mut Framed<S, RespFrameCodec>,
+) -> Result<Option<RespFrame>> {
+    // the codec is taken out for the while, as it keeps track of a partially read request
+    let mut codec = std::mem::take(framed.codec_mut());
+    let ret = codec.decode(framed.read_buffer_mut());
+    *framed.codec_mut() = codec;
+    ret
+}
+
+// like redis, tell the client what was wrong with its input before hanging up, after the
+// replies to the requests that came before it
+async fn close_with_error<S: Connection>(
+    framed: &mut Framed<S, RespFrameCodec>,
+    backend: &Backend,
+    e: anyhow::Error,
+) -> Result<()> {
+    if let Some(e) = e.downcast_ref::<RespError>() {
+        if matches!(e, RespError::LimitExceeded(_)) {
+            backend.stats().query_buffer_limit_disconnections.incr();
+        }
+        let e = protocol_error(e);
+        backend.stats().record_error(&e);
+        framed.feed(e.into()).await?;
+    }
+    framed.flush().await?;
+    Err(e)
+}
+
+async fn request_handler(req: RedisRequest, session: &mut Session) -> Result<RedisResponse> {
+    // a failed command is answered with an error reply, the connection stays usable
+    let frames = match execute_request(req.frame, session).await {
+        Ok(frames) => frames,
+        Err(e) => {
+            let e = SimpleError::from(e);
+            session.backend().stats().record_error(&e);
+            vec![e.into()]
+        }
+    };
+    session.sync_client();
+    let frames = session.filter_replies(frames);
+    Ok(RedisResponse { frames })
+}
+
+async fn execute_request(
+    frame: RespFrame,
+    session: &mut Session,
+) -> Result<Vec<RespFrame>, CommandError> {
+    let RespFrame::Array(args) = &frame else {
+        return Err(CommandError::InvalidFrame("Invalid frame type".to_string()));
+    };
+    // like redis, unknown commands and wrong arities are reported first, then missing
+    // authentication and permissions, and only then errors in the arguments
+    let spec = cmd::resolve(args)?;
+    session.record_command(spec);
+    let backend = session.backend().clone();
+    let cmd = match check_request(args, spec, session).and_then(|()| Command::try_from(frame)) {
+        Ok(cmd) => cmd,
+        Err(e) => {
+            backend.stats().record_rejected(spec.name);
+            return Err(e);
+        }
+    };
+    // commands that may change the dataset wait out CLIENT PAUSE WRITE, every one waits out ALL
+    let write = spec.flags.contains(&"write") || spec.flags.contains(&"may_replicate");
+    backend.clients().wait_unpaused(write).await;
+    // RESP3 clients can keep issuing commands while subscribed, pushes are told apart by type
+    if session.is_subscribed()
+        && session.protocol() == RespProtocol::Resp2
+        && !cmd.allowed_when_subscribed()
+    {
+        backend.stats().record_rejected(spec.name);
+        return Err(subscribed_context_error(&cmd));
+    }
+    debug!("Execute command: {:?}", cmd);
+    let start = Instant::now();
+    let ret = cmd.execute(session).await;
+    backend
+        .stats()
+        .record_call(spec.name, start.elapsed(), ret.is_err());
+    ret
+}
+
+// whether the client may run the command at all
+fn check_request(
+    args: &RespArray,
+    spec: &CommandSpec,
+    session: &Session,
+) -> Result<(), CommandError> {
+    if session.auth_required() && !spec.flags.contains(&"no_auth") {
+        return Err(CommandError::NoAuth);
+    }
+    session.check_permissions(spec, args)?;
+    // a function past busy-reply-threshold leaves room only for commands that can stop it
+    if session.backend().functions().busy() && !spec.flags.contains(&"allow_busy") {
+        return Err(CommandError::Busy);
+    }
+    Ok(())
+}
+
+fn subscribed_context_error(cmd: &Command) -> CommandError {
+    CommandError::InvalidCmd(format!(
+        "Can't execute '{}': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed in this context",
+        cmd.name()
+    ))
+}
+
+fn protocol_error(e: &RespError) -> SimpleError {
+    match e {
+        RespError::LimitExceeded(_)
+        | RespError::InvalidInline(_)
+        | RespError::InvalidLength(_)
+        | RespError::ExpectedBulk(_) => SimpleError::new(format!("ERR {}", e)),
+        e => SimpleError::new(format!("ERR Protocol error: {}", e)),
+    }
+}
+
+#[derive(Debug)]
+struct RedisRequest {
+    frame: RespFrame,
+}
+
+#[derive(Debug)]
+struct RedisResponse {
+    frames: Vec<RespFrame>,
+}
+
+// like redis, the most arguments a request may have, whatever its arguments add up to
+const MAX_MULTIBULK_LEN: usize = i32::MAX as usize;
+
+#[derive(Debug, Default, Clone)]
+struct RespFrameCodec {
+    protocol: RespProtocol,
+    limits: RespLimits,
+    // how much of the multibulk at the start of the read buffer is known to be valid, so that
+    // a request arriving over many reads is walked once instead of on every read
+    multibulk: Option<PartialMultibulk>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PartialMultibulk {
+    // the length of the header and of the elements that are complete
+    validated: usize,
+    // the elements yet to arrive
+    remaining: usize,
+}
+
+impl RespFrameCodec {
+    fn decode_multibulk(&mut self, src: &mut bytes::BytesMut) -> Result<RespFrame, RespError> {
+        let mut multibulk = match self.multibulk.take() {
+            Some(multibulk) => multibulk,
+            None => {
+                let (end, len) = extract_end_and_length(src, *RespArray::PREFIX)?;
+                // null and empty multibulks are complete with their header
+                if len <= 0 {
+                    return RespFrame::decode_frame(src);
+                }
+                if len as usize > MAX_MULTIBULK_LEN {
+                    return Err(RespError::InvalidLength("invalid multibulk length".into()));
+                }
+                PartialMultibulk {
+                    validated: end + 2,
+                    remaining: len as usize,
+                }
+            }
+        };
+        while multibulk.remaining > 0 {
+            match request_bulk_length(&src[multibulk.validated..], &self.limits) {
+                Ok(len) => {
+                    multibulk.validated += len;
+                    multibulk.remaining -= 1;
+                }
+                Err(e) => {
+                    if e == RespError::NotComplete {
+                        self.multibulk = Some(multibulk);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        RespFrame::decode_frame(src)
+    }
+}
+
+// like redis, the arguments of a request can only be bulk strings, and none of them null
+fn request_bulk_length(buf: &[u8], limits: &RespLimits) -> Result<usize, RespError> {
+    match buf.first() {
+        None => return Err(RespError::NotComplete),
+        Some(BulkString::PREFIX) => {}
+        Some(&prefix) => return Err(RespError::ExpectedBulk(prefix as char)),
+    }
+    if extract_end_and_length(buf, *BulkString::PREFIX)?.1 < 0 {
+        return Err(RespError::InvalidLength("invalid bulk length".into()));
+    }
+    BulkString::expect_length(buf, limits)
+}
+
+impl Encoder<RespFrame> for RespFrameCodec {
+    type Error = anyhow::Error;
+
+    fn encode(&mut self, item: RespFrame, dst: &mut bytes::BytesMut) -> Result<()> {
+        let item = match self.protocol {
+            RespProtocol::Resp2 => item.into_resp2(),
+            RespProtocol::Resp3 => item,
+        };
+        item.encode_to(dst);
+        Ok(())
+    }
+}
+
+impl Decoder for RespFrameCodec {
+    type Item = RespFrame;
+    type Error = anyhow::Error;
+
+    fn decode(&mut self, src: &mut bytes::BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            // like redis, anything that does not start as a multibulk is an inline command
+            let ret = match src.first() {
+                None => return Ok(None),
+                Some(RespArray::PREFIX) => self.decode_multibulk(src),
+                Some(_) => inline::decode_inline(src).map(RespFrame::from),
+            };
+            match ret {
+                // blank lines and empty multibulks carry no command
+                Ok(RespFrame::Array(frame)) if frame.is_empty() => continue,
+                Ok(frame) => return Ok(Some(frame)),
+                Err(RespError::NotComplete) if src.len() > self.limits.max_query_buffer => {
+                    return Err(
+                        RespError::LimitExceeded("query buffer limit exceeded".into()).into(),
+                    )
+                }
+                Err(RespError::NotComplete) | Err(RespError::Empty) => return Ok(None),
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BytesMut;
+    use tokio::net::TcpStream;
+
+    #[test]
+    fn test_codec_decode_inline_and_multibulk() -> Result<()> {
+        let mut codec = RespFrameCodec::default();
+        let mut buf = BytesMut::from(&b"\r\n\r\nget a\r\n*0\r\n*1\r\n$4\r\nping\r\nECH"[..]);
+
+        let get: RespFrame = RespArray::new(vec![
+            BulkString::from("get").into(),
+            BulkString::from("a").into(),
+        ])
+        .into();
+        assert_eq!(codec.decode(&mut buf)?, Some(get));
+        let ping: RespFrame = RespArray::new(vec![BulkString::from("ping").into()]).into();
+        assert_eq!(codec.decode(&mut buf)?, Some(ping));
+        assert_eq!(codec.decode(&mut buf)?, None);
+        assert_eq!(&buf[..], b"ECH");
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_decode_multibulk_in_pieces() -> Result<()> {
+        let mut codec = RespFrameCodec::default();
+        let mut buf = BytesMut::from(&b"*3\r\n$3\r\nset\r\n$1\r\na\r\n$5\r\nhel"[..]);
+        assert_eq!(codec.decode(&mut buf)?, None);
+        // the complete elements are not walked again on the next read
+        assert_eq!(
+            codec.multibulk,
+            Some(PartialMultibulk {
+                validated: 20,
+                remaining: 1
+            })
+        );
+        buf.extend_from_slice(b"lo\r\n*1\r\n$4\r\nping\r\n");
+        let set: RespFrame = RespArray::new(vec![
+            BulkString::from("set").into(),
+            BulkString::from("a").into(),
+            BulkString::from("hello").into(),
+        ])
+        .into();
+        assert_eq!(codec.decode(&mut buf)?, Some(set));
+        assert_eq!(codec.multibulk, None);
+        let ping: RespFrame = RespArray::new(vec![BulkString::from("ping").into()]).into();
+        assert_eq!(codec.decode(&mut buf)?, Some(ping));
+
+        // a bad element is only found once it arrives
+        let mut buf = BytesMut::from(&b"*2\r\n$4\r\necho\r\n"[..]);
+        assert_eq!(codec.decode(&mut buf)?, None);
+        buf.extend_from_slice(b"$x\r\n");
+        assert!(codec.decode(&mut buf).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_decode_multibulk_of_bulks_only() {
+        let mut codec = RespFrameCodec::default();
+        let mut buf = BytesMut::from(&b"*2\r\n$4\r\necho\r\n:1\r\n"[..]);
+        let e = codec.decode(&mut buf).unwrap_err();
+        assert_eq!(
+            protocol_error(e.downcast_ref().unwrap()),
+            SimpleError::new("ERR Protocol error: expected '$', got ':'")
+        );
+        let mut codec = RespFrameCodec::default();
+        let mut buf = BytesMut::from(&b"*2\r\n$4\r\necho\r\n$-1\r\n"[..]);
+        let e = codec.decode(&mut buf).unwrap_err();
+        assert_eq!(
+            protocol_error(e.downcast_ref().unwrap()),
+            SimpleError::new("ERR Protocol error: invalid bulk length")
+        );
+        let mut codec = RespFrameCodec::default();
+        let mut buf = BytesMut::from(&b"*2147483648\r\n"[..]);
+        let e = codec.decode(&mut buf).unwrap_err();
+        assert_eq!(
+            protocol_error(e.downcast_ref().unwrap()),
+            SimpleError::new("ERR Protocol error: invalid multibulk length")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pipelined_replies_before_protocol_error() -> Result<()> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await?;
+            stream_handler(stream, Backend::new()).await
+        });
+
+        let mut client = TcpStream::connect(addr).await?;
+        client
+            .write_all(b"set a 1\r\nget a\r\n*1\r\n$4\r\necho\r\n*1\r\n$x\r\n")
+            .await?;
+        let mut replies = Vec::new();
+        client.read_to_end(&mut replies).await?;
+        assert_eq!(
+            String::from_utf8_lossy(&replies),
+            "+OK\r\n$1\r\n1\r\n-ERR wrong number of arguments for 'echo' command\r\n-ERR Protocol error: invalid bulk length\r\n"
+        );
+        assert!(server.await?.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_kill_and_pause() -> Result<()> {
+        use std::time::{Duration, Instant};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(serve(Listener::Tcp(listener), Backend::new()));
+
+        let mut admin = TcpStream::connect(addr).await?;
+        let mut victim = TcpStream::connect(addr).await?;
+        victim.write_all(b"client setname victim\r\n").await?;
+        let mut reply = [0; 5];
+        victim.read_exact(&mut reply).await?;
+
+        // writes wait out the pause, the client pausing them included
+        admin
+            .write_all(b"client pause 100 write\r\nget a\r\nset a 1\r\n")
+            .await?;
+        let start = Instant::now();
+        let mut replies = [0; 15];
+        admin.read_exact(&mut replies).await?;
+        assert_eq!(&replies, b"+OK\r\n$-1\r\n+OK\r\n");
+        assert!(start.elapsed() >= Duration::from_millis(90));
+
+        admin
+            .write_all(b"client reply skip\r\nclient list\r\nclient kill id 2\r\n")
+            .await?;
+        // the replies to CLIENT REPLY SKIP and CLIENT LIST are dropped
+        let mut replies = [0; 5];
+        admin.read_exact(&mut replies).await?;
+        assert_eq!(&replies, b":+1\r\n");
+
+        let mut rest = vec![];
+        victim.read_to_end(&mut rest).await?;
+        assert!(rest.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_limits() -> Result<()> {
+        use crate::config::{ClientOutputBufferLimits, OutputBufferLimit, ServerConfig};
+        use std::time::{Duration, Instant};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let backend = Backend::with_config(ServerConfig {
+            maxclients: 2,
+            timeout: 1,
+            client_output_buffer_limit: ClientOutputBufferLimits {
+                pubsub: OutputBufferLimit {
+                    hard: 64,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(serve(Listener::Tcp(listener), backend));
+
+        let mut subscriber = TcpStream::connect(addr).await?;
+        subscriber.write_all(b"subscribe news\r\n").await?;
+        let mut reply = [0; 34];
+        subscriber.read_exact(&mut reply).await?;
+        let start = Instant::now();
+        let mut idle = TcpStream::connect(addr).await?;
+        idle.write_all(b"get a\r\n").await?;
+        let mut reply = [0; 5];
+        idle.read_exact(&mut reply).await?;
+
+        let mut rejected = TcpStream::connect(addr).await?;
+        let mut reply = vec![];
+        rejected.read_to_end(&mut reply).await?;
+        assert_eq!(reply, b"-ERR max number of clients reached\r\n");
+
+        let mut rest = vec![];
+        idle.read_to_end(&mut rest).await?;
+        assert!(rest.is_empty());
+        assert!(start.elapsed() >= Duration::from_millis(900));
+
+        // the subscriber was idle just as long, but subscribed clients are not closed for it
+
+        let mut publisher = TcpStream::connect(addr).await?;
+        publisher.write_all(b"publish news hi\r\n").await?;
+        let mut message = [0; 35];
+        subscriber.read_exact(&mut message).await?;
+        assert_eq!(
+            &message,
+            b"*3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$2\r\nhi\r\n"
+        );
+
+        // a message past the output buffer limit disconnects the subscriber instead
+        let big = format!("publish news {}\r\n", "x".repeat(64));
+        publisher.write_all(big.as_bytes()).await?;
+        let mut rest = vec![];
+        subscriber.read_to_end(&mut rest).await?;
+        assert!(rest.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ping_reset_quit() -> Result<()> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(serve(Listener::Tcp(listener), Backend::new()));
+
+        let mut stream = TcpStream::connect(addr).await?;
+        stream.write_all(b"subscribe news\r\n").await?;
+        let mut reply = [0; 34];
+        stream.read_exact(&mut reply).await?;
+        // PING and RESET are allowed while subscribed, the rest only once RESET left the channel
+        stream
+            .write_all(b"ping\r\nget a\r\nhello 2\r\nreset\r\nget a\r\nquit\r\nget a\r\n")
+            .await?;
+        let mut replies = vec![];
+        stream.read_to_end(&mut replies).await?;
+        let replies = String::from_utf8(replies)?;
+        let mut replies = replies.split_inclusive("\r\n");
+        assert_eq!(
+            replies.by_ref().take(5).collect::<String>(),
+            "*2\r\n$4\r\npong\r\n$0\r\n\r\n"
+        );
+        assert!(replies
+            .next()
+            .unwrap()
+            .starts_with("-ERR Can't execute 'get'"));
+        assert!(replies
+            .next()
+            .unwrap()
+            .starts_with("-ERR Can't execute 'hello'"));
+        assert_eq!(replies.collect::<String>(), "+RESET\r\n$-1\r\n+OK\r\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_shutdown() -> Result<()> {
+        use std::time::Duration;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let backend = Backend::new();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = tokio::spawn(serve(Listener::Tcp(listener), backend.clone()));
+
+        let mut idle = TcpStream::connect(addr).await?;
+        idle.write_all(b"get a\r\n").await?;
+        let mut reply = [0; 5];
+        idle.read_exact(&mut reply).await?;
+        let mut admin = TcpStream::connect(addr).await?;
+        // the reply to a command sent along with SHUTDOWN is still written out
+        admin.write_all(b"get a\r\nshutdown nosave\r\n").await?;
+        backend.shutdown().requested().await;
+        server.abort();
+        shutdown(&backend, Duration::from_secs(5)).await;
+        assert!(backend.clients().is_empty());
+
+        let mut reply = vec![];
+        admin.read_to_end(&mut reply).await?;
+        assert_eq!(reply, b"$-1\r\n");
+        let mut rest = vec![];
+        idle.read_to_end(&mut rest).await?;
+        assert!(rest.is_empty());
+        Ok(())
+    }
+
+    async fn run(session: &mut Session, args: &[&str]) -> Result<Vec<RespFrame>, CommandError> {
+        let args = args.iter().map(|arg| BulkString::from(*arg).into());
+        let frame = RespArray::new(args.collect::<Vec<_>>()).into();
+        execute_request(frame, session).await
+    }
+
+    #[tokio::test]
+    async fn test_auth_required() -> Result<()> {
+        use crate::config::ServerConfig;
+
+        let backend = Backend::with_config(ServerConfig {
+            requirepass: "secret".into(),
+            ..Default::default()
+        });
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(backend.clone(), tx);
+        assert_eq!(
+            run(&mut session, &["get", "a"]).await,
+            Err(CommandError::NoAuth)
+        );
+        assert_eq!(
+            run(&mut session, &["auth", "nope"]).await,
+            Err(CommandError::WrongPass)
+        );
+        let err = run(&mut session, &["hello", "3"]).await.unwrap_err();
+        assert!(err.to_string().starts_with("NOAUTH HELLO must be called"));
+        run(&mut session, &["hello", "3", "auth", "default", "secret"]).await?;
+        run(&mut session, &["get", "a"]).await?;
+
+        // clearing the password lets every connection in
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut other = Session::new(backend.clone(), tx);
+        run(&mut session, &["config", "set", "requirepass", ""]).await?;
+        run(&mut other, &["get", "a"]).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_acl_permissions() -> Result<()> {
+        let backend = Backend::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut admin = Session::new(backend.clone(), tx);
+        let rules = [
+            "setuser",
+            "alice",
+            "on",
+            ">pw",
+            "+get",
+            "+acl|whoami",
+            "~public:*",
+        ];
+        run(&mut admin, &[&["acl"][..], &rules].concat()).await?;
+
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(backend.clone(), tx);
+        run(&mut session, &["auth", "alice", "pw"]).await?;
+        assert_eq!(
+            run(&mut session, &["acl", "whoami"]).await?,
+            vec![BulkString::from("alice").into()]
+        );
+        run(&mut session, &["get", "public:a"]).await?;
+        assert_eq!(
+            run(&mut session, &["get", "secret"]).await,
+            Err(CommandError::NoPerm(
+                "No permissions to access a key".to_string()
+            ))
+        );
+        assert_eq!(
+            run(&mut session, &["set", "public:a", "1"]).await,
+            Err(CommandError::NoPerm(
+                "User alice has no permissions to run the 'set' command".to_string()
+            ))
+        );
+        assert!(run(&mut session, &["acl", "log"]).await.is_err());
+
+        let ret = run(&mut admin, &["acl", "log"]).await?;
+        let [RespFrame::Array(entries)] = &ret[..] else {
+            panic!("Expected Array");
+        };
+        let objects: Vec<_> = entries
+            .iter()
+            .map(|entry| match entry {
+                RespFrame::Map(map) => map.get(&"object".into()).cloned(),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            objects,
+            vec![
+                Some(BulkString::from("acl|log").into()),
+                Some(BulkString::from("set").into()),
+                Some(BulkString::from("secret").into()),
+            ]
+        );
+
+        // deleted users may no longer run anything
+        run(&mut admin, &["acl", "deluser", "alice"]).await?;
+        assert!(session.client().is_killed());
+        assert!(!admin.client().is_killed());
+        assert!(run(&mut session, &["get", "public:a"]).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unix_socket() -> Result<()> {
+        use crate::config::ServerConfig;
+        use std::os::unix::fs::PermissionsExt;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let path = std::env::temp_dir().join(format!("r-redis-{}.sock", std::process::id()));
+        let config = ServerConfig {
+            port: 0,
+            unixsocket: path.display().to_string(),
+            unixsocketperm: 0o700,
+            ..Default::default()
+        };
+        let mut listeners = listen(&config)?;
+        assert!(matches!(listeners[..], [Listener::Unix(_)]));
+        assert_eq!(
+            std::fs::metadata(&path)?.permissions().mode() & 0o777,
+            0o700
+        );
+        tokio::spawn(serve(listeners.remove(0), Backend::new()));
+
+        let mut client = tokio::net::UnixStream::connect(&path).await?;
+        client.write_all(b"set a 1\r\nget a\r\n").await?;
+        let mut replies = [0; 12];
+        client.read_exact(&mut replies).await?;
+        assert_eq!(&replies, b"+OK\r\n$1\r\n1\r\n");
+
+        assert_eq!(client.client_addr()?, ClientAddr::Unix { path: "".into() });
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
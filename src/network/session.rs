@@ -0,0 +1,435 @@
+use crate::acl::Denied;
+use crate::backend::ClientHandle;
+use crate::cmd::{CommandError, CommandSpec};
+use crate::network::ClientAddr;
+use crate::{Backend, ClientSender, RespArray, RespFrame, RespProtocol};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Per-connection state that outlives a single request.
+#[derive(Debug)]
+pub struct Session {
+    id: u64,
+    addr: Option<ClientAddr>,
+    backend: Backend,
+    sender: ClientSender,
+    protocol: RespProtocol,
+    // there is a single keyspace, the db is kept for commands that report it
+    db: usize,
+    name: Option<String>,
+    // the ACL user commands run as, authenticated or not
+    user: String,
+    authenticated: bool,
+    channels: BTreeSet<String>,
+    patterns: BTreeSet<String>,
+    shard_channels: BTreeSet<String>,
+    no_evict: bool,
+    reply: ReplyMode,
+    // set once CLIENT REPLY SKIP was answered, the reply to the command after it is dropped
+    skip_next_reply: bool,
+    // what CLIENT LIST shows of the session, kept up to date after every command
+    client: Arc<ClientHandle>,
+}
+
+/// Whether the client wants replies, as set by CLIENT REPLY.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyMode {
+    On,
+    Off,
+    /// Drops the reply to the next command only.
+    Skip,
+}
+
+impl Session {
+    pub fn new(backend: Backend, sender: UnboundedSender<RespFrame>) -> Self {
+        let id = backend.next_client_id();
+        let client = backend.clients().register(id);
+        Self::with_client(backend, sender, id, client)
+    }
+
+    /// A session for a new connection, or `None` when maxclients are connected already.
+    pub fn accept(backend: Backend, sender: UnboundedSender<RespFrame>) -> Option<Self> {
+        let id = backend.next_client_id();
+        let maxclients = backend.config().maxclients;
+        let client = backend.clients().try_register(id, maxclients)?;
+        Some(Self::with_client(backend, sender, id, client))
+    }
+
+    fn with_client(
+        backend: Backend,
+        sender: UnboundedSender<RespFrame>,
+        id: u64,
+        client: Arc<ClientHandle>,
+    ) -> Self {
+        // connections made while no password is required stay authenticated once one is set
+        let authenticated = !backend.acl().auth_required();
+        Self {
+            id,
+            addr: None,
+            backend,
+            sender: ClientSender::new(sender, client.clone()),
+            protocol: RespProtocol::default(),
+            db: 0,
+            name: None,
+            user: "default".to_string(),
+            authenticated,
+            channels: BTreeSet::new(),
+            patterns: BTreeSet::new(),
+            shard_channels: BTreeSet::new(),
+            no_evict: false,
+            reply: ReplyMode::On,
+            skip_next_reply: false,
+            client,
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn with_addr(mut self, addr: ClientAddr) -> Self {
+        self.client.update(|info| info.addr = Some(addr.clone()));
+        self.addr = Some(addr);
+        self
+    }
+
+    /// Where the client connected from, unknown for sessions that are not backed by a socket.
+    pub fn addr(&self) -> Option<&ClientAddr> {
+        self.addr.as_ref()
+    }
+
+    pub fn backend(&self) -> &Backend {
+        &self.backend
+    }
+
+    pub fn protocol(&self) -> RespProtocol {
+        self.protocol
+    }
+
+    pub fn set_protocol(&mut self, protocol: RespProtocol) {
+        self.protocol = protocol;
+    }
+
+    pub fn db(&self) -> usize {
+        self.db
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    pub fn set_no_evict(&mut self, no_evict: bool) {
+        self.no_evict = no_evict;
+    }
+
+    pub fn set_reply(&mut self, reply: ReplyMode) {
+        self.reply = reply;
+    }
+
+    /// Drops the replies to a command the client asked not to get with CLIENT REPLY.
+    pub fn filter_replies(&mut self, frames: Vec<RespFrame>) -> Vec<RespFrame> {
+        let skip = std::mem::take(&mut self.skip_next_reply);
+        match self.reply {
+            ReplyMode::On if !skip => frames,
+            ReplyMode::On | ReplyMode::Off => vec![],
+            // CLIENT REPLY SKIP itself goes unanswered, and so does the command after it
+            ReplyMode::Skip => {
+                self.reply = ReplyMode::On;
+                self.skip_next_reply = true;
+                vec![]
+            }
+        }
+    }
+
+    /// The registry entry other clients see this one through.
+    pub fn client(&self) -> &Arc<ClientHandle> {
+        &self.client
+    }
+
+    /// Records the command the client is about to run, for CLIENT LIST.
+    pub fn record_command(&self, spec: &CommandSpec) {
+        self.client.update(|info| {
+            info.last_cmd = spec.name;
+            info.last_interaction = Instant::now();
+        });
+    }
+
+    /// Publishes the state of the session to the client registry, and applies the output buffer
+    /// limit of its client class.
+    pub fn sync_client(&self) {
+        let ssub = self.shard_subscriptions();
+        let mut client_type = "normal";
+        self.client.update(|info| {
+            info.name = self.name.clone();
+            info.db = self.db;
+            info.user.clone_from(&self.user);
+            info.protocol = self.protocol;
+            info.sub = self.channels.len();
+            info.psub = self.patterns.len();
+            info.ssub = ssub;
+            info.no_evict = self.no_evict;
+            client_type = info.client_type();
+        });
+        let limit = self
+            .backend
+            .config()
+            .client_output_buffer_limit
+            .for_type(client_type);
+        self.client.set_output_limit(limit);
+    }
+
+    /// Whether the client has to authenticate before it may run most commands.
+    pub fn auth_required(&self) -> bool {
+        !self.authenticated && self.backend.acl().auth_required()
+    }
+
+    /// Checks the credentials, authenticating the connection as `username` when they match.
+    pub fn authenticate(&mut self, username: &str, password: &str) -> bool {
+        if !self.backend.acl().authenticate(username, password) {
+            let addr = self.addr.as_ref().map(ClientAddr::addr).unwrap_or_default();
+            self.backend.auth_failures().record(username, &addr);
+            self.log_denied("auth", "AUTH", username);
+            return false;
+        }
+        self.user = username.to_string();
+        self.authenticated = true;
+        true
+    }
+
+    /// Checks a call of `spec` with `args` against the permissions of the user, logging it to
+    /// ACL LOG when it is refused.
+    pub fn check_permissions(
+        &self,
+        spec: &CommandSpec,
+        args: &RespArray,
+    ) -> Result<(), CommandError> {
+        let args: Vec<&[u8]> = args
+            .iter()
+            .map(|arg| match arg {
+                RespFrame::BulkString(arg) => &arg[..],
+                _ => &[],
+            })
+            .collect();
+        let denied = match self.backend.acl().check(&self.user, spec, &args) {
+            Ok(()) => return Ok(()),
+            Err(denied) => denied,
+        };
+        let (reason, object, error) = match denied {
+            Denied::Command(name) => (
+                "command",
+                name.to_string(),
+                format!(
+                    "User {} has no permissions to run the '{}' command",
+                    self.user, name
+                ),
+            ),
+            Denied::Key(key) => ("key", key, "No permissions to access a key".to_string()),
+            Denied::Channel(channel) => (
+                "channel",
+                channel,
+                "No permissions to access a channel".to_string(),
+            ),
+        };
+        self.log_denied(reason, &object, &self.user);
+        Err(CommandError::NoPerm(error))
+    }
+
+    fn log_denied(&self, reason: &'static str, object: &str, username: &str) {
+        let stats = self.backend.stats();
+        match reason {
+            "auth" => stats.acl_denied_auth.incr(),
+            "command" => stats.acl_denied_cmd.incr(),
+            "key" => stats.acl_denied_key.incr(),
+            _ => stats.acl_denied_channel.incr(),
+        }
+        let max_len = self.backend.config().acllog_max_len;
+        self.backend
+            .acl()
+            .log()
+            .add(reason, object, username, self.client_info(), max_len);
+    }
+
+    /// A description of the client in the format of CLIENT LIST.
+    pub fn client_info(&self) -> String {
+        self.sync_client();
+        self.client.info().to_string()
+    }
+
+    pub fn is_subscribed(&self) -> bool {
+        self.subscriptions() + self.shard_subscriptions() > 0
+    }
+
+    pub fn subscriptions(&self) -> usize {
+        self.channels.len() + self.patterns.len()
+    }
+
+    pub fn channels(&self) -> Vec<String> {
+        self.channels.iter().cloned().collect()
+    }
+
+    pub fn patterns(&self) -> Vec<String> {
+        self.patterns.iter().cloned().collect()
+    }
+
+    pub fn shard_subscriptions(&self) -> usize {
+        self.shard_channels.len()
+    }
+
+    pub fn shard_channels(&self) -> Vec<String> {
+        self.shard_channels.iter().cloned().collect()
+    }
+
+    /// Puts the connection back the way it was when it connected, as RESET does: out of every
+    /// subscription, on db 0 with RESP2, nameless, with replies on and evictable, and
+    /// authenticated as the default user only when no password is required.
+    pub fn reset(&mut self) {
+        self.unsubscribe_all();
+        self.protocol = RespProtocol::default();
+        self.db = 0;
+        self.name = None;
+        self.user = "default".to_string();
+        self.authenticated = !self.backend.acl().auth_required();
+        self.no_evict = false;
+        self.reply = ReplyMode::On;
+        self.skip_next_reply = false;
+    }
+
+    // leaves every channel, pattern and shard channel without telling the client
+    fn unsubscribe_all(&mut self) {
+        for channel in std::mem::take(&mut self.channels) {
+            self.backend.pubsub().unsubscribe(&channel, self.id);
+        }
+        for pattern in std::mem::take(&mut self.patterns) {
+            self.backend.pubsub().punsubscribe(&pattern, self.id);
+        }
+        for channel in std::mem::take(&mut self.shard_channels) {
+            self.backend.pubsub().sunsubscribe(&channel, self.id);
+        }
+    }
+
+    // returns the number of subscriptions after subscribing
+    pub fn subscribe(&mut self, channel: String) -> usize {
+        if self.channels.insert(channel.clone()) {
+            self.backend
+                .pubsub()
+                .subscribe(channel, self.id, self.sender.clone());
+        }
+        self.subscriptions()
+    }
+
+    // returns the number of subscriptions after unsubscribing
+    pub fn unsubscribe(&mut self, channel: &str) -> usize {
+        if self.channels.remove(channel) {
+            self.backend.pubsub().unsubscribe(channel, self.id);
+        }
+        self.subscriptions()
+    }
+
+    // returns the number of subscriptions after subscribing
+    pub fn psubscribe(&mut self, pattern: String) -> usize {
+        if self.patterns.insert(pattern.clone()) {
+            self.backend
+                .pubsub()
+                .psubscribe(pattern, self.id, self.sender.clone());
+        }
+        self.subscriptions()
+    }
+
+    // returns the number of subscriptions after unsubscribing
+    pub fn punsubscribe(&mut self, pattern: &str) -> usize {
+        if self.patterns.remove(pattern) {
+            self.backend.pubsub().punsubscribe(pattern, self.id);
+        }
+        self.subscriptions()
+    }
+
+    // returns the number of shard subscriptions after subscribing
+    pub fn ssubscribe(&mut self, channel: String) -> usize {
+        self.shard_channels.insert(channel.clone());
+        self.backend
+            .pubsub()
+            .ssubscribe(channel, self.id, self.sender.clone());
+        self.shard_subscriptions()
+    }
+
+    // returns the number of shard subscriptions after unsubscribing
+    pub fn sunsubscribe(&mut self, channel: &str) -> usize {
+        if self.shard_channels.remove(channel) {
+            self.backend.pubsub().sunsubscribe(channel, self.id);
+        }
+        self.shard_subscriptions()
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.backend.clients().unregister(self.id);
+        self.unsubscribe_all();
+        if self.client.is_over_output_limit() {
+            self.backend
+                .stats()
+                .output_buffer_limit_disconnections
+                .incr();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_session_subscribe() {
+        let backend = Backend::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(backend.clone(), tx);
+        assert!(!session.is_subscribed());
+
+        assert_eq!(session.subscribe("news".into()), 1);
+        assert_eq!(session.subscribe("news".into()), 1);
+        assert_eq!(session.subscribe("sports".into()), 2);
+        assert_eq!(backend.pubsub().numsub("news"), 1);
+
+        assert_eq!(session.unsubscribe("news"), 1);
+        assert_eq!(backend.pubsub().numsub("news"), 0);
+
+        assert_eq!(session.psubscribe("events.*".into()), 2);
+        assert_eq!(backend.pubsub().numpat(), 1);
+        assert_eq!(session.punsubscribe("events.*"), 1);
+        assert_eq!(session.psubscribe("events.*".into()), 2);
+
+        drop(session);
+        assert_eq!(backend.pubsub().numsub("sports"), 0);
+        assert_eq!(backend.pubsub().numpat(), 0);
+    }
+
+    #[test]
+    fn test_session_ssubscribe() {
+        let backend = Backend::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(backend.clone(), tx);
+
+        assert_eq!(session.ssubscribe("{user}.orders".into()), 1);
+        assert_eq!(session.ssubscribe("news".into()), 2);
+        assert_eq!(session.subscriptions(), 0);
+        assert!(session.is_subscribed());
+
+        assert_eq!(session.ssubscribe("{user}.orders".into()), 2);
+        assert_eq!(session.shard_channels(), ["news", "{user}.orders"]);
+
+        assert_eq!(session.sunsubscribe("news"), 1);
+        drop(session);
+        assert_eq!(backend.pubsub().shard_numsub("{user}.orders"), 0);
+    }
+}
@@ -0,0 +1,254 @@
+use crate::config::{ServerConfig, TlsAuthClients};
+use crate::network::{ClientAddr, Connection};
+use anyhow::{bail, Context, Result};
+use std::io;
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::{self, RootCertStore};
+use tokio_rustls::server::TlsStream;
+
+/// Builds the TLS setup of the `tls-*` parameters, reading the certificates and key from disk.
+/// Called at startup and again whenever CONFIG SET changes one of them.
+pub fn load(config: &ServerConfig) -> Result<Arc<rustls::ServerConfig>> {
+    if config.tls_cert_file.is_empty() || config.tls_key_file.is_empty() {
+        bail!("Both tls-cert-file and tls-key-file must be specified");
+    }
+    let certs = load_certs(&config.tls_cert_file)?;
+    let key = PrivateKeyDer::from_pem_file(&config.tls_key_file)
+        .with_context(|| format!("Failed to load private key: {}", config.tls_key_file))?;
+
+    let builder = rustls::ServerConfig::builder();
+    let builder = match config.tls_auth_clients {
+        TlsAuthClients::No => builder.with_no_client_auth(),
+        auth => {
+            if config.tls_ca_cert_file.is_empty() {
+                bail!("tls-ca-cert-file must be specified to authenticate clients");
+            }
+            let mut roots = RootCertStore::empty();
+            for cert in load_certs(&config.tls_ca_cert_file)? {
+                roots.add(cert)?;
+            }
+            let verifier = WebPkiClientVerifier::builder(Arc::new(roots));
+            let verifier = match auth {
+                TlsAuthClients::Optional => verifier.allow_unauthenticated(),
+                _ => verifier,
+            };
+            builder.with_client_cert_verifier(verifier.build()?)
+        }
+    };
+    let tls = builder
+        .with_single_cert(certs, key)
+        .context("Invalid certificate or private key")?;
+    Ok(Arc::new(tls))
+}
+
+fn load_certs(path: &str) -> Result<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .with_context(|| format!("Failed to load certificate: {}", path))?;
+    if certs.is_empty() {
+        bail!("No certificate found in {}", path);
+    }
+    Ok(certs)
+}
+
+impl Connection for TlsStream<TcpStream> {
+    fn client_addr(&self) -> io::Result<ClientAddr> {
+        self.get_ref().0.client_addr()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{serve, Listener};
+    use crate::Backend;
+    use rcgen::{
+        BasicConstraints, CertificateParams, CertifiedKey, ExtendedKeyUsagePurpose, IsCa, KeyPair,
+    };
+    use std::path::{Path, PathBuf};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio_rustls::rustls::pki_types::ServerName;
+    use tokio_rustls::TlsConnector;
+
+    // a CA plus a server and a client certificate it signed, written out as PEM files
+    struct Certs {
+        dir: PathBuf,
+        ca: CertifiedKey,
+        client: CertifiedKey,
+    }
+
+    impl Certs {
+        fn generate(dir: &Path) -> Result<Self> {
+            std::fs::create_dir_all(dir)?;
+            let mut params = CertificateParams::new(vec![])?;
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            let key_pair = KeyPair::generate()?;
+            let ca = CertifiedKey {
+                cert: params.self_signed(&key_pair)?,
+                key_pair,
+            };
+
+            let issue = |purpose: ExtendedKeyUsagePurpose| -> Result<CertifiedKey> {
+                let mut params = CertificateParams::new(vec!["localhost".to_string()])?;
+                params.extended_key_usages = vec![purpose];
+                let key_pair = KeyPair::generate()?;
+                let cert = params.signed_by(&key_pair, &ca.cert, &ca.key_pair)?;
+                Ok(CertifiedKey { cert, key_pair })
+            };
+            let server = issue(ExtendedKeyUsagePurpose::ServerAuth)?;
+            let client = issue(ExtendedKeyUsagePurpose::ClientAuth)?;
+
+            std::fs::write(dir.join("ca.crt"), ca.cert.pem())?;
+            std::fs::write(dir.join("server.crt"), server.cert.pem())?;
+            std::fs::write(dir.join("server.key"), server.key_pair.serialize_pem())?;
+            Ok(Certs {
+                dir: dir.to_path_buf(),
+                ca,
+                client,
+            })
+        }
+
+        fn config(&self) -> ServerConfig {
+            let path = |name: &str| self.dir.join(name).display().to_string();
+            ServerConfig {
+                tls_port: 1,
+                tls_cert_file: path("server.crt"),
+                tls_key_file: path("server.key"),
+                tls_ca_cert_file: path("ca.crt"),
+                ..Default::default()
+            }
+        }
+
+        fn connector(&self, with_cert: bool) -> Result<TlsConnector> {
+            let mut roots = RootCertStore::empty();
+            roots.add(self.ca.cert.der().clone())?;
+            let builder = rustls::ClientConfig::builder().with_root_certificates(roots);
+            let config = if with_cert {
+                let key = PrivateKeyDer::try_from(self.client.key_pair.serialize_der())
+                    .map_err(anyhow::Error::msg)?;
+                builder.with_client_auth_cert(vec![self.client.cert.der().clone()], key)?
+            } else {
+                builder.with_no_client_auth()
+            };
+            Ok(TlsConnector::from(Arc::new(config)))
+        }
+    }
+
+    async fn start(config: ServerConfig) -> Result<std::net::SocketAddr> {
+        let backend = Backend::new();
+        backend.set_tls(load(&config)?);
+        *backend.config_mut() = config;
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(serve(Listener::Tls(listener), backend));
+        Ok(addr)
+    }
+
+    // sends `request` and reads until the connection closes or `len` bytes arrived
+    async fn roundtrip(
+        connector: &TlsConnector,
+        addr: std::net::SocketAddr,
+        request: &[u8],
+        len: usize,
+    ) -> Result<Vec<u8>> {
+        let stream = TcpStream::connect(addr).await?;
+        let name = ServerName::try_from("localhost")?;
+        let mut stream = connector.connect(name, stream).await?;
+        stream.write_all(request).await?;
+        let mut reply = vec![];
+        while reply.len() < len {
+            let mut buf = [0; 256];
+            match stream.read(&mut buf).await? {
+                0 => break,
+                n => reply.extend_from_slice(&buf[..n]),
+            }
+        }
+        Ok(reply)
+    }
+
+    #[tokio::test]
+    async fn test_tls_mutual_auth() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("r-redis-tls-{}-mutual", std::process::id()));
+        let certs = Certs::generate(&dir)?;
+        let addr = start(certs.config()).await?;
+
+        let reply = roundtrip(&certs.connector(true)?, addr, b"set a 1\r\nget a\r\n", 12).await?;
+        assert_eq!(reply, b"+OK\r\n$1\r\n1\r\n");
+
+        // the server turns away clients without a certificate once the handshake completes
+        let reply = roundtrip(&certs.connector(false)?, addr, b"get a\r\n", 1).await;
+        assert!(reply.map_or(true, |reply| reply.is_empty()));
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tls_reload() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("r-redis-tls-{}-reload", std::process::id()));
+        let certs = Certs::generate(&dir)?;
+        let addr = start(certs.config()).await?;
+        let connector = certs.connector(true)?;
+
+        // a certificate that does not load leaves the current setup in place
+        let cert_file = certs.config().tls_cert_file;
+        let expected = format!(
+            "-ERR CONFIG SET failed (possibly related to argument 'tls-cert-file') - Unable to update TLS configuration. Check server logs.\r\n*2\r\n$13\r\ntls-cert-file\r\n${}\r\n{}\r\n",
+            cert_file.len(),
+            cert_file
+        );
+        let reply = roundtrip(
+            &connector,
+            addr,
+            b"config set tls-cert-file /nonexistent.crt\r\nconfig get tls-cert-file\r\n",
+            expected.len(),
+        )
+        .await?;
+        assert_eq!(String::from_utf8(reply)?, expected);
+
+        let reply = roundtrip(
+            &connector,
+            addr,
+            b"config set tls-auth-clients optional\r\n",
+            5,
+        )
+        .await?;
+        assert_eq!(reply, b"+OK\r\n");
+        let reply = roundtrip(&certs.connector(false)?, addr, b"get a\r\n", 5).await?;
+        assert_eq!(reply, b"$-1\r\n");
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_errors() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("r-redis-tls-{}-load", std::process::id()));
+        let certs = Certs::generate(&dir)?;
+        assert!(load(&certs.config()).is_ok());
+
+        let config = ServerConfig {
+            tls_ca_cert_file: String::new(),
+            ..certs.config()
+        };
+        assert!(load(&config).is_err());
+        let config = ServerConfig {
+            tls_auth_clients: TlsAuthClients::No,
+            ..config
+        };
+        assert!(load(&config).is_ok());
+        let config = ServerConfig {
+            tls_key_file: certs.config().tls_ca_cert_file,
+            ..certs.config()
+        };
+        assert!(load(&config).is_err());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -1,12 +1,12 @@
 use crate::{
-    extract_end_and_length, is_combine_complete, RespDecode, RespEncode, RespError, RespFrame,
+    aggregate_frame_length, encode_header, extract_end_and_length, RespDecode, RespEncode,
+    RespError, RespFrame, RespLimits,
 };
-use bytes::Buf;
+use bytes::{Buf, BytesMut};
 use lazy_static::lazy_static;
 use std::ops::{Deref, DerefMut};
 
 const NULL_ARRAY_ENCODE: &[u8] = b"*-1\r\n";
-const ARRAY_CAP: usize = 4096;
 
 lazy_static! {
     static ref NULL_ARRAY: RespArray = RespArray::null();
@@ -15,44 +15,46 @@ lazy_static! {
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct RespArray {
     pub(crate) data: Vec<RespFrame>,
+    null: bool,
 }
 
 // - array: "*<number-of-elements>\r\n<element-1>...<element-n>"
 //        - "*2\r\n$3\r\nget\r\n$5\r\nhello\r\n"
 impl RespEncode for RespArray {
-    fn encode(&self) -> Vec<u8> {
-        if self.is_empty() {
-            return NULL_ARRAY_ENCODE.to_vec();
+    fn encode_to(&self, buf: &mut BytesMut) {
+        if self.null {
+            buf.extend_from_slice(NULL_ARRAY_ENCODE);
+            return;
         }
 
-        let mut buf = Vec::with_capacity(ARRAY_CAP);
-        buf.extend_from_slice(format!("*{}\r\n", self.len()).as_bytes());
+        encode_header(buf, *Self::PREFIX, self.len());
         for frame in &self.data {
-            buf.extend_from_slice(&frame.encode());
+            frame.encode_to(buf);
         }
-        buf
     }
 }
 
 impl RespDecode for RespArray {
     const PREFIX: &'static u8 = &b'*';
-    fn decode(buf: &mut bytes::BytesMut) -> Result<Self, RespError> {
-        if buf == NULL_ARRAY_ENCODE {
+    fn decode_frame(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let (end, len) = extract_end_and_length(buf, *Self::PREFIX)?;
+        if len < 0 {
+            buf.advance(end + 2);
             return Ok(NULL_ARRAY.clone());
         }
 
-        let (end, len) = extract_end_and_length(buf, &[*Self::PREFIX])?;
-        is_combine_complete(buf, len)?;
-
         buf.advance(end + 2);
-        let mut frames = Vec::with_capacity(len);
+        let mut frames = Vec::with_capacity(len as usize);
         for _ in 0..len {
-            let v = RespFrame::decode(buf)?;
-            frames.push(v);
+            frames.push(RespFrame::decode_frame(buf)?);
         }
 
         Ok(RespArray::new(frames))
     }
+
+    fn expect_length(buf: &[u8], limits: &RespLimits) -> Result<usize, RespError> {
+        aggregate_frame_length(buf, *Self::PREFIX, 1, limits)
+    }
 }
 
 impl Deref for RespArray {
@@ -83,11 +85,21 @@ impl From<&[RespFrame]> for RespArray {
 
 impl RespArray {
     pub fn new(arr: Vec<RespFrame>) -> Self {
-        RespArray { data: arr }
+        RespArray {
+            data: arr,
+            null: false,
+        }
     }
 
     pub fn null() -> RespArray {
-        RespArray { data: vec![] }
+        RespArray {
+            data: vec![],
+            null: true,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.null
     }
 
     pub fn push(&mut self, frame: RespFrame) {
@@ -154,6 +166,13 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_resp_array_empty() {
+        let arr = RespArray::new(vec![]);
+        assert!(!arr.is_null());
+        assert_eq!(arr.encode(), b"*0\r\n");
+    }
+
     #[test]
     fn test_resp_array_null() -> Result<()> {
         let arr = NULL_ARRAY.clone();
@@ -165,4 +184,98 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_resp_array_decode_invalid_length() {
+        let cases: [(&[u8], &str); 3] = [
+            (b"*-5\r\n", "invalid multibulk length"),
+            (b"*x\r\n", "invalid multibulk length"),
+            (b"*1\r\n$-2\r\n", "invalid bulk length"),
+        ];
+        for (data, reason) in cases {
+            let mut buf = bytes::BytesMut::from(data);
+            assert_eq!(
+                RespArray::decode(&mut buf),
+                Err(RespError::InvalidLength(reason.into()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_resp_array_decode_crlf_in_bulk_string() -> Result<()> {
+        // the old CRLF counting treated the inner "\r\n" as a frame boundary
+        let mut buf = bytes::BytesMut::from(&b"*2\r\n$4\r\na\r\nb\r\n"[..]);
+        assert_eq!(
+            RespArray::decode(&mut buf).unwrap_err(),
+            RespError::NotComplete
+        );
+        assert_eq!(buf.len(), 14);
+
+        buf.extend_from_slice(b"$1\r\nc\r\n");
+        let ret = RespArray::decode(&mut buf)?;
+        assert_eq!(
+            ret,
+            vec![
+                BulkString::new("a\r\nb").into(),
+                BulkString::new("c").into()
+            ]
+            .into()
+        );
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_resp_array_decode_nested() -> Result<()> {
+        let data = b"*3\r\n*2\r\n:+1\r\n*1\r\n$-1\r\n*-1\r\n+tail\r\n+next\r\n";
+        assert_eq!(
+            RespArray::expect_length(data, &RespLimits::default())?,
+            data.len() - 7
+        );
+
+        // every strict prefix is incomplete and leaves the buffer untouched
+        for i in 0..data.len() - 7 {
+            let mut buf = bytes::BytesMut::from(&data[..i]);
+            assert_eq!(
+                RespArray::decode(&mut buf).unwrap_err(),
+                RespError::NotComplete
+            );
+            assert_eq!(buf.len(), i);
+        }
+
+        let mut buf = bytes::BytesMut::from(&data[..]);
+        let ret = RespArray::decode(&mut buf)?;
+        assert_eq!(
+            ret,
+            vec![
+                RespArray::new(vec![
+                    1.into(),
+                    RespArray::new(vec![BulkString::null().into()]).into()
+                ])
+                .into(),
+                RespArray::null().into(),
+                SimpleString::new("tail").into(),
+            ]
+            .into()
+        );
+        assert_eq!(&buf[..], b"+next\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_resp_array_decode_zero_copy() -> Result<()> {
+        let mut buf = bytes::BytesMut::from(&b"*2\r\n$3\r\nset\r\n*1\r\n$5\r\nvalue\r\n"[..]);
+        let start = buf.as_ptr() as usize;
+        let ret = RespArray::decode(&mut buf)?;
+
+        let RespFrame::Array(ref inner) = ret[1] else {
+            panic!("Expected Array");
+        };
+        let RespFrame::BulkString(ref value) = inner[0] else {
+            panic!("Expected BulkString");
+        };
+        assert_eq!(value.as_ptr() as usize, start + 21);
+        assert_eq!(ret.encode(), b"*2\r\n$3\r\nset\r\n*1\r\n$5\r\nvalue\r\n");
+        Ok(())
+    }
 }
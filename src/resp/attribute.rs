@@ -0,0 +1,106 @@
+use crate::resp::simple_string::SimpleString;
+use crate::{
+    aggregate_frame_length, encode_header, extract_end_and_length, RespDecode, RespEncode,
+    RespError, RespFrame, RespLimits,
+};
+use bytes::{Buf, BytesMut};
+use std::collections::BTreeMap;
+use std::ops::{Deref, DerefMut};
+
+/// Auxiliary data about the reply that follows it, laid out like a map.
+#[derive(Default, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RespAttribute(BTreeMap<SimpleString, RespFrame>);
+
+// - attribute: "|<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>"
+//        - "|1\r\n+ttl\r\n:+3600\r\n"
+impl RespEncode for RespAttribute {
+    fn encode_to(&self, buf: &mut BytesMut) {
+        encode_header(buf, *Self::PREFIX, self.len());
+        for (key, value) in &self.0 {
+            key.encode_to(buf);
+            value.encode_to(buf);
+        }
+    }
+}
+
+impl RespDecode for RespAttribute {
+    const PREFIX: &'static u8 = &b'|';
+    fn decode_frame(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let (end, len) = extract_end_and_length(buf, *Self::PREFIX)?;
+        if len < 0 {
+            return Err(RespError::InvalidFrameLength(len));
+        }
+
+        buf.advance(end + 2);
+        let mut attribute = RespAttribute::new();
+        for _ in 0..len {
+            let key = SimpleString::decode_frame(buf)?;
+            let value = RespFrame::decode_frame(buf)?;
+            attribute.insert(key, value);
+        }
+
+        Ok(attribute)
+    }
+
+    fn expect_length(buf: &[u8], limits: &RespLimits) -> Result<usize, RespError> {
+        aggregate_frame_length(buf, *Self::PREFIX, 2, limits)
+    }
+}
+
+impl Deref for RespAttribute {
+    type Target = BTreeMap<SimpleString, RespFrame>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for RespAttribute {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl IntoIterator for RespAttribute {
+    type Item = (SimpleString, RespFrame);
+    type IntoIter = std::collections::btree_map::IntoIter<SimpleString, RespFrame>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl RespAttribute {
+    pub fn new() -> Self {
+        RespAttribute(BTreeMap::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BulkString;
+    use anyhow::Result;
+
+    #[test]
+    fn test_resp_attribute_encode() {
+        let mut attribute = RespAttribute::new();
+        attribute.insert("ttl".into(), 3600.into());
+        attribute.insert("owner".into(), BulkString::new("alice").into());
+        assert_eq!(
+            attribute.encode(),
+            b"|2\r\n+owner\r\n$5\r\nalice\r\n+ttl\r\n:+3600\r\n"
+        );
+    }
+
+    #[test]
+    fn test_resp_attribute_decode() -> Result<()> {
+        let mut buf = BytesMut::from(&b"|2\r\n+owner\r\n$5\r\nalice\r\n+ttl\r\n:+3600\r\n"[..]);
+        let attribute = RespAttribute::decode(&mut buf)?;
+        let mut expected = RespAttribute::new();
+        expected.insert("ttl".into(), 3600.into());
+        expected.insert("owner".into(), BulkString::new("alice").into());
+        assert_eq!(attribute, expected);
+        Ok(())
+    }
+}
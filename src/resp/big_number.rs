@@ -0,0 +1,99 @@
+use crate::{
+    extract_simple_frame_data, simple_frame_length, RespDecode, RespEncode, RespError, RespLimits,
+};
+use bytes::BytesMut;
+use std::fmt::Display;
+use std::ops::Deref;
+
+/// An integer of arbitrary precision, kept in its decimal text form.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BigNumber(String);
+
+// - big number: "([+|-]<number>\r\n"
+//        - "(3492890328409238509324850943850943825024385\r\n"
+impl RespEncode for BigNumber {
+    fn encode_to(&self, buf: &mut BytesMut) {
+        buf.extend_from_slice(format!("({}\r\n", self).as_bytes());
+    }
+}
+
+impl RespDecode for BigNumber {
+    const PREFIX: &'static u8 = &b'(';
+    fn decode_frame(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let end = extract_simple_frame_data(buf, *Self::PREFIX)?;
+        let s = buf.split_to(end + 2);
+        let s = String::from_utf8_lossy(&s[1..end]);
+
+        BigNumber::try_from(s.as_ref())
+    }
+
+    fn expect_length(buf: &[u8], _limits: &RespLimits) -> Result<usize, RespError> {
+        simple_frame_length(buf, *Self::PREFIX)
+    }
+}
+
+impl TryFrom<&str> for BigNumber {
+    type Error = RespError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let digits = s.strip_prefix(['+', '-']).unwrap_or(s);
+        if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+            return Err(RespError::InvalidFrame(format!(
+                "Invalid big number: {}",
+                s
+            )));
+        }
+        Ok(BigNumber(s.to_string()))
+    }
+}
+
+impl From<i64> for BigNumber {
+    fn from(n: i64) -> Self {
+        BigNumber(n.to_string())
+    }
+}
+
+impl Deref for BigNumber {
+    type Target = String;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Display for BigNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_big_number_encode() -> Result<()> {
+        let n = BigNumber::try_from("3492890328409238509324850943850943825024385")?;
+        assert_eq!(
+            n.encode(),
+            b"(3492890328409238509324850943850943825024385\r\n"
+        );
+        assert_eq!(BigNumber::from(-1).encode(), b"(-1\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_big_number_decode() -> Result<()> {
+        let mut buf = BytesMut::from(&b"(-3492890328409238509324850943850943825024385\r\n"[..]);
+        let n = BigNumber::decode(&mut buf)?;
+        assert_eq!(n.as_str(), "-3492890328409238509324850943850943825024385");
+
+        let mut buf = BytesMut::from(&b"(12a\r\n"[..]);
+        assert!(BigNumber::decode(&mut buf).is_err());
+
+        let mut buf = BytesMut::from(&b"(-\r\n"[..]);
+        assert!(BigNumber::decode(&mut buf).is_err());
+        Ok(())
+    }
+}
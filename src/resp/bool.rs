@@ -1,24 +1,30 @@
-use crate::{extract_simple_frame_data, is_fixed_complete, RespDecode, RespEncode};
+use crate::{
+    extract_simple_frame_data, simple_frame_length, RespDecode, RespEncode, RespError, RespLimits,
+};
 use bytes::BytesMut;
 
 // - boolean: "#<t|f>\r\n"
 impl RespEncode for bool {
-    fn encode(&self) -> Vec<u8> {
-        format!("#{}\r\n", if *self { "t" } else { "f" })
-            .as_bytes()
-            .to_vec()
+    fn encode_to(&self, buf: &mut BytesMut) {
+        buf.extend_from_slice(if *self { b"#t\r\n" } else { b"#f\r\n" });
     }
 }
 
 impl RespDecode for bool {
     const PREFIX: &'static u8 = &b'#';
 
-    fn decode(buf: &mut BytesMut) -> Result<Self, crate::RespError> {
-        is_fixed_complete(buf)?;
-
-        let end = extract_simple_frame_data(buf, &[*Self::PREFIX])?;
+    fn decode_frame(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let end = extract_simple_frame_data(buf, *Self::PREFIX)?;
         let s = buf.split_to(end + 2);
-        Ok(s[1] == b't')
+        match &s[1..end] {
+            b"t" => Ok(true),
+            b"f" => Ok(false),
+            _ => Err(RespError::InvalidFrame(String::from_utf8_lossy(&s).into())),
+        }
+    }
+
+    fn expect_length(buf: &[u8], _limits: &RespLimits) -> Result<usize, RespError> {
+        simple_frame_length(buf, *Self::PREFIX)
     }
 }
 
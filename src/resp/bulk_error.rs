@@ -0,0 +1,90 @@
+use crate::{
+    blob_frame_length, encode_blob, extract_end_and_length, RespDecode, RespEncode, RespError,
+    RespLimits,
+};
+use bytes::{Bytes, BytesMut};
+use std::ops::Deref;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BulkError(Bytes);
+
+// - bulk error: "!<length>\r\n<error>\r\n"
+//        - "!21\r\nSYNTAX invalid syntax\r\n"
+impl RespEncode for BulkError {
+    fn encode_to(&self, buf: &mut BytesMut) {
+        encode_blob(buf, *Self::PREFIX, self);
+    }
+}
+
+impl RespDecode for BulkError {
+    const PREFIX: &'static u8 = &b'!';
+    fn decode_frame(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let (end, len) = extract_end_and_length(buf, *Self::PREFIX)?;
+        if len < 0 {
+            return Err(RespError::InvalidFrameLength(len));
+        }
+
+        let total = end + 2 + len as usize + 2;
+        let frame = buf.split_to(total).freeze();
+        Ok(BulkError(frame.slice(end + 2..total - 2)))
+    }
+
+    fn expect_length(buf: &[u8], limits: &RespLimits) -> Result<usize, RespError> {
+        blob_frame_length(buf, *Self::PREFIX, limits)
+    }
+}
+
+impl Deref for BulkError {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<&str> for BulkError {
+    fn from(s: &str) -> Self {
+        BulkError(Bytes::copy_from_slice(s.as_bytes()))
+    }
+}
+
+impl From<String> for BulkError {
+    fn from(s: String) -> Self {
+        BulkError(s.into())
+    }
+}
+
+impl BulkError {
+    pub fn new(data: impl Into<Vec<u8>>) -> Self {
+        BulkError(data.into().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_bulk_error_encode() {
+        let e: BulkError = "SYNTAX invalid syntax".into();
+        assert_eq!(e.encode(), b"!21\r\nSYNTAX invalid syntax\r\n");
+    }
+
+    #[test]
+    fn test_bulk_error_decode() -> Result<()> {
+        let mut buf = BytesMut::from(&b"!22\r\nSYNTAX invalid\r\nsyntax\r\n"[..]);
+        assert_eq!(
+            BulkError::decode(&mut buf)?,
+            "SYNTAX invalid\r\nsyntax".into()
+        );
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from(&b"!21\r\nSYNTAX"[..]);
+        assert_eq!(
+            BulkError::decode(&mut buf).unwrap_err(),
+            RespError::NotComplete
+        );
+        Ok(())
+    }
+}
@@ -1,5 +1,8 @@
-use crate::{extract_end_and_length, is_single_complete, RespDecode, RespEncode, RespError};
-use bytes::{Buf, BytesMut};
+use crate::{
+    blob_frame_length, encode_blob, extract_end_and_length, RespDecode, RespEncode, RespError,
+    RespLimits,
+};
+use bytes::{Buf, Bytes, BytesMut};
 use lazy_static::lazy_static;
 use std::ops::Deref;
 
@@ -10,39 +13,36 @@ lazy_static! {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct BulkString(Vec<u8>);
+pub struct BulkString(Bytes);
 
 // - bulk string: "$<length>\r\n<data>\r\n"
 impl RespEncode for BulkString {
-    fn encode(&self) -> Vec<u8> {
-        if self.to_vec() == NULL_BULK_STRING_ENCODE {
-            return NULL_BULK_STRING_ENCODE.to_vec();
+    fn encode_to(&self, buf: &mut BytesMut) {
+        if self.0 == NULL_BULK_STRING_ENCODE {
+            buf.extend_from_slice(NULL_BULK_STRING_ENCODE);
+            return;
         }
-
-        let length = self.len();
-        let mut buf = Vec::with_capacity(1 + 2 + 1 + 1 + 1 + length + 2);
-        buf.push(*Self::PREFIX);
-        buf.extend_from_slice(format!("{}\r\n", length).as_bytes());
-        buf.extend_from_slice(self);
-        buf.extend_from_slice(b"\r\n");
-        buf
+        encode_blob(buf, *Self::PREFIX, self);
     }
 }
 
 impl RespDecode for BulkString {
     const PREFIX: &'static u8 = &b'$';
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        if buf.to_vec() == NULL_BULK_STRING_ENCODE {
+    fn decode_frame(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let (end, len) = extract_end_and_length(buf, *Self::PREFIX)?;
+        if len < 0 {
+            buf.advance(end + 2);
             return Ok(NULL_BULK_STRING.clone());
         }
 
-        let (end, len) = extract_end_and_length(buf, &[*Self::PREFIX])?;
-        is_single_complete(buf, len)?;
+        let total = end + 2 + len as usize + 2;
+        // the value keeps pointing into the read buffer instead of being copied out of it
+        let frame = buf.split_to(total).freeze();
+        Ok(BulkString(frame.slice(end + 2..total - 2)))
+    }
 
-        buf.advance(end + 2);
-        let data = buf.split_to(len).to_vec();
-        buf.advance(2);
-        Ok(BulkString(data))
+    fn expect_length(buf: &[u8], limits: &RespLimits) -> Result<usize, RespError> {
+        blob_frame_length(buf, *Self::PREFIX, limits)
     }
 }
 
@@ -60,18 +60,24 @@ impl From<&str> for BulkString {
 
 impl From<&[u8]> for BulkString {
     fn from(data: &[u8]) -> Self {
-        BulkString(data.to_vec())
+        BulkString(Bytes::copy_from_slice(data))
     }
 }
 
 impl<const N: usize> From<&[u8; N]> for BulkString {
     fn from(data: &[u8; N]) -> Self {
-        BulkString(data.to_vec())
+        BulkString(Bytes::copy_from_slice(data))
+    }
+}
+
+impl From<Bytes> for BulkString {
+    fn from(data: Bytes) -> Self {
+        BulkString(data)
     }
 }
 
 impl Deref for BulkString {
-    type Target = Vec<u8>;
+    type Target = [u8];
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -86,7 +92,15 @@ impl AsRef<[u8]> for BulkString {
 
 impl BulkString {
     pub fn new(data: impl Into<Vec<u8>>) -> Self {
-        Self(data.into())
+        Self(data.into().into())
+    }
+
+    pub fn null() -> Self {
+        NULL_BULK_STRING.clone()
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.0 == NULL_BULK_STRING_ENCODE
     }
 }
 
@@ -120,4 +134,36 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_bulk_string_decode_invalid_terminator() {
+        let mut buf = BytesMut::from(&b"$3\r\nhelloworld\r\n"[..]);
+        assert!(matches!(
+            BulkString::decode(&mut buf),
+            Err(RespError::InvalidFrame(_))
+        ));
+    }
+
+    #[test]
+    fn test_bulk_string_decode_invalid_length() {
+        let mut buf = BytesMut::from(&b"$abc\r\n"[..]);
+        assert_eq!(
+            BulkString::decode(&mut buf).unwrap_err().to_string(),
+            "Protocol error: invalid bulk length"
+        );
+    }
+
+    #[test]
+    fn test_bulk_string_decode_zero_copy() -> Result<()> {
+        let mut buf = BytesMut::from(&b"$5\r\nhello\r\n$5\r\nworld\r\n"[..]);
+        let start = buf.as_ptr() as usize;
+        let ret = BulkString::decode(&mut buf)?;
+        assert_eq!(ret.as_ptr() as usize, start + 4);
+
+        // encoding appends to whatever the buffer already holds
+        let mut out = BytesMut::from(&b"+OK\r\n"[..]);
+        ret.encode_to(&mut out);
+        assert_eq!(&out[..], b"+OK\r\n$5\r\nhello\r\n");
+        Ok(())
+    }
 }
@@ -1,4 +1,8 @@
-use crate::{is_fixed_complete, RespDecode, RespEncode, RespError};
+use crate::{
+    extract_simple_frame_data, simple_frame_length, RespDecode, RespEncode, RespError, RespLimits,
+};
+use bytes::BytesMut;
+use std::ops::Deref;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct RespF64(f64);
@@ -19,8 +23,7 @@ impl Ord for RespF64 {
 
 // - double: ",[<+|->]<integral>[.<fractional>][<E|e>[sign]<exponent>]\r\n"
 impl RespEncode for RespF64 {
-    fn encode(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(32);
+    fn encode_to(&self, buf: &mut BytesMut) {
         let ret = if self.0.abs() > 1e+8 || self.0.abs() < 1e-8 {
             format!(",{:+e}\r\n", self.0)
         } else {
@@ -28,21 +31,30 @@ impl RespEncode for RespF64 {
             format!(",{}{}\r\n", sign, self.0)
         };
         buf.extend_from_slice(ret.as_bytes());
-        buf
     }
 }
 
 impl RespDecode for RespF64 {
     const PREFIX: &'static u8 = &b',';
-    fn decode(buf: &mut bytes::BytesMut) -> Result<Self, RespError> {
-        is_fixed_complete(buf)?;
-
-        let end = crate::extract_simple_frame_data(buf, &[*Self::PREFIX])?;
+    fn decode_frame(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let end = extract_simple_frame_data(buf, *Self::PREFIX)?;
         let s = buf.split_to(end + 2);
         let s = String::from_utf8_lossy(&s[1..end]);
 
         Ok(RespF64(s.parse()?))
     }
+
+    fn expect_length(buf: &[u8], _limits: &RespLimits) -> Result<usize, RespError> {
+        simple_frame_length(buf, *Self::PREFIX)
+    }
+}
+
+impl Deref for RespF64 {
+    type Target = f64;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
 }
 
 impl From<f64> for RespF64 {
@@ -2,7 +2,10 @@ use crate::resp::f64::RespF64;
 use crate::resp::map::RespMap;
 use crate::resp::set::RespSet;
 use crate::resp::simple_error::SimpleError;
-use crate::{BulkString, RespArray, RespDecode, RespNull, SimpleString};
+use crate::{
+    BigNumber, BulkError, BulkString, RespArray, RespAttribute, RespDecode, RespLimits, RespNull,
+    RespPush, SimpleString, VerbatimString,
+};
 use bytes::BytesMut;
 use enum_dispatch::enum_dispatch;
 use thiserror::Error;
@@ -20,6 +23,11 @@ pub enum RespFrame {
     Double(RespF64),
     Map(RespMap),
     Set(RespSet),
+    VerbatimString(VerbatimString),
+    BigNumber(BigNumber),
+    BulkError(BulkError),
+    Push(RespPush),
+    Attribute(RespAttribute),
 }
 
 #[derive(Error, Debug, PartialEq, Eq)]
@@ -34,6 +42,14 @@ pub enum RespError {
     NotComplete,
     #[error("Frame is empty")]
     Empty,
+    #[error("Protocol error: {0}")]
+    LimitExceeded(String),
+    #[error("Protocol error: {0}")]
+    InvalidInline(String),
+    #[error("Protocol error: {0}")]
+    InvalidLength(String),
+    #[error("Protocol error: expected '$', got '{0}'")]
+    ExpectedBulk(char),
     #[error("ParseIntError: {0}")]
     ParseIntError(#[from] std::num::ParseIntError),
     #[error("ParseFloatError: {0}")]
@@ -42,23 +58,52 @@ pub enum RespError {
 
 impl RespDecode for RespFrame {
     const PREFIX: &'static u8 = &b'_';
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let mut iter = buf.iter().peekable();
-        match iter.peek() {
-            Some(&SimpleString::PREFIX) => Ok(SimpleString::decode(buf)?.into()),
-            Some(&SimpleError::PREFIX) => Ok(SimpleError::decode(buf)?.into()),
-            Some(&i64::PREFIX) => Ok(i64::decode(buf)?.into()),
-            Some(&BulkString::PREFIX) => Ok(BulkString::decode(buf)?.into()),
-            Some(&RespArray::PREFIX) => Ok(RespArray::decode(buf)?.into()),
-            Some(&RespSet::PREFIX) => Ok(RespSet::decode(buf)?.into()),
-            Some(&RespMap::PREFIX) => Ok(RespMap::decode(buf)?.into()),
-            Some(&RespNull::PREFIX) => Ok(RespNull::decode(buf)?.into()),
-            Some(&bool::PREFIX) => Ok(bool::decode(buf)?.into()),
-            Some(&RespF64::PREFIX) => Ok(RespF64::decode(buf)?.into()),
-            Some(e) => Err(RespError::InvalidFrame(
-                format!("Invalid prefix: {}", e.to_ascii_lowercase()).to_string(),
-            )),
-            None => Err(RespError::Empty),
+    fn decode_frame(buf: &mut BytesMut) -> Result<Self, RespError> {
+        match &buf[0] {
+            SimpleString::PREFIX => Ok(SimpleString::decode_frame(buf)?.into()),
+            SimpleError::PREFIX => Ok(SimpleError::decode_frame(buf)?.into()),
+            i64::PREFIX => Ok(i64::decode_frame(buf)?.into()),
+            BulkString::PREFIX => Ok(BulkString::decode_frame(buf)?.into()),
+            RespArray::PREFIX => Ok(RespArray::decode_frame(buf)?.into()),
+            RespSet::PREFIX => Ok(RespSet::decode_frame(buf)?.into()),
+            RespMap::PREFIX => Ok(RespMap::decode_frame(buf)?.into()),
+            RespNull::PREFIX => Ok(RespNull::decode_frame(buf)?.into()),
+            bool::PREFIX => Ok(bool::decode_frame(buf)?.into()),
+            RespF64::PREFIX => Ok(RespF64::decode_frame(buf)?.into()),
+            VerbatimString::PREFIX => Ok(VerbatimString::decode_frame(buf)?.into()),
+            BigNumber::PREFIX => Ok(BigNumber::decode_frame(buf)?.into()),
+            BulkError::PREFIX => Ok(BulkError::decode_frame(buf)?.into()),
+            RespPush::PREFIX => Ok(RespPush::decode_frame(buf)?.into()),
+            RespAttribute::PREFIX => Ok(RespAttribute::decode_frame(buf)?.into()),
+            e => Err(RespError::InvalidFrame(format!(
+                "Invalid prefix: {}",
+                e.to_ascii_lowercase()
+            ))),
+        }
+    }
+
+    fn expect_length(buf: &[u8], limits: &RespLimits) -> Result<usize, RespError> {
+        match buf.first() {
+            Some(SimpleString::PREFIX) => SimpleString::expect_length(buf, limits),
+            Some(SimpleError::PREFIX) => SimpleError::expect_length(buf, limits),
+            Some(i64::PREFIX) => i64::expect_length(buf, limits),
+            Some(BulkString::PREFIX) => BulkString::expect_length(buf, limits),
+            Some(RespArray::PREFIX) => RespArray::expect_length(buf, limits),
+            Some(RespSet::PREFIX) => RespSet::expect_length(buf, limits),
+            Some(RespMap::PREFIX) => RespMap::expect_length(buf, limits),
+            Some(RespNull::PREFIX) => RespNull::expect_length(buf, limits),
+            Some(bool::PREFIX) => bool::expect_length(buf, limits),
+            Some(RespF64::PREFIX) => RespF64::expect_length(buf, limits),
+            Some(VerbatimString::PREFIX) => VerbatimString::expect_length(buf, limits),
+            Some(BigNumber::PREFIX) => BigNumber::expect_length(buf, limits),
+            Some(BulkError::PREFIX) => BulkError::expect_length(buf, limits),
+            Some(RespPush::PREFIX) => RespPush::expect_length(buf, limits),
+            Some(RespAttribute::PREFIX) => RespAttribute::expect_length(buf, limits),
+            Some(e) => Err(RespError::InvalidFrame(format!(
+                "Invalid prefix: {}",
+                e.to_ascii_lowercase()
+            ))),
+            None => Err(RespError::NotComplete),
         }
     }
 }
@@ -1,25 +1,29 @@
-use crate::{extract_simple_frame_data, is_fixed_complete, RespDecode, RespEncode, RespError};
+use crate::{
+    extract_simple_frame_data, simple_frame_length, RespDecode, RespEncode, RespError, RespLimits,
+};
 use bytes::BytesMut;
 
 // - integer: ":[<+|->]<value>\r\n"
 impl RespEncode for i64 {
-    fn encode(&self) -> Vec<u8> {
+    fn encode_to(&self, buf: &mut BytesMut) {
         let sign = if *self < 0 { "" } else { "+" };
-        format!(":{}{}\r\n", sign, self).as_bytes().to_vec()
+        buf.extend_from_slice(format!(":{}{}\r\n", sign, self).as_bytes());
     }
 }
 
 impl RespDecode for i64 {
     const PREFIX: &'static u8 = &b':';
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        is_fixed_complete(buf)?;
-
-        let end = extract_simple_frame_data(buf, &[*Self::PREFIX])?;
+    fn decode_frame(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let end = extract_simple_frame_data(buf, *Self::PREFIX)?;
         let s = buf.split_to(end + 2);
         let s = String::from_utf8_lossy(&s[1..end]);
 
         Ok(s.parse()?)
     }
+
+    fn expect_length(buf: &[u8], _limits: &RespLimits) -> Result<usize, RespError> {
+        simple_frame_length(buf, *Self::PREFIX)
+    }
 }
 
 #[cfg(test)]
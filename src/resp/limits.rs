@@ -0,0 +1,102 @@
+use crate::RespError;
+
+const MB: usize = 1024 * 1024;
+
+/// Bounds enforced while decoding frames from untrusted clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RespLimits {
+    /// The largest bulk string accepted, `proto-max-bulk-len` in redis.conf.
+    pub max_bulk_len: usize,
+    /// The largest number of elements announced by an aggregate header. Requests are flat
+    /// multibulks of bulk strings whose count is bounded apart, so this only applies to
+    /// frames decoded as a whole.
+    pub max_aggregate_len: usize,
+    /// How deep aggregates may nest inside each other, in frames other than requests.
+    pub max_depth: usize,
+    /// How much unparsed input a client may buffer, `client-query-buffer-limit` in redis.conf.
+    pub max_query_buffer: usize,
+}
+
+impl Default for RespLimits {
+    fn default() -> Self {
+        Self {
+            max_bulk_len: 512 * MB,
+            max_aggregate_len: i32::MAX as usize,
+            max_depth: 32,
+            max_query_buffer: 1024 * MB,
+        }
+    }
+}
+
+impl RespLimits {
+    /// The limits that apply to the elements of an aggregate.
+    pub fn nested(&self) -> Result<Self, RespError> {
+        if self.max_depth == 0 {
+            return Err(RespError::LimitExceeded(
+                "aggregate nesting too deep".into(),
+            ));
+        }
+        Ok(Self {
+            max_depth: self.max_depth - 1,
+            ..*self
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RespDecode, RespFrame};
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_resp_limits_bulk_len() {
+        let limits = RespLimits {
+            max_bulk_len: 4,
+            ..Default::default()
+        };
+        assert_eq!(
+            RespFrame::expect_length(b"$4\r\n", &limits),
+            Err(RespError::NotComplete)
+        );
+        assert_eq!(
+            RespFrame::expect_length(b"*1\r\n$5\r\n", &limits),
+            Err(RespError::LimitExceeded("invalid bulk length".into()))
+        );
+    }
+
+    #[test]
+    fn test_resp_limits_aggregate_len() {
+        // rejected from the header alone, before anything is allocated for the elements
+        let mut buf = BytesMut::from(&b"*999999999999\r\n"[..]);
+        assert_eq!(
+            RespFrame::decode(&mut buf),
+            Err(RespError::LimitExceeded("invalid multibulk length".into()))
+        );
+        assert_eq!(buf.len(), 15);
+    }
+
+    #[test]
+    fn test_resp_limits_depth() {
+        let limits = RespLimits {
+            max_depth: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            RespFrame::expect_length(b"*1\r\n*1\r\n:1\r\n", &limits),
+            Ok(12)
+        );
+        assert_eq!(
+            RespFrame::expect_length(b"*1\r\n*1\r\n*1\r\n:1\r\n", &limits),
+            Err(RespError::LimitExceeded(
+                "aggregate nesting too deep".into()
+            ))
+        );
+
+        let mut buf = BytesMut::from("*1\r\n".repeat(100_000).as_str());
+        assert!(matches!(
+            RespFrame::decode(&mut buf),
+            Err(RespError::LimitExceeded(_))
+        ));
+    }
+}
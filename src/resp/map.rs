@@ -1,5 +1,8 @@
 use crate::resp::simple_string::SimpleString;
-use crate::{is_combine_complete, RespDecode, RespEncode, RespError, RespFrame};
+use crate::{
+    aggregate_frame_length, encode_header, extract_end_and_length, RespDecode, RespEncode,
+    RespError, RespFrame, RespLimits,
+};
 use bytes::{Buf, BytesMut};
 use std::collections::BTreeMap;
 use std::ops::{Deref, DerefMut};
@@ -7,37 +10,39 @@ use std::ops::{Deref, DerefMut};
 #[derive(Default, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct RespMap(BTreeMap<SimpleString, RespFrame>);
 
-const MAP_CAP: usize = 4096;
-
 // - map: "%<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>"
 impl RespEncode for RespMap {
-    fn encode(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(MAP_CAP);
-        buf.extend_from_slice(&format!("%{}\r\n", self.len()).into_bytes());
+    fn encode_to(&self, buf: &mut BytesMut) {
+        encode_header(buf, *Self::PREFIX, self.len());
         for (key, value) in &self.0 {
-            buf.extend_from_slice(&key.encode());
-            buf.extend_from_slice(&value.encode());
+            key.encode_to(buf);
+            value.encode_to(buf);
         }
-        buf
     }
 }
 
 impl RespDecode for RespMap {
     const PREFIX: &'static u8 = &b'%';
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let (end, len) = crate::extract_end_and_length(buf, &[*Self::PREFIX])?;
-        is_combine_complete(buf, len)?;
+    fn decode_frame(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let (end, len) = extract_end_and_length(buf, *Self::PREFIX)?;
+        if len < 0 {
+            return Err(RespError::InvalidFrameLength(len));
+        }
 
         buf.advance(end + 2);
         let mut map = RespMap::new();
         for _ in 0..len {
-            let key = SimpleString::decode(buf)?;
-            let value = RespFrame::decode(buf)?;
+            let key = SimpleString::decode_frame(buf)?;
+            let value = RespFrame::decode_frame(buf)?;
             map.insert(key, value);
         }
 
         Ok(map)
     }
+
+    fn expect_length(buf: &[u8], limits: &RespLimits) -> Result<usize, RespError> {
+        aggregate_frame_length(buf, *Self::PREFIX, 2, limits)
+    }
 }
 
 impl Deref for RespMap {
@@ -54,6 +59,15 @@ impl DerefMut for RespMap {
     }
 }
 
+impl IntoIterator for RespMap {
+    type Item = (SimpleString, RespFrame);
+    type IntoIter = std::collections::btree_map::IntoIter<SimpleString, RespFrame>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
 impl From<BTreeMap<SimpleString, RespFrame>> for RespMap {
     fn from(map: BTreeMap<SimpleString, RespFrame>) -> Self {
         RespMap(map)
@@ -1,97 +1,181 @@
 pub use crate::resp::array::RespArray;
+pub use crate::resp::attribute::RespAttribute;
+pub use crate::resp::big_number::BigNumber;
+pub use crate::resp::bulk_error::BulkError;
 pub use crate::resp::bulk_string::BulkString;
-use crate::resp::f64::RespF64;
+pub use crate::resp::f64::RespF64;
 pub use crate::resp::frame::RespError;
 pub use crate::resp::frame::RespFrame;
-use crate::resp::map::RespMap;
+pub use crate::resp::limits::RespLimits;
+pub use crate::resp::map::RespMap;
 pub use crate::resp::null::RespNull;
-use crate::resp::set::RespSet;
-use crate::resp::simple_error::SimpleError;
+pub use crate::resp::protocol::RespProtocol;
+pub use crate::resp::push::RespPush;
+pub use crate::resp::set::RespSet;
+pub use crate::resp::simple_error::SimpleError;
 pub use crate::resp::simple_string::SimpleString;
-use bytes::BytesMut;
+pub use crate::resp::verbatim_string::VerbatimString;
+use bytes::{BufMut, BytesMut};
 use enum_dispatch::enum_dispatch;
 
 mod array;
+mod attribute;
+mod big_number;
 mod bool;
+mod bulk_error;
 mod bulk_string;
 mod f64;
 mod frame;
 mod i64;
+mod limits;
 mod map;
 mod null;
+mod protocol;
+mod push;
 mod set;
 mod simple_error;
 mod simple_string;
+mod verbatim_string;
 
 #[enum_dispatch]
 pub trait RespEncode {
-    fn encode(&self) -> Vec<u8>;
+    /// Appends the encoded frame to `buf`, nested frames write into the same buffer.
+    fn encode_to(&self, buf: &mut BytesMut);
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = BytesMut::new();
+        self.encode_to(&mut buf);
+        buf.to_vec()
+    }
 }
 
 pub trait RespDecode: Sized {
     const PREFIX: &'static u8;
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError>;
+
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        Self::decode_with_limits(buf, &RespLimits::default())
+    }
+
+    /// Decodes a frame once it is fully buffered. Nothing is consumed while the frame is
+    /// incomplete or when it breaks `limits`.
+    fn decode_with_limits(buf: &mut BytesMut, limits: &RespLimits) -> Result<Self, RespError> {
+        Self::expect_length(buf, limits)?;
+        Self::decode_frame(buf)
+    }
+
+    /// Decodes a frame that `expect_length` has already found complete.
+    fn decode_frame(buf: &mut BytesMut) -> Result<Self, RespError>;
+
+    /// Returns the exact number of bytes the frame at the start of `buf` occupies, without
+    /// consuming anything. Fails with `NotComplete` until the whole frame has arrived.
+    fn expect_length(buf: &[u8], limits: &RespLimits) -> Result<usize, RespError>;
 }
 
-pub fn extract_end_and_length(
-    buf: &mut BytesMut,
-    prefix: &[u8],
-) -> Result<(usize, usize), RespError> {
-    let end = extract_simple_frame_data(buf, prefix)?;
-    let s = String::from_utf8_lossy(&buf[1..end]);
-    let len = s.parse::<usize>()?;
-    Ok((end, len))
+const CRLF: &[u8] = b"\r\n";
+const CRLF_LEN: usize = CRLF.len();
+
+/// Writes the `<prefix><length>\r\n` header of blob and aggregate frames.
+pub fn encode_header(buf: &mut BytesMut, prefix: u8, len: usize) {
+    buf.put_u8(prefix);
+    buf.put_slice(len.to_string().as_bytes());
+    buf.put_slice(CRLF);
 }
 
-pub fn extract_simple_frame_data(buf: &mut BytesMut, prefix: &[u8]) -> Result<usize, RespError> {
-    if buf.len() < 3 {
-        return Err(RespError::NotComplete);
-    }
-    if !buf.starts_with(prefix) {
-        return Err(RespError::InvalidFrameType("SimpleError".into()));
-    }
+/// Writes a blob frame (`$`, `!`), the data is copied straight into `buf`.
+pub fn encode_blob(buf: &mut BytesMut, prefix: u8, data: &[u8]) {
+    encode_header(buf, prefix, data.len());
+    buf.put_slice(data);
+    buf.put_slice(CRLF);
+}
 
-    let mut end = 0;
-    for i in 0..buf.len() - 1 {
-        if buf[i] == b'\r' && buf[i + 1] == b'\n' {
-            end = i;
-            break;
+/// Returns the position of the `\r` that ends the first line of a frame with the given prefix.
+pub fn extract_simple_frame_data(buf: &[u8], prefix: u8) -> Result<usize, RespError> {
+    match buf.first() {
+        None => return Err(RespError::NotComplete),
+        Some(&c) if c != prefix => {
+            return Err(RespError::InvalidFrameType(format!(
+                "expect prefix {:?}, got {:?}",
+                prefix as char, c as char
+            )))
         }
+        _ => {}
     }
-    if end == 0 {
-        return Err(RespError::NotComplete);
+
+    buf.windows(CRLF_LEN)
+        .position(|w| w == CRLF)
+        .ok_or(RespError::NotComplete)
+}
+
+/// Parses the `<prefix><length>\r\n` header of a length prefixed frame, returning the end of
+/// the header line and the length, which is `-1` for the RESP2 null bulk string and array.
+pub fn extract_end_and_length(buf: &[u8], prefix: u8) -> Result<(usize, isize), RespError> {
+    let end = extract_simple_frame_data(buf, prefix)?;
+    // like redis, a bad length is reported without the details of why it could not be used
+    let invalid = || {
+        let kind = match prefix {
+            b'$' | b'!' | b'=' => "bulk",
+            _ => "multibulk",
+        };
+        RespError::InvalidLength(format!("invalid {} length", kind))
+    };
+    let len = std::str::from_utf8(&buf[1..end])
+        .ok()
+        .and_then(|s| s.parse::<isize>().ok())
+        .ok_or_else(invalid)?;
+    if len < -1 {
+        return Err(invalid());
     }
-    Ok(end)
+    Ok((end, len))
 }
 
-pub fn is_combine_complete(buf: &[u8], len: usize) -> Result<(), RespError> {
-    is_fixed_complete(buf)?;
+/// Length of a simple frame: the prefix, the line and its CRLF.
+pub fn simple_frame_length(buf: &[u8], prefix: u8) -> Result<usize, RespError> {
+    let end = extract_simple_frame_data(buf, prefix)?;
+    Ok(end + CRLF_LEN)
+}
 
-    let mut count = 0;
-    let mut i = 0;
-    while count < len {
-        if buf[i] == b'\r' && buf[i + 1] == b'\n' {
-            count += 1;
-        }
-        i += 1;
+/// Length of a blob frame (`$`, `!`, `=`), checking that the data is terminated by CRLF.
+pub fn blob_frame_length(buf: &[u8], prefix: u8, limits: &RespLimits) -> Result<usize, RespError> {
+    let (end, len) = extract_end_and_length(buf, prefix)?;
+    if len < 0 {
+        return Ok(end + CRLF_LEN);
+    }
+    if len as usize > limits.max_bulk_len {
+        return Err(RespError::LimitExceeded("invalid bulk length".into()));
     }
-    // The content in the item may contain \r\n, so here it is >=
-    (count >= len).then_some(()).ok_or(RespError::NotComplete)
-}
 
-pub fn is_single_complete(buf: &[u8], len: usize) -> Result<(), RespError> {
-    is_fixed_complete(buf)?;
-    (buf.len() >= len + 4 + 2)
-        .then_some(())
-        .ok_or(RespError::NotComplete)
+    let total = end + CRLF_LEN + len as usize + CRLF_LEN;
+    if buf.len() < total {
+        return Err(RespError::NotComplete);
+    }
+    if &buf[total - CRLF_LEN..total] != CRLF {
+        return Err(RespError::InvalidFrame(
+            "blob data is not terminated by CRLF".into(),
+        ));
+    }
+    Ok(total)
 }
 
-pub fn is_fixed_complete(buf: &[u8]) -> Result<(), RespError> {
-    (buf.len() > 2)
-        .then_some(())
-        .ok_or(RespError::NotComplete)?;
+/// Length of an aggregate frame whose header announces `len` entries made of `per_entry`
+/// frames each, e.g. 1 for arrays and 2 for maps. Nested aggregates are walked recursively.
+pub fn aggregate_frame_length(
+    buf: &[u8],
+    prefix: u8,
+    per_entry: usize,
+    limits: &RespLimits,
+) -> Result<usize, RespError> {
+    let (end, len) = extract_end_and_length(buf, prefix)?;
+    let mut total = end + CRLF_LEN;
+    if len < 0 {
+        return Ok(total);
+    }
+    if len as usize > limits.max_aggregate_len {
+        return Err(RespError::LimitExceeded("invalid multibulk length".into()));
+    }
 
-    buf.ends_with(b"\r\n")
-        .then_some(())
-        .ok_or(RespError::NotComplete)
+    let limits = limits.nested()?;
+    for _ in 0..len as usize * per_entry {
+        total += RespFrame::expect_length(&buf[total..], &limits)?;
+    }
+    Ok(total)
 }
@@ -1,29 +1,33 @@
-use crate::{is_fixed_complete, RespDecode, RespEncode, RespError};
+use crate::{
+    extract_simple_frame_data, simple_frame_length, RespDecode, RespEncode, RespError, RespLimits,
+};
+use bytes::BytesMut;
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct RespNull;
 
 // - null: "_\r\n"
 impl RespEncode for RespNull {
-    fn encode(&self) -> Vec<u8> {
-        "_\r\n".as_bytes().to_vec()
+    fn encode_to(&self, buf: &mut BytesMut) {
+        buf.extend_from_slice(b"_\r\n");
     }
 }
 
 impl RespDecode for RespNull {
     const PREFIX: &'static u8 = &b'_';
-    fn decode(buf: &mut bytes::BytesMut) -> Result<Self, crate::RespError> {
-        is_fixed_complete(buf)?;
-
-        let s = buf.split_to(3).to_vec();
-        if s != b"_\r\n" {
-            Err(RespError::InvalidFrame(
-                String::from_utf8_lossy(s.as_slice()).into(),
-            ))
+    fn decode_frame(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let end = extract_simple_frame_data(buf, *Self::PREFIX)?;
+        let s = buf.split_to(end + 2);
+        if end != 1 {
+            Err(RespError::InvalidFrame(String::from_utf8_lossy(&s).into()))
         } else {
             Ok(RespNull)
         }
     }
+
+    fn expect_length(buf: &[u8], _limits: &RespLimits) -> Result<usize, RespError> {
+        simple_frame_length(buf, *Self::PREFIX)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,160 @@
+use crate::{BulkString, RespArray, RespFrame, SimpleError, SimpleString};
+
+/// The protocol version negotiated with HELLO. Connections start out speaking RESP2.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RespProtocol {
+    #[default]
+    Resp2,
+    Resp3,
+}
+
+impl RespProtocol {
+    pub fn version(&self) -> i64 {
+        match self {
+            RespProtocol::Resp2 => 2,
+            RespProtocol::Resp3 => 3,
+        }
+    }
+}
+
+impl TryFrom<i64> for RespProtocol {
+    type Error = i64;
+
+    fn try_from(version: i64) -> Result<Self, Self::Error> {
+        match version {
+            2 => Ok(RespProtocol::Resp2),
+            3 => Ok(RespProtocol::Resp3),
+            v => Err(v),
+        }
+    }
+}
+
+impl RespFrame {
+    /// Rewrites RESP3-only frames, including those nested in aggregates, into the closest
+    /// RESP2 equivalent so that RESP2 clients can parse them:
+    /// - map: flat array of keys and values
+    /// - set: array
+    /// - boolean: integer 1 or 0
+    /// - double: bulk string
+    /// - null: null bulk string
+    /// - verbatim string and big number: bulk string
+    /// - bulk error: simple error, with line breaks replaced by spaces
+    /// - push: array
+    /// - attribute: flat array of keys and values, RESP2 has no out-of-band metadata
+    pub fn into_resp2(self) -> RespFrame {
+        match self {
+            RespFrame::Array(array) if array.is_null() => array.into(),
+            RespFrame::Array(array) => {
+                RespArray::new(array.data.into_iter().map(|f| f.into_resp2()).collect()).into()
+            }
+            RespFrame::Map(map) => flatten_resp2(map.into_iter()),
+            RespFrame::Attribute(attribute) => flatten_resp2(attribute.into_iter()),
+            RespFrame::Set(set) => {
+                RespArray::new(set.into_iter().map(|f| f.into_resp2()).collect()).into()
+            }
+            RespFrame::Boolean(b) => RespFrame::Integer(b as i64),
+            RespFrame::Double(d) => BulkString::from(d.to_string()).into(),
+            RespFrame::Null(_) => BulkString::null().into(),
+            RespFrame::VerbatimString(s) => BulkString::from(s.into_data()).into(),
+            RespFrame::BigNumber(n) => BulkString::from(n.to_string()).into(),
+            RespFrame::BulkError(e) => {
+                let e = String::from_utf8_lossy(&e).replace(['\r', '\n'], " ");
+                SimpleError::new(e).into()
+            }
+            RespFrame::Push(push) => {
+                RespArray::new(push.into_iter().map(|f| f.into_resp2()).collect()).into()
+            }
+            frame => frame,
+        }
+    }
+}
+
+// [key-1, value-1, ..., key-n, value-n]
+fn flatten_resp2(entries: impl ExactSizeIterator<Item = (SimpleString, RespFrame)>) -> RespFrame {
+    let mut data = Vec::with_capacity(entries.len() * 2);
+    for (key, value) in entries {
+        data.push(key.into());
+        data.push(value.into_resp2());
+    }
+    RespArray::new(data).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resp::f64::RespF64;
+    use crate::resp::map::RespMap;
+    use crate::resp::set::RespSet;
+    use crate::{
+        BigNumber, BulkError, RespAttribute, RespEncode, RespNull, RespPush, VerbatimString,
+    };
+
+    #[test]
+    fn test_resp_protocol_version() {
+        assert_eq!(RespProtocol::default(), RespProtocol::Resp2);
+        assert_eq!(RespProtocol::try_from(3), Ok(RespProtocol::Resp3));
+        assert_eq!(RespProtocol::try_from(4), Err(4));
+        assert_eq!(RespProtocol::Resp3.version(), 3);
+    }
+
+    #[test]
+    fn test_into_resp2_scalars() {
+        assert_eq!(RespFrame::from(true).into_resp2(), RespFrame::Integer(1));
+        assert_eq!(RespFrame::from(false).into_resp2(), RespFrame::Integer(0));
+        assert_eq!(
+            RespFrame::from(RespF64::new(2.5)).into_resp2(),
+            BulkString::from("2.5").into()
+        );
+        assert_eq!(RespFrame::from(RespNull).into_resp2().encode(), b"$-1\r\n");
+        assert_eq!(
+            RespFrame::from(RespArray::null()).into_resp2().encode(),
+            b"*-1\r\n"
+        );
+        assert_eq!(
+            RespFrame::from(SimpleString::new("OK")).into_resp2(),
+            SimpleString::new("OK").into()
+        );
+    }
+
+    #[test]
+    fn test_into_resp2_aggregates() {
+        let mut map = RespMap::new();
+        map.insert("proto".into(), 2.into());
+        map.insert("ok".into(), true.into());
+        let mut set = RespSet::new();
+        set.insert(RespNull.into());
+        let frame: RespFrame = RespArray::new(vec![map.into(), set.into()]).into();
+
+        assert_eq!(
+            frame.into_resp2().encode(),
+            b"*2\r\n*4\r\n+ok\r\n:+1\r\n+proto\r\n:+2\r\n*1\r\n$-1\r\n"
+        );
+    }
+
+    #[test]
+    fn test_into_resp2_resp3_only_types() -> Result<(), crate::RespError> {
+        assert_eq!(
+            RespFrame::from(VerbatimString::text("hello")).into_resp2(),
+            BulkString::from("hello").into()
+        );
+        assert_eq!(
+            RespFrame::from(BigNumber::try_from("12345678901234567890")?).into_resp2(),
+            BulkString::from("12345678901234567890").into()
+        );
+        assert_eq!(
+            RespFrame::from(BulkError::from("ERR bad\r\nthing")).into_resp2(),
+            SimpleError::new("ERR bad  thing").into()
+        );
+        assert_eq!(
+            RespFrame::from(RespPush::new(vec![true.into()])).into_resp2(),
+            RespArray::new(vec![1.into()]).into()
+        );
+        let mut attribute = RespAttribute::new();
+        attribute.insert("ttl".into(), 10.into());
+        assert_eq!(
+            RespFrame::from(attribute).into_resp2().encode(),
+            b"*2\r\n+ttl\r\n:+10\r\n"
+        );
+        Ok(())
+    }
+}
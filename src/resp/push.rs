@@ -0,0 +1,113 @@
+use crate::{
+    aggregate_frame_length, encode_header, extract_end_and_length, RespDecode, RespEncode,
+    RespError, RespFrame, RespLimits,
+};
+use bytes::{Buf, BytesMut};
+use std::ops::{Deref, DerefMut};
+
+/// Out-of-band data sent by the server, such as Pub/Sub messages.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RespPush(Vec<RespFrame>);
+
+// - push: "><number-of-elements>\r\n<element-1>...<element-n>"
+//        - ">3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$5\r\nhello\r\n"
+impl RespEncode for RespPush {
+    fn encode_to(&self, buf: &mut BytesMut) {
+        encode_header(buf, *Self::PREFIX, self.len());
+        for frame in &self.0 {
+            frame.encode_to(buf);
+        }
+    }
+}
+
+impl RespDecode for RespPush {
+    const PREFIX: &'static u8 = &b'>';
+    fn decode_frame(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let (end, len) = extract_end_and_length(buf, *Self::PREFIX)?;
+        if len < 0 {
+            return Err(RespError::InvalidFrameLength(len));
+        }
+
+        buf.advance(end + 2);
+        let mut frames = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            frames.push(RespFrame::decode_frame(buf)?);
+        }
+
+        Ok(RespPush(frames))
+    }
+
+    fn expect_length(buf: &[u8], limits: &RespLimits) -> Result<usize, RespError> {
+        aggregate_frame_length(buf, *Self::PREFIX, 1, limits)
+    }
+}
+
+impl Deref for RespPush {
+    type Target = Vec<RespFrame>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for RespPush {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<Vec<RespFrame>> for RespPush {
+    fn from(frames: Vec<RespFrame>) -> Self {
+        RespPush(frames)
+    }
+}
+
+impl IntoIterator for RespPush {
+    type Item = RespFrame;
+    type IntoIter = std::vec::IntoIter<RespFrame>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl RespPush {
+    pub fn new(frames: Vec<RespFrame>) -> Self {
+        RespPush(frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BulkString;
+    use anyhow::Result;
+
+    #[test]
+    fn test_resp_push_encode() {
+        let push = RespPush::new(vec![
+            BulkString::new("message").into(),
+            BulkString::new("news").into(),
+            BulkString::new("hello").into(),
+        ]);
+        assert_eq!(
+            push.encode(),
+            b">3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$5\r\nhello\r\n"
+        );
+    }
+
+    #[test]
+    fn test_resp_push_decode() -> Result<()> {
+        let mut buf = BytesMut::from(&b">3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$5\r\nhello\r\n"[..]);
+        let push = RespPush::decode(&mut buf)?;
+        assert_eq!(
+            push,
+            RespPush::new(vec![
+                BulkString::new("message").into(),
+                BulkString::new("news").into(),
+                BulkString::new("hello").into(),
+            ])
+        );
+        Ok(())
+    }
+}
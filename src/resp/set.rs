@@ -1,5 +1,6 @@
 use crate::{
-    extract_end_and_length, is_combine_complete, RespDecode, RespEncode, RespError, RespFrame,
+    aggregate_frame_length, encode_header, extract_end_and_length, RespDecode, RespEncode,
+    RespError, RespFrame, RespLimits,
 };
 use bytes::{Buf, BytesMut};
 use std::collections::BTreeSet;
@@ -9,35 +10,36 @@ use std::ops::{Deref, DerefMut};
 #[derive(Debug, Clone, PartialEq)]
 pub struct RespSet(BTreeSet<RespFrame>);
 
-const SET_CAP: usize = 4096;
-
 // - set: "~<number-of-elements>\r\n<element-1>...<element-n>"
 impl RespEncode for RespSet {
-    fn encode(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(SET_CAP);
-        buf.extend_from_slice(format!("~{}\r\n", self.len()).as_bytes());
+    fn encode_to(&self, buf: &mut BytesMut) {
+        encode_header(buf, *Self::PREFIX, self.len());
         for frame in &self.0 {
-            buf.extend_from_slice(&frame.encode());
+            frame.encode_to(buf);
         }
-        buf
     }
 }
 
 impl RespDecode for RespSet {
     const PREFIX: &'static u8 = &b'~';
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let (end, len) = extract_end_and_length(buf, &[*Self::PREFIX])?;
-        is_combine_complete(buf, len)?;
+    fn decode_frame(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let (end, len) = extract_end_and_length(buf, *Self::PREFIX)?;
+        if len < 0 {
+            return Err(RespError::InvalidFrameLength(len));
+        }
 
         buf.advance(end + 2);
         let mut frames = BTreeSet::new();
         for _ in 0..len {
-            let v = RespFrame::decode(buf)?;
-            frames.insert(v);
+            frames.insert(RespFrame::decode_frame(buf)?);
         }
 
         Ok(RespSet(frames))
     }
+
+    fn expect_length(buf: &[u8], limits: &RespLimits) -> Result<usize, RespError> {
+        aggregate_frame_length(buf, *Self::PREFIX, 1, limits)
+    }
 }
 
 impl Hash for RespSet {
@@ -75,6 +77,15 @@ impl DerefMut for RespSet {
     }
 }
 
+impl IntoIterator for RespSet {
+    type Item = RespFrame;
+    type IntoIter = std::collections::btree_set::IntoIter<RespFrame>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
 impl From<BTreeSet<RespFrame>> for RespSet {
     fn from(set: BTreeSet<RespFrame>) -> Self {
         RespSet(set)
@@ -1,4 +1,6 @@
-use crate::{extract_simple_frame_data, is_fixed_complete, RespDecode, RespEncode, RespError};
+use crate::{
+    extract_simple_frame_data, simple_frame_length, RespDecode, RespEncode, RespError, RespLimits,
+};
 use bytes::BytesMut;
 use std::fmt::Display;
 use std::ops::Deref;
@@ -8,23 +10,24 @@ pub struct SimpleString(String);
 
 // - simple string: "+OK\r\n"
 impl RespEncode for SimpleString {
-    fn encode(&self) -> Vec<u8> {
-        format!("+{}\r\n", self).as_bytes().to_vec()
+    fn encode_to(&self, buf: &mut BytesMut) {
+        buf.extend_from_slice(format!("+{}\r\n", self).as_bytes());
     }
 }
 
 impl RespDecode for SimpleString {
     const PREFIX: &'static u8 = &b'+';
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        is_fixed_complete(buf)?;
-
-        let end = extract_simple_frame_data(buf, &[*Self::PREFIX])?;
-
+    fn decode_frame(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let end = extract_simple_frame_data(buf, *Self::PREFIX)?;
         let s = buf.split_to(end + 2);
         let s = String::from_utf8_lossy(&s[1..end]);
 
         Ok(SimpleString(s.into()))
     }
+
+    fn expect_length(buf: &[u8], _limits: &RespLimits) -> Result<usize, RespError> {
+        simple_frame_length(buf, *Self::PREFIX)
+    }
 }
 
 impl Deref for SimpleString {
@@ -0,0 +1,113 @@
+use crate::{
+    blob_frame_length, encode_header, extract_end_and_length, RespDecode, RespEncode, RespError,
+    RespLimits,
+};
+use bytes::{Bytes, BytesMut};
+use std::ops::Deref;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct VerbatimString {
+    format: [u8; 3],
+    data: Bytes,
+}
+
+// - verbatim string: "=<length>\r\n<encoding>:<data>\r\n"
+//        - "=15\r\ntxt:Some string\r\n", the length covers the encoding and the colon
+impl RespEncode for VerbatimString {
+    fn encode_to(&self, buf: &mut BytesMut) {
+        encode_header(buf, *Self::PREFIX, self.data.len() + 4);
+        buf.extend_from_slice(&self.format);
+        buf.extend_from_slice(b":");
+        buf.extend_from_slice(&self.data);
+        buf.extend_from_slice(b"\r\n");
+    }
+}
+
+impl RespDecode for VerbatimString {
+    const PREFIX: &'static u8 = &b'=';
+    fn decode_frame(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let (end, len) = extract_end_and_length(buf, *Self::PREFIX)?;
+        if len < 4 || buf[end + 2 + 3] != b':' {
+            return Err(RespError::InvalidFrame(
+                "Verbatim string without encoding".into(),
+            ));
+        }
+
+        let total = end + 2 + len as usize + 2;
+        let frame = buf.split_to(total).freeze();
+        let start = end + 2;
+        Ok(VerbatimString {
+            format: [frame[start], frame[start + 1], frame[start + 2]],
+            data: frame.slice(start + 4..total - 2),
+        })
+    }
+
+    fn expect_length(buf: &[u8], limits: &RespLimits) -> Result<usize, RespError> {
+        blob_frame_length(buf, *Self::PREFIX, limits)
+    }
+}
+
+impl Deref for VerbatimString {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl VerbatimString {
+    pub fn new(format: [u8; 3], data: impl Into<Vec<u8>>) -> Self {
+        VerbatimString {
+            format,
+            data: data.into().into(),
+        }
+    }
+
+    /// Plain text, `txt`.
+    pub fn text(data: impl Into<Vec<u8>>) -> Self {
+        Self::new(*b"txt", data)
+    }
+
+    /// Markdown, `mkd`.
+    pub fn markdown(data: impl Into<Vec<u8>>) -> Self {
+        Self::new(*b"mkd", data)
+    }
+
+    pub fn format(&self) -> &[u8; 3] {
+        &self.format
+    }
+
+    pub fn into_data(self) -> Bytes {
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_verbatim_string_encode() {
+        let s = VerbatimString::text("Some string");
+        assert_eq!(s.encode(), b"=15\r\ntxt:Some string\r\n");
+    }
+
+    #[test]
+    fn test_verbatim_string_decode() -> Result<()> {
+        let mut buf = BytesMut::from(&b"=16\r\nmkd:Some\r\nstr"[..]);
+        let s = VerbatimString::decode(&mut buf);
+        assert_eq!(s.unwrap_err(), RespError::NotComplete);
+
+        let mut buf = BytesMut::from(&b"=16\r\nmkd:Some\r\nstring\r\n"[..]);
+        let s = VerbatimString::decode(&mut buf)?;
+        assert_eq!(s, VerbatimString::markdown("Some\r\nstring"));
+        assert_eq!(s.format(), b"mkd");
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from(&b"=3\r\ntxt\r\n"[..]);
+        let s = VerbatimString::decode(&mut buf);
+        assert!(matches!(s.unwrap_err(), RespError::InvalidFrame(_)));
+        Ok(())
+    }
+}